@@ -1,30 +1,99 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Decimal, StdResult, Storage};
+use cosmwasm_std::{
+    ensure, Addr, Api, Checksum, Decimal, QuerierWrapper, StdError, StdResult, Storage,
+};
 use cw_storage_plus::Item;
+use kujira::KujiraQuery;
 use kujira_ghost::receipt_vault::ConfigResponse as GhostConfig;
 use monetary::Denom;
 use unstake::{
     adapter::Adapter,
     broker::Broker,
-    controller::{ConfigResponse, InstantiateMsg},
-    denoms::{Ask, Base, Debt, Rcpt},
+    controller::{
+        AskAsset, ConfigResponse, ConfigUpdate, FeeRecipient, FeeTier, InstantiateMsg,
+        KeeperReward, Operation,
+    },
+    denoms::{Base, Debt, Rcpt},
+    ownership::PendingOwner,
+    timelock, ContractError,
 };
 
 static CONFIG: Item<Config> = Item::new("config");
 
+/// The max interest rate assumed when neither the vault nor the owner provide one
+const DEFAULT_VAULT_MAX_INTEREST: Decimal = Decimal::percent(300);
+
+/// The highest fallback max interest rate that can be configured
+pub const MAX_VAULT_MAX_INTEREST: Decimal = Decimal::percent(1000);
+
+/// The largest daily change in redemption rate accepted, unless configured otherwise
+const DEFAULT_MAX_RATE_CHANGE: Decimal = Decimal::percent(1);
+
+/// The highest daily change in redemption rate that can be configured
+pub const MAX_MAX_RATE_CHANGE: Decimal = Decimal::percent(100);
+
+/// The highest protocol fee that can be configured
+pub const MAX_PROTOCOL_FEE: Decimal = Decimal::percent(50);
+
 #[cw_serde]
 pub struct Config {
     pub owner: Addr,
     pub protocol_fee: Decimal,
     pub protocol_fee_address: Addr,
     pub delegate_code_id: u64,
+    /// The checksum of `delegate_code_id`, recorded when the code id is set
+    #[serde(default)]
+    pub delegate_code_checksum: Option<Checksum>,
     pub reserve_address: Addr,
     pub vault_address: Addr,
     pub offer_denom: Denom<Base>,
-    pub ask_denom: Denom<Ask>,
+    pub ask_denom: AskAsset,
     pub debt_denom: Denom<Debt>,
     pub ghost_denom: Denom<Rcpt>,
     pub adapter: Adapter,
+    /// Fallback max interest rate, used when the GHOST vault doesn't publish its interest curve
+    #[serde(default = "default_vault_max_interest")]
+    pub vault_max_interest: Decimal,
+    /// The reward paid to the address that triggers the completion of a delegate
+    #[serde(default)]
+    pub keeper_reward: KeeperReward,
+    /// The largest change in a provider's redemption rate accepted per day
+    #[serde(default = "default_max_rate_change")]
+    pub max_rate_change: Decimal,
+    /// The address that can pause operations, alongside the owner
+    #[serde(default)]
+    pub guardian: Option<Addr>,
+    #[serde(default)]
+    pub paused: Vec<Operation>,
+    /// An ownership transfer awaiting acceptance
+    #[serde(default)]
+    pub pending_owner: Option<PendingOwner>,
+    /// The minimum notice in seconds given of a `Change`
+    #[serde(default = "default_timelock")]
+    pub timelock: u64,
+    /// The recipients the protocol fee is split between. `protocol_fee_address` receives all of
+    /// it when empty
+    #[serde(default)]
+    pub fee_recipients: Vec<FeeRecipient>,
+    /// The share of the protocol fee paid to the referrer of an Unstake, up to the referrer's tier
+    #[serde(default)]
+    pub referral_share: Decimal,
+    /// The discounts offered to qualifying Unstakers. The first tier an Unstaker qualifies for
+    /// is applied
+    #[serde(default)]
+    pub fee_tiers: Vec<FeeTier>,
+}
+
+fn default_vault_max_interest() -> Decimal {
+    DEFAULT_VAULT_MAX_INTEREST
+}
+
+fn default_max_rate_change() -> Decimal {
+    DEFAULT_MAX_RATE_CHANGE
+}
+
+fn default_timelock() -> u64 {
+    timelock::DEFAULT_DELAY
 }
 
 impl Config {
@@ -34,6 +103,7 @@ impl Config {
             protocol_fee: msg.protocol_fee,
             protocol_fee_address: msg.protocol_fee_address,
             delegate_code_id: msg.delegate_code_id,
+            delegate_code_checksum: None,
             reserve_address: msg.reserve_address,
             vault_address: msg.vault_address,
             offer_denom: msg.offer_denom,
@@ -41,6 +111,16 @@ impl Config {
             debt_denom: Denom::new(ghost_cfg.debt_token_denom),
             ghost_denom: Denom::new(ghost_cfg.receipt_denom),
             adapter: msg.adapter,
+            vault_max_interest: msg.vault_max_interest.unwrap_or(DEFAULT_VAULT_MAX_INTEREST),
+            keeper_reward: msg.keeper_reward.unwrap_or_default(),
+            max_rate_change: msg.max_rate_change.unwrap_or(DEFAULT_MAX_RATE_CHANGE),
+            guardian: msg.guardian,
+            paused: vec![],
+            pending_owner: None,
+            timelock: msg.timelock.unwrap_or(timelock::DEFAULT_DELAY),
+            fee_recipients: msg.fee_recipients.unwrap_or_default(),
+            referral_share: Decimal::zero(),
+            fee_tiers: vec![],
         }
     }
     pub fn load(storage: &dyn Storage) -> StdResult<Self> {
@@ -51,35 +131,128 @@ impl Config {
         CONFIG.save(storage, self)
     }
 
-    pub fn update(
-        &mut self,
-        owner: Option<Addr>,
-        protocol_fee: Option<Decimal>,
-        protocol_fee_address: Option<Addr>,
-        delegate_code_id: Option<u64>,
-    ) {
-        if let Some(owner) = owner {
-            self.owner = owner
+    pub fn update(&mut self, update: ConfigUpdate) {
+        let ConfigUpdate {
+            keeper_reward,
+            max_rate_change,
+            guardian,
+            referral_share,
+        } = update;
+        if let Some(keeper_reward) = keeper_reward {
+            self.keeper_reward = keeper_reward
+        }
+
+        if let Some(max_rate_change) = max_rate_change {
+            self.max_rate_change = max_rate_change
         }
-        if let Some(protocol_fee) = protocol_fee {
-            self.protocol_fee = protocol_fee
+
+        if let Some(guardian) = guardian {
+            self.guardian = Some(guardian)
+        }
+
+        if let Some(referral_share) = referral_share {
+            self.referral_share = referral_share
         }
+    }
 
-        if let Some(protocol_fee_address) = protocol_fee_address {
-            self.protocol_fee_address = protocol_fee_address
+    /// The recipients of the protocol fee, falling back to `protocol_fee_address`
+    pub fn fee_recipients(&self) -> Vec<FeeRecipient> {
+        if self.fee_recipients.is_empty() {
+            return vec![FeeRecipient {
+                address: self.protocol_fee_address.clone(),
+                weight: Decimal::one(),
+                callback: None,
+            }];
         }
+        self.fee_recipients.clone()
+    }
+
+    /// The checksum of the delegate code, recorded when it was validated
+    pub fn delegate_checksum(&self) -> StdResult<&Checksum> {
+        self.delegate_code_checksum
+            .as_ref()
+            .ok_or_else(|| StdError::not_found("delegate code checksum"))
+    }
 
-        if let Some(delegate_code_id) = delegate_code_id {
-            self.delegate_code_id = delegate_code_id
+    /// Checks the protocol fee and the addresses it's paid to, its recipients, the fee tiers and
+    /// the referral share, the guard and keeper settings, the timelock, and that the delegate code
+    /// has been stored, recording its checksum
+    pub fn validate(
+        &mut self,
+        api: &dyn Api,
+        querier: QuerierWrapper<KujiraQuery>,
+    ) -> Result<(), ContractError> {
+        api.addr_validate(self.protocol_fee_address.as_str())?;
+        FeeRecipient::validate(api, &self.fee_recipients)?;
+        for (i, tier) in self.fee_tiers.iter().enumerate() {
+            tier.validate()?;
+            // Members are listed by tier name, so names must be unique
+            ensure!(
+                !self.fee_tiers[..i].iter().any(|x| x.name == tier.name),
+                ContractError::InvalidFeeTier {
+                    name: tier.name.clone()
+                }
+            );
         }
+        ensure!(
+            !self.vault_max_interest.is_zero() && self.vault_max_interest <= MAX_VAULT_MAX_INTEREST,
+            ContractError::InvalidVaultMaxInterest {
+                max: MAX_VAULT_MAX_INTEREST
+            }
+        );
+        ensure!(
+            self.protocol_fee <= MAX_PROTOCOL_FEE,
+            ContractError::InvalidProtocolFee {
+                max: MAX_PROTOCOL_FEE
+            }
+        );
+        ensure!(
+            self.referral_share <= Decimal::one(),
+            ContractError::InvalidReferralShare {}
+        );
+        ensure!(
+            !self.max_rate_change.is_zero() && self.max_rate_change <= MAX_MAX_RATE_CHANGE,
+            ContractError::InvalidMaxRateChange {
+                max: MAX_MAX_RATE_CHANGE
+            }
+        );
+        self.keeper_reward.validate()?;
+        timelock::validate_delay(self.timelock)?;
+        let code = querier
+            .query_wasm_code_info(self.delegate_code_id)
+            .map_err(|_| ContractError::InvalidDelegateCode {
+                code_id: self.delegate_code_id,
+            })?;
+        self.delegate_code_checksum = Some(code.checksum);
+        Ok(())
+    }
+
+    pub fn pause(&mut self, operations: &[Operation]) {
+        for operation in operations {
+            if !self.paused.contains(operation) {
+                self.paused.push(operation.clone())
+            }
+        }
+    }
+
+    pub fn unpause(&mut self, operations: &[Operation]) {
+        self.paused.retain(|x| !operations.contains(x))
+    }
+
+    /// Errors if `operation` has been paused
+    pub fn ensure_active(&self, operation: Operation) -> Result<(), ContractError> {
+        ensure!(!self.paused.contains(&operation), ContractError::Paused {});
+        Ok(())
     }
 
-    pub fn to_response(self, broker: Broker) -> ConfigResponse {
+    pub fn to_response(self, broker: Broker, unbonding_duration: u64) -> ConfigResponse {
+        let fee_recipients = self.fee_recipients();
         ConfigResponse {
             owner: self.owner,
             protocol_fee: self.protocol_fee,
             protocol_fee_address: self.protocol_fee_address,
             delegate_code_id: self.delegate_code_id,
+            delegate_code_checksum: self.delegate_code_checksum,
             reserve_address: self.reserve_address,
             vault_address: self.vault_address,
             offer_denom: self.offer_denom,
@@ -87,7 +260,18 @@ impl Config {
             debt_denom: self.debt_denom,
             ghost_denom: self.ghost_denom,
             adapter: self.adapter,
+            vault_max_interest: self.vault_max_interest,
+            keeper_reward: self.keeper_reward,
+            max_rate_change: self.max_rate_change,
+            guardian: self.guardian,
+            paused: self.paused,
+            pending_owner: self.pending_owner,
+            timelock: self.timelock,
+            fee_recipients,
+            referral_share: self.referral_share,
+            fee_tiers: self.fee_tiers,
             broker,
+            unbonding_duration,
         }
     }
 }
@@ -1,38 +1,64 @@
 use std::ops::Sub;
 
 use crate::config::Config;
+use crate::fee_tier;
+use crate::fees;
+use crate::position;
+use crate::rate_guard;
+use crate::referral;
+use crate::route;
+use cosmwasm_schema::cw_serde;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    ensure_eq, to_json_binary, wasm_execute, Addr, Binary, Coin, CosmosMsg, Deps, DepsMut, Empty,
-    Env, Event, MessageInfo, Order, Response, StdError, StdResult, Timestamp, WasmMsg,
+    ensure, ensure_eq, from_json, to_json_binary, to_json_string, wasm_execute, Addr, Api, BankMsg,
+    Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, Event, MessageInfo,
+    QuerierWrapper, Reply, Response, StdError, StdResult, SubMsg, SubMsgResult, WasmMsg,
 };
 use cw2::set_contract_version;
-use cw_storage_plus::Map;
-use cw_utils::NativeBalance;
+use cw20::Cw20ExecuteMsg;
+use cw_storage_plus::{Item, Map};
+use cw_utils::{one_coin, NativeBalance};
 use kujira::{KujiraMsg, KujiraQuery};
-use monetary::{must_pay, AmountU128, CheckedCoin, Denom};
+use monetary::{AmountU128, CheckedCoin, Denom};
 use serde::Serialize;
-use unstake::broker::Status;
 use unstake::controller::{
-    CallbackType, DelegatesResponse, ExecuteMsg, InstantiateMsg, OfferResponse, QueryMsg,
-    RatesResponse, StatusResponse,
+    AskAsset, CallbackType, Change, ExecuteMsg, FeeRecipient, FeeTierMembersResponse,
+    InstantiateMsg, LocksResponse, OfferForOutputResponse, OfferResponse, Operation, Position,
+    PositionStatus, PositionsResponse, QueryMsg, RatesResponse, ReceiveMsg, ReferralsResponse,
+    Route, RoutesResponse, StatusResponse, UnclaimedFeesResponse,
 };
-use unstake::denoms::Base;
+use unstake::denoms::{Ask, Base};
 use unstake::helpers::predict_address;
-use unstake::rates::Rates;
+use unstake::ownership::PendingOwner;
+use unstake::rates::{Rates, VaultRates};
+use unstake::timelock::{PendingChangesResponse, Timelock};
 use unstake::{broker::Broker, ContractError};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:unstake";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-static DELEGATES: Map<Addr, Timestamp> = Map::new("delegates");
+// The delegates dispatched by each in-flight CompleteMatured, keyed by nonce
+static COMPLETE_BATCHES: Map<u64, CompleteBatch> = Map::new("complete_batches");
+
+static COMPLETE_NONCE: Item<u64> = Item::new("complete_nonce");
+
+const TIMELOCK: Timelock<Change> = Timelock::new();
+
+#[cw_serde]
+struct CompleteBatch {
+    delegates: Vec<Addr>,
+    completed: Vec<Addr>,
+    failed: Vec<Addr>,
+    /// The cursor for the following page of matured delegates, when this page was full
+    next: Option<Addr>,
+}
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut<KujiraQuery>,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response<KujiraMsg>, ContractError> {
@@ -41,10 +67,14 @@ pub fn instantiate(
         &msg.vault_address,
         &kujira_ghost::receipt_vault::QueryMsg::Config {},
     )?;
-    let config = Config::new(msg.clone(), ghost_cfg);
+    let mut config = Config::new(msg.clone(), ghost_cfg);
+    config.validate(deps.api, deps.querier)?;
     config.save(deps.storage)?;
     let broker = Broker::from(msg);
-    broker.init(deps.storage)?;
+    broker.validate()?;
+    let route = route::primary(&config, &broker);
+    route.validate(deps.api, deps.querier)?;
+    rate_guard::accept(deps.storage, deps.querier, &route, &env.block)?;
     broker.save(deps.storage)?;
 
     Ok(Response::default())
@@ -59,68 +89,60 @@ pub fn execute(
 ) -> Result<Response<KujiraMsg>, ContractError> {
     let config = Config::load(deps.storage)?;
     match msg {
-        ExecuteMsg::Unstake { max_fee, callback } => {
-            let amount = must_pay(&info, &config.ask_denom)?;
+        ExecuteMsg::Unstake {
+            max_fee,
+            min_receive,
+            deadline,
+            callback,
+            referrer,
+        } => {
+            let funds = one_coin(&info)?;
             let broker = Broker::load(deps.storage)?;
-            let rates = Rates::load(deps.querier, &config.adapter, &config.vault_address)?;
-            let reserve_status = deps.querier.query_wasm_smart(
-                &config.reserve_address,
-                &unstake::reserve::QueryMsg::Status {},
-            )?;
-            let offer = broker.offer(&reserve_status, &rates, amount)?;
-            if offer.fee.gt(&max_fee) {
-                return Err(ContractError::MaxFeeExceeded {});
-            };
-            broker.accept_offer(deps.storage, &offer)?;
-
-            let borrow_amount = offer.offer_amount - offer.reserve_allocation;
-
-            let mut msgs = vec![];
-            // Number one, request reserves from the reserve contract.
-            if !offer.reserve_allocation.is_zero() {
-                msgs.push(request_reserve_msg(
-                    &config.reserve_address,
-                    offer.reserve_allocation,
-                )?);
-            }
-            // Number two, borrow from GHOST
-            msgs.push(vault_borrow_msg(
-                &config.vault_address,
-                borrow_amount,
-                Some(&CallbackType::GhostBorrow {
-                    offer: offer.clone(),
-                }),
-            )?);
-            // Number three, return instant liquidity to sender.
-            msgs.push(
-                callback
-                    .map(|cb| cb.to_message(&info.sender, Empty {}, []).unwrap())
-                    .unwrap_or(
-                        config
-                            .offer_denom
-                            .send(&info.sender, offer.offer_amount)
-                            .into(),
-                    ),
+            // CW20 tokens are only accepted through Receive
+            let route = route::load(deps.storage, &config, &broker, &Denom::new(&funds.denom))?;
+            ensure!(
+                !route.ask_denom.is_cw20(),
+                ContractError::UnknownRoute {
+                    ask_denom: funds.denom
+                }
             );
-
-            // Calculate delegate address in advance
-            let label = delegate_label(&env);
-            let (address, _) =
-                predict_address(config.delegate_code_id, &label, &deps.as_ref(), &env)?;
-
-            let event = Event::new("unstake/controller/unstake")
-                .add_attribute("amount", amount)
-                .add_attribute("rates", rates)
-                .add_attribute("offer", offer)
-                .add_attribute("sender", info.sender)
-                .add_attribute("delegate", address);
-
-            Ok(Response::default().add_event(event).add_messages(msgs))
+            let amount = AmountU128::new(funds.amount);
+            let msg = ReceiveMsg::Unstake {
+                max_fee,
+                min_receive,
+                deadline,
+                callback,
+                referrer,
+            };
+            unstake(deps, env, config, route, info.sender, amount, msg)
+        }
+        ExecuteMsg::Receive(cw20_msg) => {
+            let broker = Broker::load(deps.storage)?;
+            let route = route::load(
+                deps.storage,
+                &config,
+                &broker,
+                &Denom::new(info.sender.as_str()),
+            )
+            .ok()
+            .filter(|x| x.ask_denom.is_cw20())
+            .ok_or(ContractError::Unauthorized {})?;
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            let amount = AmountU128::new(cw20_msg.amount);
+            let msg = from_json(&cw20_msg.msg)?;
+            unstake(deps, env, config, route, sender, amount, msg)
         }
         ExecuteMsg::Callback(cb) => {
             let cb_type: CallbackType = cb.deserialize_callback()?;
-            let offer = match cb_type {
-                CallbackType::GhostBorrow { offer } => offer,
+            let (offer, unstaker, ask_denom, referrer, referral_share, rebate) = match cb_type {
+                CallbackType::GhostBorrow {
+                    offer,
+                    unstaker,
+                    ask_denom,
+                    referrer,
+                    referral_share,
+                    rebate,
+                } => (offer, unstaker, ask_denom, referrer, referral_share, rebate),
             };
 
             ensure_eq!(
@@ -130,19 +152,43 @@ pub fn execute(
             );
             let debt = amount(&config.debt_denom, &info.funds)?;
             let debt_amount = debt.amount;
-            let unbond = config.ask_denom.coin(offer.unbond_amount);
-            let mut funds = NativeBalance(vec![debt.into(), unbond.into()]);
-            funds.normalize();
+            let broker = Broker::load(deps.storage)?;
+            let route = route::load(deps.storage, &config, &broker, &ask_denom)?;
+            let duration = route.broker(deps.querier, &broker).duration;
 
             let label = delegate_label(&env);
             let (address, salt) =
-                predict_address(config.delegate_code_id, &label, &deps.as_ref(), &env)?;
+                predict_address(config.delegate_checksum()?, &label, &deps.as_ref(), &env)?;
+
+            let mut msgs: Vec<CosmosMsg<KujiraMsg>> = vec![];
+            let mut funds = NativeBalance(vec![debt.into()]);
+            let ask_denom = route.ask_denom.denom();
+            if let AskAsset::Cw20 { cw20 } = &route.ask_denom {
+                // CW20 tokens can't be attached to the instantiation, so they're transferred to
+                // the delegate's address beforehand
+                msgs.push(
+                    wasm_execute(
+                        cw20,
+                        &Cw20ExecuteMsg::Transfer {
+                            recipient: address.to_string(),
+                            amount: offer.unbond_amount.uint128(),
+                        },
+                        vec![],
+                    )?
+                    .into(),
+                );
+            } else {
+                let unbond: Coin = ask_denom.coin(offer.unbond_amount).into();
+                funds += unbond;
+            }
+            funds.normalize();
 
             let msg = unstake::delegate::InstantiateMsg {
-                unbond_amount: config.ask_denom.coin(offer.unbond_amount),
+                unbond_amount: ask_denom.coin(offer.unbond_amount),
                 controller: env.contract.address.clone(),
                 offer: offer.clone(),
-                adapter: config.adapter,
+                adapter: route.adapter,
+                cw20: route.ask_denom.is_cw20(),
             };
 
             let instantiate: WasmMsg = WasmMsg::Instantiate2 {
@@ -154,27 +200,48 @@ pub fn execute(
                 salt,
             };
 
-            DELEGATES.save(deps.storage, address.clone(), &env.block.time)?;
+            position::save(
+                deps.storage,
+                &Position {
+                    delegate: address.clone(),
+                    unstaker: Some(unstaker.clone()),
+                    offer: offer.clone(),
+                    ask_denom: Some(ask_denom),
+                    referrer,
+                    referral_share,
+                    rebate,
+                    debt_tokens: debt_amount,
+                    start: env.block.time,
+                    maturity: env.block.time.plus_seconds(duration),
+                    status: PositionStatus::Unbonding,
+                },
+            )?;
 
             let event: Event = Event::new("unstake/controller/callback/unstake")
                 .add_attribute("unbond_amount", offer.unbond_amount)
+                .add_attribute("ask_denom", route.ask_denom.to_string())
                 .add_attribute("debt_amount", debt_amount)
+                .add_attribute("unstaker", unstaker)
                 .add_attribute("delegate", address);
 
             Ok(Response::default()
                 .add_event(event)
+                .add_messages(msgs)
                 .add_message(instantiate))
         }
-        ExecuteMsg::Complete { offer } => {
-            DELEGATES
-                .load(deps.storage, info.sender.clone())
-                .map_err(|_| ContractError::Unauthorized {})?;
-            DELEGATES.remove(deps.storage, info.sender.clone());
+        ExecuteMsg::Complete { offer, keeper } => {
+            let mut position = position::load(deps.storage, &info.sender)?
+                .filter(|x| x.status == PositionStatus::Unbonding)
+                .ok_or(ContractError::Unauthorized {})?;
+            position.status = PositionStatus::Completed;
+            position::archive(deps.storage, &position)?;
 
             let debt = amount(&config.debt_denom, &info.funds)?;
             let base = amount(&config.offer_denom, &info.funds)?;
 
-            let rates = Rates::load(deps.querier, &config.adapter, &config.vault_address)?;
+            // Only the vault rates are needed to close the offer, so the route's provider isn't
+            // queried. Its route may since have been removed
+            let rates = VaultRates::load(deps.querier, &config.vault_address)?;
             let broker = Broker::load(deps.storage)?;
 
             let mut msgs = vec![];
@@ -184,10 +251,37 @@ pub fn execute(
                 return Err(ContractError::InsufficentFunds {});
             }
 
+            broker.record_interest(deps.storage, env.block.time, rates.vault_interest)?;
+            let ask_denom = position
+                .ask_denom
+                .unwrap_or_else(|| config.ask_denom.denom());
+            route::close(deps.storage, &ask_denom, base.amount)?;
             let (repay_funds, reserve_return, base_fee_amount) =
-                broker.close_offer(deps, &rates, &offer, debt, base.clone())?;
+                broker.close_offer(&rates, &offer, debt, base.clone())?;
             let protocol_fee = base_fee_amount.dec_mul_floor(config.protocol_fee);
             let reserve_fee = base_fee_amount.sub(protocol_fee);
+            // The keeper is paid out of the protocol's share of the fee
+            let keeper_reward = match keeper {
+                Some(_) => config.keeper_reward.amount(base_fee_amount, protocol_fee),
+                None => AmountU128::zero(),
+            };
+            let protocol_fee = protocol_fee.sub(keeper_reward);
+            // The referrer is paid out of what remains, at the share of its tier at the Unstake
+            let referral_fee = match &position.referrer {
+                Some(referrer) => {
+                    let referral_fee = protocol_fee.dec_mul_floor(position.referral_share);
+                    referral::record_earnings(deps.storage, referrer, referral_fee)?;
+                    referral_fee
+                }
+                None => AmountU128::zero(),
+            };
+            let protocol_fee = protocol_fee.sub(referral_fee);
+            // Followed by the unstaker's rebate, from its fee tier
+            let rebate = match &position.unstaker {
+                Some(_) => protocol_fee.dec_mul_floor(position.rebate),
+                None => AmountU128::zero(),
+            };
+            let protocol_fee = protocol_fee.sub(rebate);
 
             // repay ghost
             let ghost_repay_msg = vault_repay_msg(&config.vault_address, repay_funds.clone())?;
@@ -201,14 +295,50 @@ pub fn execute(
                 msgs.push(reserve_repay_msg);
             }
 
-            // Finally, send the protocol fee to the fee address
-            if !protocol_fee.is_zero() {
-                msgs.push(
-                    config
-                        .offer_denom
-                        .send(&config.protocol_fee_address, protocol_fee)
-                        .into(),
-                );
+            // Pay the keeper for triggering the completion
+            if let Some(keeper) = keeper.as_ref().filter(|_| !keeper_reward.is_zero()) {
+                msgs.push(config.offer_denom.send(keeper, keeper_reward).into());
+            }
+
+            // Pay the referrer their share
+            if let Some(referrer) = position
+                .referrer
+                .as_ref()
+                .filter(|_| !referral_fee.is_zero())
+            {
+                msgs.push(config.offer_denom.send(referrer, referral_fee).into());
+            }
+
+            // Rebate the unstaker
+            if let Some(unstaker) = position.unstaker.as_ref().filter(|_| !rebate.is_zero()) {
+                msgs.push(config.offer_denom.send(unstaker, rebate).into());
+            }
+
+            // Finally, split the protocol fee between the fee recipients. A failing callback
+            // leaves its share to be claimed, rather than failing the completion
+            let mut callbacks = vec![];
+            let recipients = config.fee_recipients();
+            let shares = FeeRecipient::split(&recipients, protocol_fee);
+            for (recipient, share) in recipients.iter().zip(shares) {
+                if share.is_zero() {
+                    continue;
+                }
+                match &recipient.callback {
+                    Some(cb) => {
+                        let msg = cb.to_message(
+                            &recipient.address,
+                            Empty {},
+                            vec![config.offer_denom.coin(share).into()],
+                        )?;
+                        callbacks.push(fees::callback(
+                            deps.storage,
+                            &recipient.address,
+                            share,
+                            msg,
+                        )?);
+                    }
+                    None => msgs.push(config.offer_denom.send(&recipient.address, share).into()),
+                }
             }
             let event: Event = Event::new("unstake/controller/complete")
                 .add_attribute("returned_tokens", base.amount)
@@ -222,65 +352,724 @@ pub fn execute(
                 )
                 .add_attribute("protocol_fee_amount", protocol_fee)
                 .add_attribute("reserve_fee", reserve_fee)
+                .add_attribute("keeper", keeper.map(|x| x.to_string()).unwrap_or_default())
+                .add_attribute("keeper_reward", keeper_reward)
+                .add_attribute(
+                    "referrer",
+                    position.referrer.map(|x| x.to_string()).unwrap_or_default(),
+                )
+                .add_attribute("referral_fee", referral_fee)
+                .add_attribute("rebate", rebate)
                 .add_attribute("delegate", info.sender);
-            Ok(Response::default().add_event(event).add_messages(msgs))
+            Ok(Response::default()
+                .add_event(event)
+                .add_messages(msgs)
+                .add_submessages(callbacks))
+        }
+        ExecuteMsg::CompleteMatured { start_after, limit } => {
+            let matured = position::matured(deps.storage, env.block.time, start_after, limit)?;
+            let next = matured
+                .last()
+                .filter(|_| matured.len() == position::limit(limit))
+                .map(|x| x.delegate.clone());
+            let batch = CompleteBatch {
+                delegates: matured.into_iter().map(|x| x.delegate).collect(),
+                completed: vec![],
+                failed: vec![],
+                next,
+            };
+            if batch.delegates.is_empty() {
+                return Ok(Response::default().add_event(batch.event()));
+            }
+
+            // Each batch has its own nonce, so that batches in progress at the same time never
+            // share reply ids. The nonce is in the high bits of the reply id, and the delegate's
+            // index in the low bits
+            let nonce = COMPLETE_NONCE.may_load(deps.storage)?.unwrap_or_default();
+            COMPLETE_NONCE.save(deps.storage, &nonce.wrapping_add(1))?;
+
+            // Replies are needed on success as well as failure, in order to summarise the batch
+            // once the final delegate has been processed
+            let mut msgs = vec![];
+            for (idx, delegate) in batch.delegates.iter().enumerate() {
+                msgs.push(SubMsg::reply_always(
+                    wasm_execute(
+                        delegate,
+                        &unstake::delegate::ExecuteMsg::Complete {
+                            keeper: Some(info.sender.clone()),
+                        },
+                        vec![],
+                    )?,
+                    reply_id(nonce, idx),
+                ));
+            }
+            COMPLETE_BATCHES.save(deps.storage, nonce, &batch)?;
+
+            Ok(Response::default().add_submessages(msgs))
+        }
+        ExecuteMsg::MigratePositions { limit } => {
+            let broker = Broker::load(deps.storage)?;
+            let (migrated, remaining) =
+                position::migrate(deps.storage, deps.querier, &config, &broker, limit)?;
+            ensure!(migrated > 0, ContractError::MigrationComplete {});
+
+            let event = Event::new("unstake/controller/migrate_positions")
+                .add_attribute("positions", migrated.to_string())
+                .add_attribute("remaining", remaining.to_string());
+            Ok(Response::default().add_event(event))
         }
-        ExecuteMsg::UpdateBroker { min_rate, duration } => {
+        ExecuteMsg::ScheduleChange { change, effective } => {
             ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            // Rejected changes are caught now, rather than once the timelock has elapsed
+            apply_change(
+                deps.api,
+                deps.querier,
+                &mut config.clone(),
+                &mut Broker::load(deps.storage)?,
+                change.clone(),
+            )?;
+            let pending = TIMELOCK.schedule(
+                deps.storage,
+                change,
+                effective,
+                config.timelock,
+                env.block.time,
+            )?;
+
+            let event = Event::new("unstake/controller/schedule_change")
+                .add_attribute("id", pending.id.to_string())
+                .add_attribute("change", to_json_string(&pending.change)?)
+                .add_attribute("effective", pending.effective.seconds().to_string());
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::ExecuteChange { id } => {
+            let change = TIMELOCK.take(deps.storage, id, env.block.time)?;
+            if let Change::Timelock { delay } = &change {
+                TIMELOCK.update_delay(deps.storage, config.timelock, *delay, env.block.time)?;
+            }
+            let mut config = config;
             let mut broker = Broker::load(deps.storage)?;
-            broker.update(min_rate, duration);
+            apply_change(
+                deps.api,
+                deps.querier,
+                &mut config,
+                &mut broker,
+                change.clone(),
+            )?;
+            config.save(deps.storage)?;
             broker.save(deps.storage)?;
-            Ok(Response::default())
+
+            let event =
+                Event::new("unstake/controller/execute_change").add_attribute("id", id.to_string());
+            let response = Response::default().add_event(event);
+            // Routes are stored apart from the config, and their open positions are only checked
+            // once the change is executed
+            match change {
+                Change::Route { route } => {
+                    route::save(deps.storage, &config, &route)?;
+                    let rate = rate_guard::accept(deps.storage, deps.querier, &route, &env.block)?;
+
+                    let event = Event::new("unstake/controller/add_route")
+                        .add_attribute("ask_denom", route.ask_denom.to_string())
+                        .add_attribute("route", to_json_string(&route)?)
+                        .add_attribute("rate", rate.to_string());
+                    Ok(response.add_event(event))
+                }
+                Change::RemoveRoute { ask_denom } => {
+                    route::remove(deps.storage, &config, &ask_denom)?;
+
+                    let event = Event::new("unstake/controller/remove_route")
+                        .add_attribute("ask_denom", ask_denom.to_string());
+                    Ok(response.add_event(event))
+                }
+                _ => Ok(response),
+            }
+        }
+        ExecuteMsg::CancelChange { id } => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            TIMELOCK.cancel(deps.storage, id)?;
+
+            let event =
+                Event::new("unstake/controller/cancel_change").add_attribute("id", id.to_string());
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::AcceptRate { ask_denom } => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let broker = Broker::load(deps.storage)?;
+            let route = route::load(deps.storage, &config, &broker, &ask_denom)?;
+            let rate = rate_guard::accept(deps.storage, deps.querier, &route, &env.block)?;
+            let event = Event::new("unstake/controller/accept_rate")
+                .add_attribute("ask_denom", ask_denom.to_string())
+                .add_attribute("rate", rate.to_string());
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::Pause { operations } => {
+            ensure!(
+                info.sender == config.owner || config.guardian.as_ref() == Some(&info.sender),
+                ContractError::Unauthorized {}
+            );
+            let mut config = Config::load(deps.storage)?;
+            config.pause(&operations);
+            config.save(deps.storage)?;
+
+            let event = Event::new("unstake/controller/pause")
+                .add_attribute("operations", to_json_string(&operations)?)
+                .add_attribute("sender", info.sender);
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::Unpause { operations } => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let mut config = Config::load(deps.storage)?;
+            config.unpause(&operations);
+            config.save(deps.storage)?;
+
+            let event = Event::new("unstake/controller/unpause")
+                .add_attribute("operations", to_json_string(&operations)?);
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::RemoveGuardian {} => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let mut config = Config::load(deps.storage)?;
+            let guardian = config.guardian.take().ok_or(ContractError::NoGuardian {})?;
+            config.save(deps.storage)?;
+
+            let event = Event::new("unstake/controller/remove_guardian")
+                .add_attribute("guardian", guardian);
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::ProposeOwner { owner, expiry } => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let mut config = Config::load(deps.storage)?;
+            config.pending_owner = Some(PendingOwner::new(owner.clone(), expiry, env.block.time)?);
+            config.save(deps.storage)?;
+
+            let event = Event::new("unstake/controller/propose_owner")
+                .add_attribute("owner", owner)
+                .add_attribute(
+                    "expiry",
+                    expiry.map_or("null".to_string(), |x| x.seconds().to_string()),
+                );
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::AcceptOwner {} => {
+            let mut config = Config::load(deps.storage)?;
+            let previous = config.owner;
+            config.owner = config
+                .pending_owner
+                .take()
+                .ok_or(ContractError::NoPendingOwner {})?
+                .accept(&info.sender, env.block.time)?;
+            config.save(deps.storage)?;
+
+            let event = Event::new("unstake/controller/accept_owner")
+                .add_attribute("previous_owner", previous)
+                .add_attribute("owner", config.owner);
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::CancelOwner {} => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let mut config = Config::load(deps.storage)?;
+            let pending = config
+                .pending_owner
+                .take()
+                .ok_or(ContractError::NoPendingOwner {})?;
+            config.save(deps.storage)?;
+
+            let event =
+                Event::new("unstake/controller/cancel_owner").add_attribute("owner", pending.owner);
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::UpdateConfig(update) => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let event = Event::new("unstake/controller/update_config")
+                .add_attribute("update", to_json_string(&update)?);
+            let mut config = Config::load(deps.storage)?;
+            config.update(update);
+            config.validate(deps.api, deps.querier)?;
+            config.save(deps.storage)?;
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::ClaimFees {} => {
+            let amount = fees::claim(deps.storage, &info.sender)?;
+            ensure!(!amount.is_zero(), ContractError::NothingToClaim {});
+
+            let event = Event::new("unstake/controller/claim_fees")
+                .add_attribute("recipient", info.sender.clone())
+                .add_attribute("amount", amount);
+            Ok(Response::default()
+                .add_event(event)
+                .add_message(config.offer_denom.send(&info.sender, amount)))
+        }
+        ExecuteMsg::SetReferralTier { referrer, share } => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            match share {
+                Some(share) => {
+                    ensure!(
+                        share <= Decimal::one(),
+                        ContractError::InvalidReferralShare {}
+                    );
+                    referral::set_tier(deps.storage, &referrer, share)?;
+                }
+                None => referral::remove(deps.storage, &referrer),
+            }
+
+            let event = Event::new("unstake/controller/set_referral_tier")
+                .add_attribute("referrer", referrer)
+                .add_attribute("share", share.map(|x| x.to_string()).unwrap_or_default());
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::SetFeeTierMember { address, tier } => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let address = deps.api.addr_validate(address.as_str())?;
+            if let Some(tier) = &tier {
+                ensure!(
+                    config.fee_tiers.iter().any(|x| &x.name == tier),
+                    ContractError::InvalidFeeTier { name: tier.clone() }
+                );
+            }
+            fee_tier::set_member(deps.storage, &address, tier.as_deref())?;
+
+            let event = Event::new("unstake/controller/set_fee_tier_member")
+                .add_attribute("address", address)
+                .add_attribute("tier", tier.unwrap_or_default());
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::Lock {} => {
+            let funds = one_coin(&info)?;
+            let lock = fee_tier::lock(deps.storage, &info.sender, &funds, env.block.time)?;
+
+            let event = Event::new("unstake/controller/lock")
+                .add_attribute("holder", info.sender)
+                .add_attribute("amount", funds.to_string())
+                .add_attribute("locked", lock.amount.to_string());
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::Unlock { amount } => {
+            fee_tier::unlock(deps.storage, &info.sender, &amount)?;
+
+            let event = Event::new("unstake/controller/unlock")
+                .add_attribute("holder", info.sender.clone())
+                .add_attribute("amount", amount.to_string());
+            Ok(Response::default()
+                .add_event(event)
+                .add_message(BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: vec![amount],
+                }))
         }
-        ExecuteMsg::UpdateConfig {
-            owner,
+    }
+}
+
+/// Applies `change` to `config` and `broker`, validating the result. Route changes are only
+/// validated, and are stored by `ExecuteChange`
+fn apply_change(
+    api: &dyn Api,
+    querier: QuerierWrapper<KujiraQuery>,
+    config: &mut Config,
+    broker: &mut Broker,
+    change: Change,
+) -> Result<(), ContractError> {
+    match change {
+        Change::Broker {
+            min_rate,
+            duration,
+            pricing,
+        } => {
+            broker.update(min_rate, duration, pricing);
+            broker.validate()
+        }
+        Change::Config {
             protocol_fee,
             protocol_fee_address,
             delegate_code_id,
+            vault_max_interest,
         } => {
-            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
-            let mut config = Config::load(deps.storage)?;
-            config.update(owner, protocol_fee, protocol_fee_address, delegate_code_id);
-            config.save(deps.storage)?;
-            Ok(Response::default())
+            if let Some(protocol_fee) = protocol_fee {
+                config.protocol_fee = protocol_fee
+            }
+            if let Some(protocol_fee_address) = protocol_fee_address {
+                config.protocol_fee_address = protocol_fee_address
+            }
+            if let Some(delegate_code_id) = delegate_code_id {
+                config.delegate_code_id = delegate_code_id
+            }
+            if let Some(vault_max_interest) = vault_max_interest {
+                config.vault_max_interest = vault_max_interest
+            }
+            config.validate(api, querier)
+        }
+        Change::Timelock { delay } => {
+            config.timelock = delay;
+            config.validate(api, querier)
+        }
+        Change::FeeTiers { tiers } => {
+            config.fee_tiers = tiers;
+            config.validate(api, querier)
+        }
+        Change::FeeRecipients { recipients } => {
+            config.fee_recipients = recipients;
+            config.validate(api, querier)
+        }
+        Change::Route { route } => {
+            ensure!(
+                route.ask_denom.denom() != config.ask_denom.denom(),
+                ContractError::InvalidRoute {}
+            );
+            route.validate(api, querier)
+        }
+        Change::RemoveRoute { ask_denom } => {
+            ensure!(
+                ask_denom != config.ask_denom.denom(),
+                ContractError::InvalidRoute {}
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Quotes and accepts an offer for `amount` of the route's ask token, sent by `sender`
+fn unstake(
+    deps: DepsMut<KujiraQuery>,
+    env: Env,
+    config: Config,
+    route: Route,
+    sender: Addr,
+    amount: AmountU128<Ask>,
+    msg: ReceiveMsg,
+) -> Result<Response<KujiraMsg>, ContractError> {
+    let ReceiveMsg::Unstake {
+        max_fee,
+        min_receive,
+        deadline,
+        callback,
+        referrer,
+    } = msg;
+    config.ensure_active(Operation::Unstake)?;
+    let referrer = referrer
+        .map(|x| deps.api.addr_validate(x.as_str()))
+        .transpose()?;
+    if deadline.is_some_and(|x| env.block.time > x) {
+        return Err(ContractError::DeadlineExceeded {});
+    }
+    let tier = fee_tier::find(deps.storage, &config.fee_tiers, &sender, env.block.time)?;
+    let broker = route.broker(deps.querier, &Broker::load(deps.storage)?);
+    let broker = match tier {
+        Some(tier) => tier.broker(&broker),
+        None => broker,
+    };
+    let rates = Rates::load(
+        deps.querier,
+        &route.adapter,
+        &config.vault_address,
+        config.vault_max_interest,
+    )?;
+    rate_guard::check(
+        deps.storage,
+        deps.querier,
+        &route,
+        config.max_rate_change,
+        &env.block,
+    )?;
+    let reserve_status = deps.querier.query_wasm_smart(
+        &config.reserve_address,
+        &unstake::reserve::QueryMsg::Status {},
+    )?;
+    let average = broker.average_interest(deps.storage, env.block.time, rates.vault_interest)?;
+    let quote = broker.quote(&reserve_status, &rates, average, amount)?;
+    let offer = quote.offer;
+    if offer.fee.gt(&max_fee) {
+        return Err(ContractError::MaxFeeExceeded {});
+    };
+    if min_receive.is_some_and(|x| offer.offer_amount.lt(&x)) {
+        return Err(ContractError::MinReceiveNotMet {});
+    };
+    broker.record_interest(deps.storage, env.block.time, rates.vault_interest)?;
+    route::accept(deps.storage, &route.ask_denom.denom(), offer.unbond_amount)?;
+    // The referral share is fixed at the Unstake, as the rebate is
+    let referral_share = match &referrer {
+        Some(referrer) => {
+            referral::record_volume(deps.storage, referrer, offer.offer_amount)?;
+            referral::share(deps.storage, referrer, config.referral_share)?
+        }
+        None => Decimal::zero(),
+    };
+
+    let borrow_amount = offer.offer_amount - offer.reserve_allocation;
+
+    let mut msgs = vec![];
+    // Number one, request reserves from the reserve contract.
+    if !offer.reserve_allocation.is_zero() {
+        msgs.push(request_reserve_msg(
+            &config.reserve_address,
+            offer.reserve_allocation,
+        )?);
+    }
+    // Number two, borrow from GHOST
+    msgs.push(vault_borrow_msg(
+        &config.vault_address,
+        borrow_amount,
+        Some(&CallbackType::GhostBorrow {
+            offer: offer.clone(),
+            unstaker: sender.clone(),
+            ask_denom: route.ask_denom.denom(),
+            referrer: referrer.clone(),
+            referral_share,
+            rebate: tier.map_or(Decimal::zero(), |x| x.rebate),
+        }),
+    )?);
+    // Number three, return instant liquidity to sender.
+    msgs.push(match callback {
+        Some(cb) => cb.to_message(&sender, Empty {}, [])?,
+        None => config.offer_denom.send(&sender, offer.offer_amount).into(),
+    });
+
+    // Calculate delegate address in advance
+    let label = delegate_label(&env);
+    let (address, _) = predict_address(config.delegate_checksum()?, &label, &deps.as_ref(), &env)?;
+
+    let event = Event::new("unstake/controller/unstake")
+        .add_attribute("amount", amount)
+        .add_attribute("ask_denom", route.ask_denom.to_string())
+        .add_attribute("rates", rates)
+        .add_attribute("offer", offer)
+        .add_attribute("rate", quote.rate.to_string())
+        .add_attribute("clamped", quote.clamped.to_string())
+        .add_attribute("effective_rate", quote.effective_rate.to_string())
+        .add_attribute("sender", sender)
+        .add_attribute(
+            "referrer",
+            referrer.map(|x| x.to_string()).unwrap_or_default(),
+        )
+        .add_attribute("fee_tier", tier.map(|x| x.name.clone()).unwrap_or_default())
+        .add_attribute("delegate", address);
+
+    Ok(Response::default().add_event(event).add_messages(msgs))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(
+    deps: DepsMut<KujiraQuery>,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response<KujiraMsg>, ContractError> {
+    if msg.id & fees::REPLY_FLAG != 0 {
+        let event = fees::reply(deps.storage, msg.id, msg.result)?;
+        return Ok(Response::default().add_events(event));
+    }
+
+    let (nonce, idx) = (msg.id >> 32, (msg.id & u32::MAX as u64) as usize);
+    let mut batch = COMPLETE_BATCHES.load(deps.storage, nonce)?;
+    let delegate = batch
+        .delegates
+        .get(idx)
+        .cloned()
+        .ok_or_else(|| StdError::not_found(format!("reply {}", msg.id)))?;
+
+    let mut response = Response::default();
+    match msg.result {
+        SubMsgResult::Ok(_) => batch.completed.push(delegate),
+        SubMsgResult::Err(error) => {
+            let event = Event::new("unstake/controller/complete_matured/failure")
+                .add_attribute("delegate", delegate.clone())
+                .add_attribute("error", error);
+            response = response.add_event(event);
+            batch.failed.push(delegate);
         }
     }
+
+    if batch.completed.len() + batch.failed.len() < batch.delegates.len() {
+        COMPLETE_BATCHES.save(deps.storage, nonce, &batch)?;
+        return Ok(response);
+    }
+
+    COMPLETE_BATCHES.remove(deps.storage, nonce);
+    Ok(response.add_event(batch.event()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps<KujiraQuery>, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+pub fn query(deps: Deps<KujiraQuery>, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     let config = Config::load(deps.storage)?;
-    let rates = Rates::load(deps.querier, &config.adapter, &config.vault_address)?;
     let broker = Broker::load(deps.storage)?;
+    let rates = |route: &Route| {
+        Rates::load(
+            deps.querier,
+            &route.adapter,
+            &config.vault_address,
+            config.vault_max_interest,
+        )
+    };
+    // The fee tier of the sender being quoted for, if any
+    let sender_tier = |sender: Option<Addr>| match sender {
+        Some(sender) => fee_tier::find(deps.storage, &config.fee_tiers, &sender, env.block.time),
+        None => Ok(None),
+    };
     match msg {
-        QueryMsg::Offer { amount } => {
+        QueryMsg::Offer {
+            amount,
+            ask_denom,
+            sender,
+        } => {
+            let route = match ask_denom {
+                Some(ask_denom) => route::load(deps.storage, &config, &broker, &ask_denom)?,
+                None => route::primary(&config, &broker),
+            };
+            let tier = sender_tier(sender)?;
+            let broker = route.broker(deps.querier, &broker);
+            let broker = match tier {
+                Some(tier) => tier.broker(&broker),
+                None => broker,
+            };
+            let rates = rates(&route)?;
+            rate_guard::verify(
+                deps.storage,
+                deps.querier,
+                &route,
+                config.max_rate_change,
+                &env.block,
+            )?;
             let reserve_status = deps.querier.query_wasm_smart(
                 &config.reserve_address,
                 &unstake::reserve::QueryMsg::Status {},
             )?;
-            let offer = broker.offer(&reserve_status, &rates, amount)?;
-            Ok(to_json_binary(&OfferResponse::from(offer))?)
-        }
-        QueryMsg::Delegates {} => {
-            let delegates = DELEGATES
-                .range(deps.storage, None, None, Order::Ascending)
-                .collect::<StdResult<Vec<(Addr, Timestamp)>>>()?;
-            let response = DelegatesResponse { delegates };
+            let average =
+                broker.average_interest(deps.storage, env.block.time, rates.vault_interest)?;
+            let quote = broker.quote(&reserve_status, &rates, average, amount)?;
+            Ok(to_json_binary(&OfferResponse::new(
+                quote,
+                rates,
+                broker.pricing,
+                tier.map(|x| x.name.clone()),
+            ))?)
+        }
+        QueryMsg::OfferForOutput {
+            amount,
+            ask_denom,
+            sender,
+        } => {
+            let route = match ask_denom {
+                Some(ask_denom) => route::load(deps.storage, &config, &broker, &ask_denom)?,
+                None => route::primary(&config, &broker),
+            };
+            let tier = sender_tier(sender)?;
+            let broker = route.broker(deps.querier, &broker);
+            let broker = match tier {
+                Some(tier) => tier.broker(&broker),
+                None => broker,
+            };
+            let rates = rates(&route)?;
+            rate_guard::verify(
+                deps.storage,
+                deps.querier,
+                &route,
+                config.max_rate_change,
+                &env.block,
+            )?;
+            let reserve_status = deps.querier.query_wasm_smart(
+                &config.reserve_address,
+                &unstake::reserve::QueryMsg::Status {},
+            )?;
+            let average =
+                broker.average_interest(deps.storage, env.block.time, rates.vault_interest)?;
+            let offer = broker.offer_for_output(&reserve_status, &rates, average, amount)?;
+            Ok(to_json_binary(&OfferForOutputResponse::new(
+                offer,
+                broker.pricing,
+            ))?)
+        }
+        QueryMsg::Delegates {
+            start_after,
+            limit,
+            filter,
+        } => {
+            let response =
+                position::delegates(deps.storage, env.block.time, filter, start_after, limit)?;
             Ok(to_json_binary(&response)?)
         }
-        QueryMsg::Rates {} => Ok(to_json_binary(&RatesResponse::from(rates))?),
-        QueryMsg::Config {} => Ok(to_json_binary(&config.to_response(broker))?),
-        QueryMsg::Status {} => Ok(to_json_binary(&StatusResponse::from(Status::load(
-            deps.storage,
-        )))?),
+        QueryMsg::Positions { start_after, limit } => {
+            let positions = position::all(deps.storage, start_after, limit)?;
+            Ok(to_json_binary(&PositionsResponse { positions })?)
+        }
+        QueryMsg::PositionsByUser {
+            unstaker,
+            start_after,
+            limit,
+        } => {
+            let positions = position::by_unstaker(deps.storage, &unstaker, start_after, limit)?;
+            Ok(to_json_binary(&PositionsResponse { positions })?)
+        }
+        QueryMsg::MaturedPositions { start_after, limit } => {
+            let positions = position::matured(deps.storage, env.block.time, start_after, limit)?;
+            Ok(to_json_binary(&PositionsResponse { positions })?)
+        }
+        QueryMsg::CompletedPositions { start_after, limit } => {
+            let positions = position::archived(deps.storage, start_after, limit)?;
+            Ok(to_json_binary(&PositionsResponse { positions })?)
+        }
+        QueryMsg::Rates {} => {
+            let rates = rates(&route::primary(&config, &broker))?;
+            Ok(to_json_binary(&RatesResponse::from(rates))?)
+        }
+        QueryMsg::Config {} => {
+            let duration = route::primary(&config, &broker)
+                .broker(deps.querier, &broker)
+                .duration;
+            Ok(to_json_binary(&config.to_response(broker, duration))?)
+        }
+        QueryMsg::Status {} => {
+            let routes = route::all(deps.storage, &config, &broker)?;
+            Ok(to_json_binary(&StatusResponse::new(route::status(
+                deps.storage,
+                routes,
+            )?))?)
+        }
+        QueryMsg::Routes {} => Ok(to_json_binary(&RoutesResponse {
+            routes: route::all(deps.storage, &config, &broker)?,
+        })?),
+        QueryMsg::PendingChanges {} => Ok(to_json_binary(&PendingChangesResponse {
+            changes: TIMELOCK.all(deps.storage)?,
+        })?),
+        QueryMsg::Referrals { start_after, limit } => {
+            let referrals = referral::all(deps.storage, start_after, limit)?;
+            Ok(to_json_binary(&ReferralsResponse { referrals })?)
+        }
+        QueryMsg::UnclaimedFees { address } => Ok(to_json_binary(&UnclaimedFeesResponse {
+            amount: fees::unclaimed(deps.storage, &address)?,
+        })?),
+        QueryMsg::FeeTierMembers { start_after, limit } => {
+            let members = fee_tier::members(deps.storage, start_after, limit)?;
+            Ok(to_json_binary(&FeeTierMembersResponse { members })?)
+        }
+        QueryMsg::Locks { address } => Ok(to_json_binary(&LocksResponse {
+            locks: fee_tier::locks(deps.storage, &address)?,
+        })?),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut<KujiraQuery>, _env: Env, _msg: ()) -> StdResult<Response<KujiraMsg>> {
+pub fn migrate(
+    deps: DepsMut<KujiraQuery>,
+    env: Env,
+    _msg: (),
+) -> Result<Response<KujiraMsg>, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    Ok(Response::default())
+    // Validating records the checksum of the delegate code, for configs stored before it was
+    let mut config = Config::load(deps.storage)?;
+    config.validate(deps.api, deps.querier)?;
+    config.save(deps.storage)?;
+    let broker = Broker::load(deps.storage)?;
+    // Any remaining positions are migrated with `ExecuteMsg::MigratePositions`
+    let (migrated, remaining) =
+        position::migrate(deps.storage, deps.querier, &config, &broker, None)?;
+    route::migrate(deps.storage, &config)?;
+    for route in route::all(deps.storage, &config, &broker)? {
+        rate_guard::seed(deps.storage, deps.querier, &route, &env.block)?;
+    }
+    let event = Event::new("unstake/controller/migrate")
+        .add_attribute("positions", migrated.to_string())
+        .add_attribute("remaining", remaining.to_string());
+
+    Ok(Response::default().add_event(event))
 }
 
 pub fn vault_borrow_msg<T>(
@@ -346,6 +1135,31 @@ pub fn amount<T>(denom: &Denom<T>, funds: &[Coin]) -> StdResult<CheckedCoin<T>>
         .ok_or_else(|| StdError::not_found(denom.to_string()))
 }
 
+impl CompleteBatch {
+    fn event(&self) -> Event {
+        let join = |x: &Vec<Addr>| {
+            x.iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        Event::new("unstake/controller/complete_matured")
+            .add_attribute("completed_count", self.completed.len().to_string())
+            .add_attribute("failed_count", self.failed.len().to_string())
+            .add_attribute("completed", join(&self.completed))
+            .add_attribute("failed", join(&self.failed))
+            .add_attribute(
+                "next",
+                self.next.as_ref().map(Addr::to_string).unwrap_or_default(),
+            )
+    }
+}
+
+/// The reply id of the delegate at `idx` in the `CompleteMatured` batch with `nonce`
+fn reply_id(nonce: u64, idx: usize) -> u64 {
+    (nonce << 32) | idx as u64
+}
+
 pub fn delegate_label(env: &Env) -> String {
     format!(
         "Unstake.fi delegate {}/{}",
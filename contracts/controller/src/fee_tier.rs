@@ -0,0 +1,126 @@
+use cosmwasm_std::{Addr, Coin, Order, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::{Bound, Map};
+use unstake::{
+    controller::{FeeTier, FeeTierMember, Lock},
+    ContractError,
+};
+
+/// How long tokens must stay locked before they count towards a fee tier, so that tokens
+/// borrowed for an Unstake can't qualify for a discount
+pub const LOCK_PERIOD: u64 = 7 * 24 * 60 * 60;
+
+// The name of the fee tier each listed address is a member of
+static MEMBERS: Map<&Addr, String> = Map::new("fee_tier_members");
+
+// The tokens locked by each address, and when the lock was last added to, keyed by denom
+static LOCKS: Map<(&Addr, &str), (Uint128, Timestamp)> = Map::new("fee_tier_locks");
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// Lists `address` in the tier named `tier`, or removes it from its tier
+pub fn set_member(storage: &mut dyn Storage, address: &Addr, tier: Option<&str>) -> StdResult<()> {
+    match tier {
+        Some(tier) => MEMBERS.save(storage, address, &tier.to_string()),
+        None => {
+            MEMBERS.remove(storage, address);
+            Ok(())
+        }
+    }
+}
+
+pub fn members(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<FeeTierMember>> {
+    MEMBERS
+        .range(
+            storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize)
+        .map(|x| x.map(|(address, tier)| FeeTierMember { address, tier }))
+        .collect()
+}
+
+/// Adds `amount` to the holder's lock, restarting its lock period
+pub fn lock(
+    storage: &mut dyn Storage,
+    holder: &Addr,
+    amount: &Coin,
+    time: Timestamp,
+) -> StdResult<Lock> {
+    let key = (holder, amount.denom.as_str());
+    let (locked, _) = LOCKS.may_load(storage, key)?.unwrap_or_default();
+    let locked = locked + amount.amount;
+    LOCKS.save(storage, key, &(locked, time))?;
+    Ok(Lock {
+        amount: Coin::new(locked, amount.denom.clone()),
+        start: time,
+    })
+}
+
+/// Takes `amount` from the holder's lock
+pub fn unlock(
+    storage: &mut dyn Storage,
+    holder: &Addr,
+    amount: &Coin,
+) -> Result<(), ContractError> {
+    let key = (holder, amount.denom.as_str());
+    let (locked, start) = LOCKS
+        .may_load(storage, key)?
+        .filter(|(locked, _)| !amount.amount.is_zero() && *locked >= amount.amount)
+        .ok_or(ContractError::InsufficentFunds {})?;
+    let locked = locked - amount.amount;
+    if locked.is_zero() {
+        LOCKS.remove(storage, key);
+    } else {
+        LOCKS.save(storage, key, &(locked, start))?;
+    }
+    Ok(())
+}
+
+pub fn locks(storage: &dyn Storage, holder: &Addr) -> StdResult<Vec<Lock>> {
+    LOCKS
+        .prefix(holder)
+        .range(storage, None, None, Order::Ascending)
+        .map(|x| {
+            x.map(|(denom, (amount, start))| Lock {
+                amount: Coin::new(amount, denom),
+                start,
+            })
+        })
+        .collect()
+}
+
+/// The first of `tiers` that `sender` is listed in, or has locked the minimum balance of for
+/// the lock period
+pub fn find<'a>(
+    storage: &dyn Storage,
+    tiers: &'a [FeeTier],
+    sender: &Addr,
+    time: Timestamp,
+) -> StdResult<Option<&'a FeeTier>> {
+    let member = MEMBERS.may_load(storage, sender)?;
+    for tier in tiers {
+        if member.as_ref() == Some(&tier.name) {
+            return Ok(Some(tier));
+        }
+        let Some(min_balance) = &tier.min_balance else {
+            continue;
+        };
+        let (locked, start) = LOCKS
+            .may_load(storage, (sender, min_balance.denom.as_str()))?
+            .unwrap_or_default();
+        if locked >= min_balance.amount
+            && !locked.is_zero()
+            && time.seconds() >= start.seconds().saturating_add(LOCK_PERIOD)
+        {
+            return Ok(Some(tier));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,64 @@
+use cosmwasm_std::{Addr, CosmosMsg, Event, StdError, StdResult, Storage, SubMsg, SubMsgResult};
+use cw_storage_plus::{Item, Map};
+use kujira::KujiraMsg;
+use monetary::AmountU128;
+use unstake::denoms::Base;
+
+/// Set on the reply ids of fee callbacks, to keep them clear of the `CompleteMatured` batches
+pub const REPLY_FLAG: u64 = 1 << 63;
+
+// The fee shares sent with a recipient's callback, keyed by reply id, until it replies
+static PENDING: Map<u64, (Addr, AmountU128<Base>)> = Map::new("fee_pending");
+
+static PENDING_NONCE: Item<u64> = Item::new("fee_pending_nonce");
+
+// The fee shares whose callback failed, held for the recipient to claim
+static UNCLAIMED: Map<Addr, AmountU128<Base>> = Map::new("fee_unclaimed");
+
+/// Dispatches `msg`, which pays `share` to `recipient` through its callback, so that a failing
+/// callback leaves the share to be claimed rather than failing the completion
+pub fn callback(
+    storage: &mut dyn Storage,
+    recipient: &Addr,
+    share: AmountU128<Base>,
+    msg: CosmosMsg<KujiraMsg>,
+) -> StdResult<SubMsg<KujiraMsg>> {
+    let nonce = PENDING_NONCE.may_load(storage)?.unwrap_or_default();
+    PENDING_NONCE.save(storage, &(nonce + 1))?;
+    let id = REPLY_FLAG | nonce;
+    PENDING.save(storage, id, &(recipient.clone(), share))?;
+    Ok(SubMsg::reply_always(msg, id))
+}
+
+/// Settles the callback with reply `id`, holding the share for the recipient if it failed
+pub fn reply(storage: &mut dyn Storage, id: u64, result: SubMsgResult) -> StdResult<Option<Event>> {
+    let (recipient, share) = PENDING
+        .may_load(storage, id)?
+        .ok_or_else(|| StdError::not_found(format!("reply {id}")))?;
+    PENDING.remove(storage, id);
+    let SubMsgResult::Err(error) = result else {
+        return Ok(None);
+    };
+    UNCLAIMED.update(storage, recipient.clone(), |x| -> StdResult<_> {
+        Ok(x.unwrap_or_default() + share)
+    })?;
+    let event = Event::new("unstake/controller/fee_callback_failure")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", share)
+        .add_attribute("error", error);
+    Ok(Some(event))
+}
+
+/// The fee shares held for `recipient`
+pub fn unclaimed(storage: &dyn Storage, recipient: &Addr) -> StdResult<AmountU128<Base>> {
+    Ok(UNCLAIMED
+        .may_load(storage, recipient.clone())?
+        .unwrap_or_default())
+}
+
+/// Takes the fee shares held for `recipient`
+pub fn claim(storage: &mut dyn Storage, recipient: &Addr) -> StdResult<AmountU128<Base>> {
+    let amount = unclaimed(storage, recipient)?;
+    UNCLAIMED.remove(storage, recipient.clone());
+    Ok(amount)
+}
@@ -1,5 +1,11 @@
 pub mod config;
 pub mod contract;
+pub mod fee_tier;
+pub mod fees;
+pub mod position;
+pub mod rate_guard;
+pub mod referral;
+pub mod route;
 
 #[cfg(test)]
 mod testing;
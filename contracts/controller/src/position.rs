@@ -0,0 +1,280 @@
+use cosmwasm_std::{
+    from_json, Addr, Decimal, Order, QuerierWrapper, StdError, StdResult, Storage, Timestamp,
+};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Map, MultiIndex, PrefixBound};
+use kujira::KujiraQuery;
+use monetary::AmountU128;
+use std::cmp::max;
+use unstake::{
+    broker::Broker,
+    controller::{DelegatesFilter, DelegatesResponse, Position, PositionStatus},
+};
+
+use crate::config::Config;
+
+// Delegate address -> creation time, superseded by the position records
+static LEGACY_DELEGATES: Map<Addr, Timestamp> = Map::new("delegates");
+
+// Delegate address -> completed position, kept apart so that the indexes only cover open positions
+static ARCHIVE: Map<Addr, Position> = Map::new("positions_archive");
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+const MIGRATE_LIMIT: u32 = 50;
+
+pub struct PositionIndexes<'a> {
+    pub unstaker: MultiIndex<'a, String, Position, Addr>,
+    pub maturity: MultiIndex<'a, (u8, u64), Position, Addr>,
+}
+
+impl IndexList<Position> for PositionIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Position>> + '_> {
+        let v: Vec<&dyn Index<Position>> = vec![&self.unstaker, &self.maturity];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn positions<'a>() -> IndexedMap<Addr, Position, PositionIndexes<'a>> {
+    let indexes = PositionIndexes {
+        unstaker: MultiIndex::new(
+            |_, x| x.unstaker.as_ref().map(Addr::to_string).unwrap_or_default(),
+            "positions",
+            "positions__unstaker",
+        ),
+        maturity: MultiIndex::new(
+            |_, x| (status_key(&x.status), x.maturity.seconds()),
+            "positions",
+            "positions__maturity",
+        ),
+    };
+    IndexedMap::new("positions", indexes)
+}
+
+pub fn status_key(status: &PositionStatus) -> u8 {
+    match status {
+        PositionStatus::Unbonding => 0,
+        PositionStatus::Completed => 1,
+    }
+}
+
+pub fn limit(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize
+}
+
+pub fn load(storage: &dyn Storage, delegate: &Addr) -> StdResult<Option<Position>> {
+    positions().may_load(storage, delegate.clone())
+}
+
+pub fn save(storage: &mut dyn Storage, position: &Position) -> StdResult<()> {
+    positions().save(storage, position.delegate.clone(), position)
+}
+
+/// Moves a completed position out of the open positions and into the archive
+pub fn archive(storage: &mut dyn Storage, position: &Position) -> StdResult<()> {
+    positions().remove(storage, position.delegate.clone())?;
+    ARCHIVE.save(storage, position.delegate.clone(), position)
+}
+
+pub fn archived(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Position>> {
+    ARCHIVE
+        .range(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(self::limit(limit))
+        .map(|x| x.map(|(_, v)| v))
+        .collect()
+}
+
+pub fn all(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Position>> {
+    positions()
+        .range(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(self::limit(limit))
+        .map(|x| x.map(|(_, v)| v))
+        .collect()
+}
+
+pub fn by_unstaker(
+    storage: &dyn Storage,
+    unstaker: &Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Position>> {
+    positions()
+        .idx
+        .unstaker
+        .prefix(unstaker.to_string())
+        .range(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(self::limit(limit))
+        .map(|x| x.map(|(_, v)| v))
+        .collect()
+}
+
+/// Unbonding positions with a maturity at or before `now`, in order of maturity
+pub fn matured(
+    storage: &dyn Storage,
+    now: Timestamp,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Position>> {
+    unbonding(storage, None, Some(now), start_after, limit)
+}
+
+/// Unbonding positions with a maturity between `from` and `to` inclusive, in order of maturity
+pub fn unbonding(
+    storage: &dyn Storage,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Position>> {
+    let unbonding = status_key(&PositionStatus::Unbonding);
+    // Positions sharing a maturity are ordered by delegate address, so resume from the
+    // maturity of `start_after` and skip past it
+    let start = match start_after {
+        Some(addr) => {
+            // The delegate may have been completed since the page was read
+            let position = match positions().may_load(storage, addr.clone())? {
+                Some(position) => position,
+                None => ARCHIVE.load(storage, addr.clone())?,
+            };
+            Some((position.maturity.seconds(), addr))
+        }
+        None => None,
+    };
+    let from = max(
+        from.map_or(0, |x| x.seconds()),
+        start.as_ref().map_or(0, |(maturity, _)| *maturity),
+    );
+    let min_bound = Some(PrefixBound::inclusive((unbonding, from)));
+    let max_bound = Some(PrefixBound::inclusive((
+        unbonding,
+        to.map_or(u64::MAX, |x| x.seconds()),
+    )));
+
+    positions()
+        .idx
+        .maturity
+        .prefix_range(storage, min_bound, max_bound, Order::Ascending)
+        .filter(|x| match (x, &start) {
+            (Ok((addr, position)), Some((maturity, start_after))) => {
+                position.maturity.seconds() > *maturity || addr > start_after
+            }
+            _ => true,
+        })
+        .take(self::limit(limit))
+        .map(|x| x.map(|(_, v)| v))
+        .collect()
+}
+
+/// A page of delegates with an unbonding in progress, optionally filtered on whether they have
+/// matured as of `now`
+pub fn delegates(
+    storage: &dyn Storage,
+    now: Timestamp,
+    filter: Option<DelegatesFilter>,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<DelegatesResponse> {
+    let (from, to) = match filter {
+        None => (None, None),
+        Some(DelegatesFilter::Matured) => (None, Some(now)),
+        Some(DelegatesFilter::Unbonding) => (Some(now.plus_seconds(1)), None),
+    };
+    let positions = unbonding(storage, from, to, start_after, limit)?;
+    let next = positions
+        .last()
+        .filter(|_| positions.len() == self::limit(limit))
+        .map(|x| x.delegate.clone());
+
+    Ok(DelegatesResponse {
+        delegates: positions
+            .into_iter()
+            .map(|x| (x.delegate, x.start))
+            .collect(),
+        next,
+    })
+}
+
+/// Moves up to `limit` legacy delegate records over to positions, and then archives completed
+/// positions with what remains of the batch. The offer is read from the delegate's storage, and
+/// the debt tokens from its balance. The unstaker wasn't recorded, so is left empty.
+/// Returns the number of records migrated, and whether any remain
+pub fn migrate(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper<KujiraQuery>,
+    config: &Config,
+    broker: &Broker,
+    limit: Option<u32>,
+) -> StdResult<(usize, bool)> {
+    let limit = limit.unwrap_or(MIGRATE_LIMIT).min(MIGRATE_LIMIT) as usize;
+    let legacy = LEGACY_DELEGATES
+        .range(storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(Addr, Timestamp)>>>()?;
+
+    for (delegate, start) in legacy.iter() {
+        let offer = querier
+            .query_wasm_raw(delegate, b"offer".to_vec())?
+            .ok_or_else(|| StdError::not_found(format!("offer for {delegate}")))?;
+        let debt_tokens = querier.query_balance(delegate, config.debt_denom.to_string())?;
+
+        save(
+            storage,
+            &Position {
+                delegate: delegate.clone(),
+                unstaker: None,
+                offer: from_json(offer)?,
+                ask_denom: None,
+                referrer: None,
+                referral_share: Decimal::zero(),
+                rebate: Decimal::zero(),
+                debt_tokens: AmountU128::new(debt_tokens.amount),
+                start: *start,
+                maturity: start.plus_seconds(broker.duration),
+                status: PositionStatus::Unbonding,
+            },
+        )?;
+        LEGACY_DELEGATES.remove(storage, delegate.clone());
+    }
+
+    let completed = completed(storage, limit - legacy.len())?;
+    for position in completed.iter() {
+        archive(storage, position)?;
+    }
+
+    let remaining = !LEGACY_DELEGATES.is_empty(storage) || !self::completed(storage, 1)?.is_empty();
+    Ok((legacy.len() + completed.len(), remaining))
+}
+
+/// Completed positions that are yet to be archived
+fn completed(storage: &dyn Storage, limit: usize) -> StdResult<Vec<Position>> {
+    positions()
+        .idx
+        .maturity
+        .sub_prefix(status_key(&PositionStatus::Completed))
+        .range(storage, None, None, Order::Ascending)
+        .take(limit)
+        .map(|x| x.map(|(_, v)| v))
+        .collect()
+}
@@ -0,0 +1,106 @@
+use cosmwasm_std::{BlockInfo, Decimal, QuerierWrapper, StdResult, Storage, Timestamp};
+use cw_storage_plus::Map;
+use kujira::KujiraQuery;
+use unstake::{adapter::Unstake, controller::Route, ContractError};
+
+const DAY_SECONDS: u64 = 24 * 60 * 60;
+
+/// The most days' allowance a rate may move by, however long ago the last rate was accepted
+pub const MAX_DAYS: u64 = 7;
+
+/// The least time's allowance a rate may move by within a block
+pub const MIN_BLOCK_STEP: u64 = 60 * 60;
+
+// The redemption rate last accepted on each route, and when, keyed by ask denom
+static LAST_RATES: Map<String, (Decimal, Timestamp)> = Map::new("last_rates");
+
+// The redemption rate first seen on each route in a block, and the block height
+static BLOCK_RATES: Map<String, (Decimal, u64)> = Map::new("block_rates");
+
+/// Checks the provider's redemption rate against the rate last accepted on the route, without
+/// recording it. The rate may move by `max_change` of the last rate for each day since, with
+/// part days rounded up, up to `MAX_DAYS`. Within a block it may only move from the block's first
+/// rate by the allowance pro-rated to the time since the last rate, at least `MIN_BLOCK_STEP` and
+/// at most a day, so that it can't be pushed far and unstaked against in the same block
+pub fn verify(
+    storage: &dyn Storage,
+    querier: QuerierWrapper<KujiraQuery>,
+    route: &Route,
+    max_change: Decimal,
+    block: &BlockInfo,
+) -> Result<Decimal, ContractError> {
+    let rate = route.adapter.redemption_rate(querier)?;
+    let key = route.ask_denom.to_string();
+    let last = LAST_RATES.may_load(storage, key.clone())?;
+    if let Some((block_rate, height)) = BLOCK_RATES.may_load(storage, key)? {
+        let elapsed = last.map_or(0, |(_, time)| {
+            block.time.seconds().saturating_sub(time.seconds())
+        });
+        let step = elapsed.clamp(MIN_BLOCK_STEP, DAY_SECONDS);
+        let limit = block_rate * max_change * Decimal::from_ratio(step, DAY_SECONDS);
+        if height == block.height && rate.abs_diff(block_rate) > limit {
+            return Err(ContractError::RateDeviation {
+                rate,
+                last_rate: block_rate,
+            });
+        }
+    }
+    if let Some((last_rate, time)) = last {
+        let elapsed = block.time.seconds().saturating_sub(time.seconds());
+        let days = elapsed.div_ceil(DAY_SECONDS).clamp(1, MAX_DAYS);
+        let limit = last_rate * max_change * Decimal::from_ratio(days, 1u64);
+        if rate.abs_diff(last_rate) > limit {
+            return Err(ContractError::RateDeviation { rate, last_rate });
+        }
+    }
+    Ok(rate)
+}
+
+/// Verifies the provider's redemption rate, and records it. The rate is only re-accepted once a
+/// day has passed, so that it can't be walked up in small steps
+pub fn check(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper<KujiraQuery>,
+    route: &Route,
+    max_change: Decimal,
+    block: &BlockInfo,
+) -> Result<(), ContractError> {
+    let rate = verify(storage, querier, route, max_change, block)?;
+    let key = route.ask_denom.to_string();
+    BLOCK_RATES.save(storage, key.clone(), &(rate, block.height))?;
+    if let Some((_, time)) = LAST_RATES.may_load(storage, key.clone())? {
+        if block.time.seconds().saturating_sub(time.seconds()) < DAY_SECONDS {
+            return Ok(());
+        }
+    }
+    LAST_RATES.save(storage, key, &(rate, block.time))?;
+    Ok(())
+}
+
+/// Accepts the provider's current redemption rate, regardless of the last rate
+pub fn accept(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper<KujiraQuery>,
+    route: &Route,
+    block: &BlockInfo,
+) -> StdResult<Decimal> {
+    let rate = route.adapter.redemption_rate(querier)?;
+    let key = route.ask_denom.to_string();
+    LAST_RATES.save(storage, key.clone(), &(rate, block.time))?;
+    BLOCK_RATES.save(storage, key, &(rate, block.height))?;
+    Ok(rate)
+}
+
+/// Accepts the provider's current redemption rate on a route that has none recorded, so that
+/// routes added before rates were recorded aren't left to trust the first rate they see
+pub fn seed(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper<KujiraQuery>,
+    route: &Route,
+    block: &BlockInfo,
+) -> StdResult<()> {
+    if !LAST_RATES.has(storage, route.ask_denom.to_string()) {
+        accept(storage, querier, route, block)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use cosmwasm_std::{Addr, Decimal, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Map};
+use monetary::AmountU128;
+use std::cmp::min;
+use unstake::{controller::Referral, denoms::Base};
+
+// The tier and running totals of each referrer
+static REFERRALS: Map<Addr, Referral> = Map::new("referrals");
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// The referrer's tier and totals, if the referrer has a tier
+pub fn load(storage: &dyn Storage, referrer: &Addr) -> StdResult<Option<Referral>> {
+    REFERRALS.may_load(storage, referrer.clone())
+}
+
+/// Sets the referrer's tier, keeping its totals
+pub fn set_tier(storage: &mut dyn Storage, referrer: &Addr, tier: Decimal) -> StdResult<()> {
+    let referral = load(storage, referrer)?.unwrap_or_else(|| Referral {
+        referrer: referrer.clone(),
+        tier,
+        volume: AmountU128::zero(),
+        earnings: AmountU128::zero(),
+    });
+    REFERRALS.save(storage, referrer.clone(), &Referral { tier, ..referral })
+}
+
+/// Removes the referrer's tier, along with its totals
+pub fn remove(storage: &mut dyn Storage, referrer: &Addr) {
+    REFERRALS.remove(storage, referrer.clone())
+}
+
+/// The share of a protocol fee paid to `referrer`, given the configured `referral_share`.
+/// Nothing is paid to a referrer without a tier
+pub fn share(
+    storage: &dyn Storage,
+    referrer: &Addr,
+    referral_share: Decimal,
+) -> StdResult<Decimal> {
+    Ok(load(storage, referrer)?.map_or(Decimal::zero(), |x| min(referral_share, x.tier)))
+}
+
+/// Adds an Unstake with an offer of `amount` to the referrer's volume, if it has a tier
+pub fn record_volume(
+    storage: &mut dyn Storage,
+    referrer: &Addr,
+    amount: AmountU128<Base>,
+) -> StdResult<()> {
+    if let Some(mut referral) = load(storage, referrer)? {
+        referral.volume = referral.volume + amount;
+        REFERRALS.save(storage, referrer.clone(), &referral)?;
+    }
+    Ok(())
+}
+
+/// Adds `amount` of the protocol fee paid to the referrer's earnings, if it still has a tier
+pub fn record_earnings(
+    storage: &mut dyn Storage,
+    referrer: &Addr,
+    amount: AmountU128<Base>,
+) -> StdResult<()> {
+    if let Some(mut referral) = load(storage, referrer)? {
+        referral.earnings = referral.earnings + amount;
+        REFERRALS.save(storage, referrer.clone(), &referral)?;
+    }
+    Ok(())
+}
+
+pub fn all(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Referral>> {
+    REFERRALS
+        .range(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize)
+        .map(|x| x.map(|(_, v)| v))
+        .collect()
+}
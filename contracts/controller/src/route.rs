@@ -0,0 +1,165 @@
+use cosmwasm_std::{Order, StdResult, Storage};
+use cw_storage_plus::Map;
+use monetary::{AmountU128, Denom};
+use unstake::{
+    broker::{Broker, Status},
+    controller::{Route, RouteStatus},
+    denoms::{Ask, Base},
+    ContractError,
+};
+
+use crate::config::Config;
+
+// The routes added alongside the primary route in the Config, keyed by ask denom
+static ROUTES: Map<String, Route> = Map::new("routes");
+
+// The (base, quote) totals of each route, as tallied by the Broker for the controller as a whole
+static TOTALS: Map<String, (AmountU128<Ask>, AmountU128<Base>)> = Map::new("route_totals");
+
+// The number of open positions on each route, keyed by ask denom
+static OPEN: Map<String, u32> = Map::new("route_open");
+
+/// The route set at instantiation, with the Broker's duration
+pub fn primary(config: &Config, broker: &Broker) -> Route {
+    Route {
+        ask_denom: config.ask_denom.clone(),
+        adapter: config.adapter.clone(),
+        unbonding_duration: broker.duration,
+    }
+}
+
+pub fn load(
+    storage: &dyn Storage,
+    config: &Config,
+    broker: &Broker,
+    ask_denom: &Denom<Ask>,
+) -> Result<Route, ContractError> {
+    if *ask_denom == config.ask_denom.denom() {
+        return Ok(primary(config, broker));
+    }
+    ROUTES
+        .may_load(storage, ask_denom.to_string())?
+        .ok_or_else(|| ContractError::UnknownRoute {
+            ask_denom: ask_denom.to_string(),
+        })
+}
+
+/// All routes, starting with the primary route
+pub fn all(storage: &dyn Storage, config: &Config, broker: &Broker) -> StdResult<Vec<Route>> {
+    let mut routes = vec![primary(config, broker)];
+    for route in ROUTES.range(storage, None, None, Order::Ascending) {
+        routes.push(route?.1);
+    }
+    Ok(routes)
+}
+
+pub fn save(
+    storage: &mut dyn Storage,
+    config: &Config,
+    route: &Route,
+) -> Result<(), ContractError> {
+    if route.ask_denom.denom() == config.ask_denom.denom() {
+        return Err(ContractError::InvalidRoute {});
+    }
+    // The positions on a route were priced against its adapter, so it can't be swapped out from
+    // under them
+    let count = open(storage, &route.ask_denom.denom())?;
+    if ROUTES.has(storage, route.ask_denom.to_string()) && count > 0 {
+        return Err(ContractError::OpenPositions { count });
+    }
+    ROUTES.save(storage, route.ask_denom.to_string(), route)?;
+    Ok(())
+}
+
+pub fn remove(
+    storage: &mut dyn Storage,
+    config: &Config,
+    ask_denom: &Denom<Ask>,
+) -> Result<(), ContractError> {
+    if *ask_denom == config.ask_denom.denom() {
+        return Err(ContractError::InvalidRoute {});
+    }
+    if !ROUTES.has(storage, ask_denom.to_string()) {
+        return Err(ContractError::UnknownRoute {
+            ask_denom: ask_denom.to_string(),
+        });
+    }
+    // Nor can it be removed, and then re-added with another adapter
+    let count = open(storage, ask_denom)?;
+    if count > 0 {
+        return Err(ContractError::OpenPositions { count });
+    }
+    ROUTES.remove(storage, ask_denom.to_string());
+    Ok(())
+}
+
+/// Adds an accepted unbond to the route's totals, and counts its position as open
+pub fn accept(
+    storage: &mut dyn Storage,
+    ask_denom: &Denom<Ask>,
+    amount: AmountU128<Ask>,
+) -> StdResult<()> {
+    TOTALS.update(storage, ask_denom.to_string(), |x| -> StdResult<_> {
+        let (total_base, total_quote) = x.unwrap_or_default();
+        Ok((total_base + amount, total_quote))
+    })?;
+    OPEN.update(storage, ask_denom.to_string(), |x| -> StdResult<_> {
+        Ok(x.unwrap_or_default() + 1)
+    })?;
+    Ok(())
+}
+
+/// The number of positions open on the route for `ask_denom`
+pub fn open(storage: &dyn Storage, ask_denom: &Denom<Ask>) -> StdResult<u32> {
+    Ok(OPEN
+        .may_load(storage, ask_denom.to_string())?
+        .unwrap_or_default())
+}
+
+/// Adds the tokens returned by a completed unbond to the route's totals, and counts its
+/// position as closed
+pub fn close(
+    storage: &mut dyn Storage,
+    ask_denom: &Denom<Ask>,
+    returned: AmountU128<Base>,
+) -> StdResult<()> {
+    TOTALS.update(storage, ask_denom.to_string(), |x| -> StdResult<_> {
+        let (total_base, total_quote) = x.unwrap_or_default();
+        Ok((total_base, total_quote + returned))
+    })?;
+    // Positions opened before they were counted aren't included
+    let count = open(storage, ask_denom)?;
+    OPEN.save(storage, ask_denom.to_string(), &count.saturating_sub(1))?;
+    Ok(())
+}
+
+/// The totals of each of `routes`
+pub fn status(storage: &dyn Storage, routes: Vec<Route>) -> StdResult<Vec<RouteStatus>> {
+    routes
+        .into_iter()
+        .map(|route| {
+            let (total_base, total_quote) = TOTALS
+                .may_load(storage, route.ask_denom.to_string())?
+                .unwrap_or_default();
+            Ok(RouteStatus {
+                ask_denom: route.ask_denom.denom(),
+                total_base,
+                total_quote,
+            })
+        })
+        .collect()
+}
+
+/// Seeds the totals of the primary route with the controller's totals, which were all made on
+/// it before routes were introduced
+pub fn migrate(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    if TOTALS.has(storage, config.ask_denom.to_string()) {
+        return Ok(());
+    }
+    let status = Status::load(storage);
+    TOTALS.save(
+        storage,
+        config.ask_denom.to_string(),
+        &(status.total_base, status.total_quote),
+    )
+}
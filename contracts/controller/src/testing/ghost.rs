@@ -3,21 +3,40 @@ use std::{
     str::FromStr,
 };
 
+use cosmwasm_schema::serde::{Deserialize, Serialize};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     to_json_binary, Binary, Coin, CosmosMsg, CustomQuery, Decimal, Deps, DepsMut, Empty, Env,
-    MessageInfo, Response, StdResult, Timestamp, Uint128,
+    MessageInfo, Response, StdError, StdResult, Timestamp, Uint128,
 };
 use cw_storage_plus::Item;
 use cw_utils::{must_pay, NativeBalance};
 use kujira::{Denom, DenomMsg, KujiraMsg, KujiraQuery};
 use kujira_ghost::receipt_vault::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StatusResponse,
+    ConfigResponse, ExecuteMsg as VaultExecuteMsg, InstantiateMsg, QueryMsg, StatusResponse,
 };
+use unstake::rates::InterestParamsResponse;
 
 static INIT: Item<InstantiateMsg> = Item::new("init");
 static TS: Item<(Timestamp, Decimal)> = Item::new("ts");
+static INTEREST: Item<Option<Vec<(Decimal, Decimal)>>> = Item::new("interest");
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(
+    deny_unknown_fields,
+    rename_all = "snake_case",
+    crate = "cosmwasm_schema::serde"
+)]
+pub enum ExecuteMsg {
+    /// Set the interest curve returned from `InterestParams`. `None` emulates a vault
+    /// that doesn't support the query
+    SetInterestParams {
+        utilization_to_rate: Option<Vec<(Decimal, Decimal)>>,
+    },
+    #[serde(untagged)]
+    Vault(VaultExecuteMsg),
+}
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -28,6 +47,14 @@ pub fn instantiate(
 ) -> StdResult<Response<KujiraMsg>> {
     INIT.save(deps.storage, &msg)?;
     TS.save(deps.storage, &(env.block.time, Decimal::from_str("1.12")?))?;
+    INTEREST.save(
+        deps.storage,
+        &Some(vec![
+            (Decimal::zero(), Decimal::zero()),
+            (Decimal::percent(80), Decimal::percent(20)),
+            (Decimal::one(), Decimal::percent(300)),
+        ]),
+    )?;
     let denom_msgs = vec![
         DenomMsg::Create {
             subdenom: "udebt".into(),
@@ -50,8 +77,17 @@ pub fn execute(
     let debt_token_denom = Denom::from(format!("factory/{}/udebt", env.contract.address));
     let rcpt_token_denom = Denom::from(format!("factory/{}/urcpt", env.contract.address));
     let denom = init.denom;
+    let msg = match msg {
+        ExecuteMsg::SetInterestParams {
+            utilization_to_rate,
+        } => {
+            INTEREST.save(deps.storage, &utilization_to_rate)?;
+            return Ok(Response::default());
+        }
+        ExecuteMsg::Vault(msg) => msg,
+    };
     match msg {
-        ExecuteMsg::Deposit(msg) => {
+        VaultExecuteMsg::Deposit(msg) => {
             let rec = must_pay(&info, denom.as_ref()).unwrap();
             // just dummy mint
             let rcpt_mint_msg = CosmosMsg::Custom(KujiraMsg::Denom(DenomMsg::Mint {
@@ -69,7 +105,7 @@ pub fn execute(
 
             Ok(Response::default().add_messages(vec![rcpt_mint_msg, msg]))
         }
-        ExecuteMsg::Withdraw(msg) => {
+        VaultExecuteMsg::Withdraw(msg) => {
             let rec = must_pay(&info, rcpt_token_denom.as_ref()).unwrap();
             let rcpt_burn_msg = CosmosMsg::Custom(KujiraMsg::Denom(DenomMsg::Burn {
                 denom: rcpt_token_denom.clone(),
@@ -83,7 +119,7 @@ pub fn execute(
 
             Ok(Response::default().add_messages(vec![rcpt_burn_msg, msg]))
         }
-        ExecuteMsg::Borrow(msg) => {
+        VaultExecuteMsg::Borrow(msg) => {
             let (_rate, debt_share_ratio) = rates(deps.as_ref(), env.block.time)?;
             let debt_shares = msg.amount.div_ceil(debt_share_ratio);
             TS.save(deps.storage, &(env.block.time, debt_share_ratio))?;
@@ -114,7 +150,7 @@ pub fn execute(
             );
             Ok(Response::default().add_messages(vec![debt_mint_msg, borrow_msg]))
         }
-        ExecuteMsg::Repay(_) => {
+        VaultExecuteMsg::Repay(_) => {
             let mut debt_tokens = Uint128::zero();
             let mut repay_amount = Uint128::zero();
 
@@ -145,10 +181,10 @@ pub fn execute(
 
             Ok(Response::default().add_message(debt_burn_msg))
         }
-        ExecuteMsg::WhitelistMarket(_) => todo!(),
-        ExecuteMsg::UpdateMarket(_) => todo!(),
-        ExecuteMsg::UpdateConfig(_) => todo!(),
-        ExecuteMsg::UpdateInterest(_) => todo!(),
+        VaultExecuteMsg::WhitelistMarket(_) => todo!(),
+        VaultExecuteMsg::UpdateMarket(_) => todo!(),
+        VaultExecuteMsg::UpdateConfig(_) => todo!(),
+        VaultExecuteMsg::UpdateInterest(_) => todo!(),
     }
 }
 
@@ -183,6 +219,14 @@ pub fn query(deps: Deps<KujiraQuery>, env: Env, msg: QueryMsg) -> StdResult<Bina
         }),
         QueryMsg::MarketParams { .. } => todo!(),
         QueryMsg::Markets { .. } => todo!(),
-        QueryMsg::InterestParams { .. } => todo!(),
+        QueryMsg::InterestParams { .. } => match INTEREST.load(deps.storage)? {
+            Some(utilization_to_rate) => to_json_binary(&InterestParamsResponse {
+                utilization_to_rate,
+            }),
+            None => Err(StdError::parse_err(
+                "kujira_ghost::receipt_vault::QueryMsg",
+                "unknown variant `interest_params`",
+            )),
+        },
     }
 }
@@ -3,14 +3,20 @@ use std::str::FromStr;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, Timestamp, Uint128,
+    to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, Timestamp,
+    Uint128,
 };
-use cw_storage_plus::Item;
+use cw_storage_plus::Map;
 use cw_utils::one_coin;
 use kujira::{Denom, KujiraMsg, KujiraQuery};
-use unstake::{adapter::eris::ExecuteMsg, ContractError};
+use unstake::{
+    adapter::eris::{Batch, ExecuteMsg, UnbondRequestsByUserResponseItemDetails},
+    ContractError,
+};
+
+static PENDING: Map<Addr, (Timestamp, Uint128)> = Map::new("pending");
 
-static PENDING: Item<(Timestamp, Uint128)> = Item::new("pending");
+const UNBONDING_DURATION: u64 = 14 * 24 * 60 * 60;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -32,17 +38,18 @@ pub fn execute(
     let rate = Decimal::from_str("1.07375")?;
     match msg {
         ExecuteMsg::WithdrawUnbonded { .. } => {
-            let (time, pending) = PENDING.load(deps.storage)?;
+            let (time, pending) = PENDING.load(deps.storage, info.sender.clone())?;
             let amount = pending.mul_floor(rate);
-            if env.block.time.seconds() - time.seconds() < 14 * 24 * 60 * 60 {
+            if env.block.time.seconds() - time.seconds() < UNBONDING_DURATION {
                 return Ok(Response::default());
             }
+            PENDING.remove(deps.storage, info.sender.clone());
 
             Ok(Response::default().add_message(Denom::from("quote").send(&info.sender, &amount)))
         }
         ExecuteMsg::QueueUnbond { .. } => {
             let amount = one_coin(&info)?;
-            PENDING.save(deps.storage, &(env.block.time, amount.amount))?;
+            PENDING.save(deps.storage, info.sender, &(env.block.time, amount.amount))?;
 
             Ok(Response::default())
         }
@@ -51,8 +58,8 @@ pub fn execute(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(
-    _deps: Deps<KujiraQuery>,
-    _env: Env,
+    deps: Deps<KujiraQuery>,
+    env: Env,
     msg: unstake::adapter::eris::ContractQueryMsg,
 ) -> Result<Binary, ContractError> {
     match msg {
@@ -61,5 +68,33 @@ pub fn query(
                 exchange_rate: Decimal::from_str("1.07375")?,
             },
         )?),
+        unstake::adapter::eris::ContractQueryMsg::Config {} => Ok(to_json_binary(
+            &unstake::adapter::eris::ContractConfigResponse {
+                epoch_period: 0,
+                unbond_period: UNBONDING_DURATION,
+            },
+        )?),
+        unstake::adapter::eris::ContractQueryMsg::UnbondRequestsByUserDetails { user, .. } => {
+            let pending = PENDING.may_load(deps.storage, deps.api.addr_validate(&user)?)?;
+            let requests: Vec<UnbondRequestsByUserResponseItemDetails> = pending
+                .map(|(time, shares)| {
+                    let end = time.plus_seconds(UNBONDING_DURATION);
+                    UnbondRequestsByUserResponseItemDetails {
+                        id: 1,
+                        shares,
+                        state: if env.block.time < end {
+                            "UNBONDING".to_string()
+                        } else {
+                            "COMPLETED".to_string()
+                        },
+                        batch: Some(Batch {
+                            est_unbond_end_time: end.seconds(),
+                        }),
+                    }
+                })
+                .into_iter()
+                .collect();
+            Ok(to_json_binary(&requests)?)
+        }
     }
 }
@@ -1,8 +1,12 @@
 use std::str::FromStr;
 
-use cosmwasm_std::{coin, coins, Addr, Coin, Decimal, Event, Uint128};
-use cw_multi_test::{ContractWrapper, Executor};
-use kujira::{fee_address, Denom, HumanPrice};
+use cosmwasm_std::{
+    coin, coins, testing::mock_env, to_json_binary, Addr, Coin, Decimal, Empty, Event, Timestamp,
+    Uint128,
+};
+use cw20::{Cw20Coin, Cw20ExecuteMsg};
+use cw_multi_test::{AppResponse, ContractWrapper, Executor};
+use kujira::{fee_address, CallbackData, Denom, HumanPrice};
 use kujira_ghost::common::OracleType;
 use kujira_rs_testing::{
     api::MockApiBech32,
@@ -10,8 +14,20 @@ use kujira_rs_testing::{
 };
 use monetary::AmountU128;
 use unstake::{
-    controller::{DelegatesResponse, ExecuteMsg, OfferResponse, QueryMsg, StatusResponse},
+    adapter::{generic::Generic, Adapter, UnbondStatus, Unstake},
+    broker::{Offer, MAX_DURATION},
+    controller::{
+        AskAsset, Change, ConfigResponse, ConfigUpdate, DelegatesFilter, DelegatesResponse,
+        ExecuteMsg, FeeRecipient, FeeTier, FeeTierMember, FeeTierMembersResponse, KeeperReward,
+        LocksResponse, OfferForOutputResponse, OfferResponse, Operation, PositionStatus,
+        PositionsResponse, QueryMsg, RatesResponse, ReceiveMsg, Referral, ReferralsResponse, Route,
+        RouteStatus, RoutesResponse, StatusResponse, UnclaimedFeesResponse,
+    },
     denoms::Base,
+    ownership::PendingOwner,
+    pricing::PricingModel,
+    timelock::PendingChangesResponse,
+    ContractError,
 };
 
 struct Contracts {
@@ -41,7 +57,8 @@ fn setup(
         crate::contract::execute,
         crate::contract::instantiate,
         crate::contract::query,
-    );
+    )
+    .with_reply(crate::contract::reply);
     let ghost_code = ContractWrapper::new(
         crate::testing::ghost::execute,
         crate::testing::ghost::instantiate,
@@ -97,6 +114,8 @@ fn setup(
                 owner: app.api().addr_make("owner"),
                 ghost_vault_addr: vault_address.clone(),
                 base_denom: monetary::Denom::new("quote"),
+                guardian: None,
+                timelock: Some(0),
             },
             &[],
             "reserve",
@@ -115,13 +134,20 @@ fn setup(
                 delegate_code_id,
                 vault_address: vault_address.clone(),
                 reserve_address: reserve_address.clone(),
-                ask_denom: monetary::Denom::new("base"),
+                ask_denom: AskAsset::Native(monetary::Denom::new("base")),
                 offer_denom: monetary::Denom::new("quote"),
                 adapter: unstake::adapter::Adapter::Eris(provider_address.clone().into()),
                 // 2 weeks
                 unbonding_duration: 2 * 7 * 24 * 60 * 60,
                 // 3%
                 min_rate: Decimal::from_str("0.03").unwrap(),
+                pricing: None,
+                vault_max_interest: None,
+                keeper_reward: None,
+                max_rate_change: None,
+                guardian: None,
+                timelock: Some(0),
+                fee_recipients: None,
             },
             &[],
             "controller",
@@ -130,16 +156,12 @@ fn setup(
         .unwrap();
 
     // Add controller to reserve
-    app.execute_contract(
-        app.api().addr_make("owner"),
-        reserve_address.clone(),
-        &unstake::reserve::ExecuteMsg::AddController {
-            controller: controller_address.clone(),
-            limit: controller_limit,
-        },
-        &[],
-    )
-    .unwrap();
+    add_controller(
+        &mut app,
+        &reserve_address,
+        &controller_address,
+        controller_limit,
+    );
 
     (
         app,
@@ -152,6 +174,69 @@ fn setup(
     )
 }
 
+/// Whitelists a controller on the reserve, which has no timelock in the setup
+fn add_controller(
+    app: &mut CustomApp,
+    reserve: &Addr,
+    controller: &Addr,
+    limit: Option<AmountU128<Base>>,
+) {
+    app.execute_contract(
+        app.api().addr_make("owner"),
+        reserve.clone(),
+        &unstake::reserve::ExecuteMsg::ScheduleChange {
+            change: unstake::reserve::Change::Controller {
+                controller: controller.clone(),
+                limit,
+            },
+            effective: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let pending: PendingChangesResponse<unstake::reserve::Change> = app
+        .wrap()
+        .query_wasm_smart(reserve, &unstake::reserve::QueryMsg::PendingChanges {})
+        .unwrap();
+    app.execute_contract(
+        app.api().addr_make("owner"),
+        reserve.clone(),
+        &unstake::reserve::ExecuteMsg::ExecuteChange {
+            id: pending.changes.last().unwrap().id,
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+/// Schedules a change on the controller and executes it, which the setup's lack of a timelock
+/// allows in the same block
+fn change(app: &mut CustomApp, contracts: &Contracts, change: Change) -> AppResponse {
+    app.execute_contract(
+        app.api().addr_make("owner"),
+        contracts.controller.clone(),
+        &ExecuteMsg::ScheduleChange {
+            change,
+            effective: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let pending: PendingChangesResponse<Change> = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::PendingChanges {})
+        .unwrap();
+    app.execute_contract(
+        app.api().addr_make("keeper"),
+        contracts.controller.clone(),
+        &ExecuteMsg::ExecuteChange {
+            id: pending.changes.last().unwrap().id,
+        },
+        &[],
+    )
+    .unwrap()
+}
+
 fn fund_reserve(app: &mut CustomApp, funder: Addr, reserve: Addr, amount: Uint128, denom: &str) {
     app.execute_contract(
         funder,
@@ -189,6 +274,8 @@ fn quote_initial() {
             contracts.controller,
             &QueryMsg::Offer {
                 amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: None,
+                sender: None,
             },
         )
         .unwrap();
@@ -229,6 +316,8 @@ fn quote_reserve_clamped() {
             contracts.controller,
             &QueryMsg::Offer {
                 amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: None,
+                sender: None,
             },
         )
         .unwrap();
@@ -248,6 +337,191 @@ fn quote_reserve_clamped() {
     // Offer amount 10737 - 1034 = 9,703
     assert_eq!(quote.amount, AmountU128::new(Uint128::from(9701u128)));
     assert_eq!(quote.fee, AmountU128::new(Uint128::from(1036u128)));
+
+    // The whole reserve is allocated, and the shortfall is added to the fee
+    assert!(quote.clamped);
+    assert_eq!(quote.rate, Decimal::one());
+    assert_eq!(
+        quote.offer.reserve_allocation,
+        AmountU128::new(Uint128::from(200u128))
+    );
+    assert_eq!(quote.offer.unbond_amount, AmountU128::new(10000u128.into()));
+    assert_eq!(quote.rates.vault_interest, Decimal::one());
+    // 1036 / 9701 * 26.0714285714
+    assert_eq!(
+        quote.effective_rate,
+        Decimal::from_str("2.784249046490052547").unwrap()
+    );
+}
+
+#[test]
+fn quote_vault_interest_params() {
+    // Same as quote_reserve_clamped, but the vault publishes a lower max rate of 150%
+    let api = MockApiBech32::new("kujira");
+
+    let balances = vec![(api.addr_make("funder"), coins(100000000u128, "quote"))];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        200u128.into(),
+        "quote",
+    );
+
+    app.execute_contract(
+        api.addr_make("ghost-owner"),
+        contracts.ghost.clone(),
+        &crate::testing::ghost::ExecuteMsg::SetInterestParams {
+            utilization_to_rate: Some(vec![
+                (Decimal::zero(), Decimal::zero()),
+                (Decimal::percent(80), Decimal::percent(20)),
+                (Decimal::one(), Decimal::percent(150)),
+            ]),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let rates: RatesResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Rates {})
+        .unwrap();
+    assert_eq!(rates.vault_max_interest, Decimal::percent(150));
+    assert_eq!(rates.vault_interest_curve.map(|x| x.len()), Some(3));
+
+    let quote: OfferResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller,
+            &QueryMsg::Offer {
+                amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+
+    // Mock redemption rate of 1.07375
+    // current interest rate: 100%
+    // Max interest rate of 150%
+    // Default 2 week unbonding
+
+    // List price 10737
+    // Interest amount 412
+    // Reserve requirement 10737 * 0.5 * 0.03835616438 = 206
+    // Available reserve = 200, shortfall of 6
+    // Offer amount 10737 - 412 - 6 = 10,319
+    assert_eq!(quote.amount, AmountU128::new(Uint128::from(10319u128)));
+    assert_eq!(quote.fee, AmountU128::new(Uint128::from(418u128)));
+}
+
+#[test]
+fn quote_vault_interest_fallback() {
+    // When the vault doesn't publish its interest curve, the configured ceiling is used
+    let api = MockApiBech32::new("kujira");
+
+    let balances = vec![(api.addr_make("funder"), coins(100000000u128, "quote"))];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        200u128.into(),
+        "quote",
+    );
+
+    app.execute_contract(
+        api.addr_make("ghost-owner"),
+        contracts.ghost.clone(),
+        &crate::testing::ghost::ExecuteMsg::SetInterestParams {
+            utilization_to_rate: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Default fallback of 300%
+    let rates: RatesResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Rates {})
+        .unwrap();
+    assert_eq!(rates.vault_max_interest, Decimal::percent(300));
+    assert_eq!(rates.vault_interest_curve, None);
+
+    let vault_change = |vault_max_interest| Change::Config {
+        protocol_fee: None,
+        protocol_fee_address: None,
+        protocol_fee_address: None,
+        delegate_code_id: None,
+        vault_max_interest: Some(vault_max_interest),
+    };
+    for vault_max_interest in [Decimal::zero(), Decimal::percent(1001)] {
+        let err = app
+            .execute_contract(
+                app.api().addr_make("owner"),
+                contracts.controller.clone(),
+                &ExecuteMsg::ScheduleChange {
+                    change: vault_change(vault_max_interest),
+                    effective: None,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidVaultMaxInterest { .. }
+        ));
+    }
+
+    change(&mut app, &contracts, vault_change(Decimal::percent(150)));
+
+    let rates: RatesResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Rates {})
+        .unwrap();
+    assert_eq!(rates.vault_max_interest, Decimal::percent(150));
+
+    let quote: OfferResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Offer {
+                amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+
+    // Identical to quote_vault_interest_params
+    assert_eq!(quote.amount, AmountU128::new(Uint128::from(10319u128)));
+    assert_eq!(quote.fee, AmountU128::new(Uint128::from(418u128)));
+
+    // At the highest ceiling over the longest unbonding, the reserve shortfall exceeds the
+    // value of the Unstake, which is an error rather than a panic
+    change(&mut app, &contracts, vault_change(Decimal::percent(1000)));
+    change(
+        &mut app,
+        &contracts,
+        Change::Broker {
+            min_rate: None,
+            duration: Some(MAX_DURATION),
+            pricing: None,
+        },
+    );
+    let err = app
+        .wrap()
+        .query_wasm_smart::<OfferResponse>(
+            contracts.controller,
+            &QueryMsg::Offer {
+                amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: None,
+                sender: None,
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("RateOverflow"));
 }
 
 #[test]
@@ -271,6 +545,8 @@ fn quote_unclamped() {
             contracts.controller,
             &QueryMsg::Offer {
                 amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: None,
+                sender: None,
             },
         )
         .unwrap();
@@ -288,6 +564,101 @@ fn quote_unclamped() {
     // Offer amount 10737 - 411 = 9,703
     assert_eq!(quote.amount, AmountU128::new(Uint128::from(10325u128)));
     assert_eq!(quote.fee, AmountU128::new(Uint128::from(412u128)));
+
+    // Reserve allocation of 10737 * 0.07671232876 to cover the max rate
+    assert!(!quote.clamped);
+    assert_eq!(quote.rate, Decimal::one());
+    assert_eq!(
+        quote.offer.reserve_allocation,
+        AmountU128::new(Uint128::from(824u128))
+    );
+    // 412 / 10325 * 26.0714285714
+    assert_eq!(
+        quote.effective_rate,
+        Decimal::from_str("1.040332065029401584").unwrap()
+    );
+}
+
+#[test]
+fn quote_for_output() {
+    let api = MockApiBech32::new("kujira");
+
+    let balances = vec![(api.addr_make("funder"), coins(100000000u128, "quote"))];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        200u128.into(),
+        "quote",
+    );
+
+    // Reserve clamped, the inverse of quote_reserve_clamped
+    // 9999 unbonded: list price 10736, fee 412 + shortfall 624, offer amount 9700
+    let quote: OfferForOutputResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::OfferForOutput {
+                amount: AmountU128::new(Uint128::from(9701u128)),
+                ask_denom: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(quote.amount, AmountU128::new(Uint128::from(10000u128)));
+    assert_eq!(
+        quote.offer.offer_amount,
+        AmountU128::new(Uint128::from(9701u128))
+    );
+    assert_eq!(quote.offer.fee, AmountU128::new(Uint128::from(1036u128)));
+    assert_eq!(quote.model, PricingModel::Spot {});
+
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        19800u128.into(),
+        "quote",
+    );
+
+    // Unclamped, the inverse of quote_unclamped
+    // 9999 unbonded: list price 10736, fee 412, offer amount 10324
+    let quote: OfferForOutputResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::OfferForOutput {
+                amount: AmountU128::new(Uint128::from(10325u128)),
+                ask_denom: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(quote.amount, AmountU128::new(Uint128::from(10000u128)));
+    assert_eq!(
+        quote.offer.offer_amount,
+        AmountU128::new(Uint128::from(10325u128))
+    );
+    assert_eq!(quote.offer.fee, AmountU128::new(Uint128::from(412u128)));
+
+    // 10001 unbonded: list price 10738, fee 412, offer amount 10326
+    let quote: OfferForOutputResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller,
+            &QueryMsg::OfferForOutput {
+                amount: AmountU128::new(Uint128::from(10326u128)),
+                ask_denom: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(quote.amount, AmountU128::new(Uint128::from(10001u128)));
+    assert_eq!(
+        quote.offer.offer_amount,
+        AmountU128::new(Uint128::from(10326u128))
+    );
 }
 
 #[test]
@@ -305,16 +676,15 @@ fn quote_min_rate_clamped() {
         "quote",
     );
 
-    app.execute_contract(
-        app.api().addr_make("owner"),
-        contracts.controller.clone(),
-        &ExecuteMsg::UpdateBroker {
+    change(
+        &mut app,
+        &contracts,
+        Change::Broker {
             min_rate: Some(Decimal::from_str("1.1").unwrap()),
             duration: None,
+            pricing: None,
         },
-        &[],
-    )
-    .unwrap();
+    );
 
     let quote: OfferResponse = app
         .wrap()
@@ -322,6 +692,8 @@ fn quote_min_rate_clamped() {
             contracts.controller,
             &QueryMsg::Offer {
                 amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: None,
+                sender: None,
             },
         )
         .unwrap();
@@ -342,6 +714,75 @@ fn quote_min_rate_clamped() {
     assert_eq!(quote.fee, AmountU128::new(Uint128::from(454u128)));
 }
 
+#[test]
+fn quote_pricing_model() {
+    // Quote with a flat rate pricing model, below the current vault rate
+    let api = MockApiBech32::new("kujira");
+
+    let balances = vec![(api.addr_make("funder"), coins(100000000u128, "quote"))];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+
+    let quote: OfferResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Offer {
+                amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(quote.model, PricingModel::Spot {});
+
+    change(
+        &mut app,
+        &contracts,
+        Change::Broker {
+            min_rate: None,
+            duration: None,
+            pricing: Some(PricingModel::Flat {
+                rate: Decimal::percent(50),
+            }),
+        },
+    );
+
+    let quote: OfferResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller,
+            &QueryMsg::Offer {
+                amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+
+    // Mock redemption rate of 1.07375
+    // flat interest rate: 50%
+    // Default 2 week unbonding
+
+    // 0.01917808219 interest
+    // List price 10737, interest 206
+    // Offer amount 10737 - 206 = 10,531
+    assert_eq!(
+        quote.model,
+        PricingModel::Flat {
+            rate: Decimal::percent(50)
+        }
+    );
+    assert_eq!(quote.amount, AmountU128::new(Uint128::from(10531u128)));
+    assert_eq!(quote.fee, AmountU128::new(Uint128::from(206u128)));
+}
+
 #[test]
 fn execute_offer() {
     // Quote where we have plenty of reserves, and the minimum rate is highter than the current rate
@@ -377,17 +818,64 @@ fn execute_offer() {
         &ExecuteMsg::Unstake {
             callback: None,
             max_fee: AmountU128::new(Uint128::from(400u128)),
+            min_receive: None,
+            deadline: None,
+            referrer: None,
         },
         &coins(10000u128, "base"),
     )
     .unwrap_err();
 
+    // Executing with min_receive = 10326 > 10325 should fail
+    let err = app
+        .execute_contract(
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Unstake {
+                callback: None,
+                max_fee: AmountU128::new(Uint128::from(500u128)),
+                min_receive: Some(AmountU128::new(Uint128::from(10326u128))),
+                deadline: None,
+                referrer: None,
+            },
+            &coins(10000u128, "base"),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MinReceiveNotMet {}
+    ));
+
+    // Executing after the deadline should fail
+    let deadline = app.block_info().time.minus_seconds(1);
+    let err = app
+        .execute_contract(
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Unstake {
+                callback: None,
+                max_fee: AmountU128::new(Uint128::from(500u128)),
+                min_receive: None,
+                deadline: Some(deadline),
+                referrer: None,
+            },
+            &coins(10000u128, "base"),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DeadlineExceeded {}
+    ));
+
     app.execute_contract(
         api.addr_make("unstaker"),
         contracts.controller.clone(),
         &ExecuteMsg::Unstake {
             callback: None,
             max_fee: AmountU128::new(Uint128::from(500u128)),
+            min_receive: Some(AmountU128::new(Uint128::from(10325u128))),
+            deadline: Some(app.block_info().time),
+            referrer: None,
         },
         &coins(10000u128, "base"),
     )
@@ -395,7 +883,14 @@ fn execute_offer() {
 
     let delegates: DelegatesResponse = app
         .wrap()
-        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Delegates {})
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
         .unwrap();
     assert_eq!(delegates.delegates.len(), 1);
     let (delegate, _) = delegates.delegates[0].clone();
@@ -492,6 +987,9 @@ fn execute_unfunded_offer() {
         &ExecuteMsg::Unstake {
             callback: None,
             max_fee: amount,
+            min_receive: None,
+            deadline: None,
+            referrer: None,
         },
         &coins(10000u128, "base"),
     )
@@ -499,9 +997,16 @@ fn execute_unfunded_offer() {
 
     let delegates: DelegatesResponse = app
         .wrap()
-        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Delegates {})
-        .unwrap();
-    assert_eq!(delegates.delegates.len(), 1);
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(delegates.delegates.len(), 1);
     let (delegate, _) = delegates.delegates[0].clone();
 
     let unstaker_balances = query_balances(&app, api.addr_make("unstaker"));
@@ -587,6 +1092,9 @@ fn close_offer() {
         &ExecuteMsg::Unstake {
             callback: None,
             max_fee: amount,
+            min_receive: None,
+            deadline: None,
+            referrer: None,
         },
         &coins(10000u128, "base"),
     )
@@ -594,7 +1102,14 @@ fn close_offer() {
 
     let delegates: DelegatesResponse = app
         .wrap()
-        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Delegates {})
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
         .unwrap();
     assert_eq!(delegates.delegates.len(), 1);
     let (delegate, _) = delegates.delegates[0].clone();
@@ -631,7 +1146,7 @@ fn close_offer() {
     app.execute_contract(
         api.addr_make("random"),
         delegate.clone(),
-        &unstake::delegate::ExecuteMsg::Complete {},
+        &unstake::delegate::ExecuteMsg::Complete { keeper: None },
         &[],
     )
     .unwrap();
@@ -698,9 +1213,7 @@ fn close_offer() {
 }
 
 #[test]
-fn close_early_offer() {
-    // Make sure that we bail if the offer attempts to close early, and there's nothing returned
-    // from the provider to repay ghost
+fn positions() {
     let api = MockApiBech32::new("kujira");
     let balances = vec![
         (api.addr_make("funder"), coins(100000000u128, "quote")),
@@ -715,15 +1228,12 @@ fn close_early_offer() {
         20000u128.into(),
         "quote",
     );
-
-    // Make sure that the provider has enough tokens to return once unbonding is complete
     app.send_tokens(
         api.addr_make("funder"),
         contracts.provider.clone(),
         &coins(500000u128, "quote"),
     )
     .unwrap();
-
     app.execute_contract(
         api.addr_make("lender"),
         contracts.ghost.clone(),
@@ -734,48 +1244,193 @@ fn close_early_offer() {
     )
     .unwrap();
 
-    let amount = AmountU128::new(Uint128::from(10000u128));
-
+    let start = app.block_info().time;
     app.execute_contract(
         api.addr_make("unstaker"),
         contracts.controller.clone(),
         &ExecuteMsg::Unstake {
             callback: None,
-            max_fee: amount,
+            max_fee: AmountU128::new(Uint128::from(10000u128)),
+            min_receive: None,
+            deadline: None,
+            referrer: None,
         },
         &coins(10000u128, "base"),
     )
     .unwrap();
 
-    let delegates: DelegatesResponse = app
+    let positions: PositionsResponse = app
         .wrap()
-        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Delegates {})
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Positions {
+                start_after: None,
+                limit: None,
+            },
+        )
         .unwrap();
-    assert_eq!(delegates.delegates.len(), 1);
-    let (delegate, _) = delegates.delegates[0].clone();
+    assert_eq!(positions.positions.len(), 1);
+    let position = positions.positions[0].clone();
+    assert_eq!(position.unstaker, Some(api.addr_make("unstaker")));
+    assert_eq!(
+        position.offer.offer_amount,
+        AmountU128::new(Uint128::from(10325u128))
+    );
+    // (10325 - 824) / 1.12
+    assert_eq!(
+        position.debt_tokens,
+        AmountU128::new(Uint128::from(8484u128))
+    );
+    assert_eq!(position.start, start);
+    assert_eq!(position.maturity, start.plus_days(14));
+    assert_eq!(position.status, PositionStatus::Unbonding);
+
+    let by_user: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::PositionsByUser {
+                unstaker: api.addr_make("unstaker"),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(by_user.positions, vec![position.clone()]);
+
+    let by_other: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::PositionsByUser {
+                unstaker: api.addr_make("funder"),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(by_other.positions, vec![]);
+
+    // Not yet matured
+    let matured: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::MaturedPositions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(matured.positions, vec![]);
 
-    // 2 weeks later, ghost debt rate should have increased
     app.update_block(|x| {
-        x.time = x.time.plus_days(13);
+        x.time = x.time.plus_days(14);
     });
 
+    let matured: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::MaturedPositions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(matured.positions, vec![position.clone()]);
+
+    let matured: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::MaturedPositions {
+                start_after: Some(position.delegate.clone()),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(matured.positions, vec![]);
+
     app.execute_contract(
         api.addr_make("random"),
-        delegate.clone(),
-        &unstake::delegate::ExecuteMsg::Complete {},
+        position.delegate.clone(),
+        &unstake::delegate::ExecuteMsg::Complete { keeper: None },
         &[],
     )
-    .unwrap_err();
+    .unwrap();
+
+    // The position is archived, and no longer open, matured or listed as a delegate
+    let positions: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Positions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(positions.positions, vec![]);
+
+    let positions: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::CompletedPositions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(positions.positions[0].delegate, position.delegate);
+    assert_eq!(positions.positions[0].status, PositionStatus::Completed);
+
+    let err = app
+        .execute_contract(
+            api.addr_make("random"),
+            contracts.controller.clone(),
+            &ExecuteMsg::MigratePositions { limit: None },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MigrationComplete {}
+    ));
+
+    let matured: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::MaturedPositions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(matured.positions, vec![]);
+
+    let delegates: DelegatesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller,
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(delegates.delegates, vec![]);
 }
 
 #[test]
-fn close_losing_offer() {
-    // Now let's finally test a loss-making completion
-    // Instead of updating the ghost rate, we will just make it complete the unbonding a week too late
+fn delegates_paginated() {
     let api = MockApiBech32::new("kujira");
     let balances = vec![
         (api.addr_make("funder"), coins(100000000u128, "quote")),
-        (api.addr_make("unstaker"), coins(10000u128, "base")),
+        (api.addr_make("unstaker"), coins(20000u128, "base")),
         (api.addr_make("lender"), coins(100000000u128, "quote")),
     ];
     let (mut app, contracts) = setup(balances, None);
@@ -786,15 +1441,6 @@ fn close_losing_offer() {
         20000u128.into(),
         "quote",
     );
-
-    // Make sure that the provider has enough tokens to return once unbonding is complete
-    app.send_tokens(
-        api.addr_make("funder"),
-        contracts.provider.clone(),
-        &coins(500000u128, "quote"),
-    )
-    .unwrap();
-
     app.execute_contract(
         api.addr_make("lender"),
         contracts.ghost.clone(),
@@ -805,78 +1451,84 @@ fn close_losing_offer() {
     )
     .unwrap();
 
-    let amount = AmountU128::new(Uint128::from(10000u128));
-
-    app.execute_contract(
-        api.addr_make("unstaker"),
-        contracts.controller.clone(),
-        &ExecuteMsg::Unstake {
-            callback: None,
-            max_fee: amount,
-        },
-        &coins(10000u128, "base"),
-    )
-    .unwrap();
-
-    let delegates: DelegatesResponse = app
-        .wrap()
-        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Delegates {})
+    // Two unstakes, a day apart
+    let mut starts = vec![];
+    for _ in 0..2 {
+        starts.push(app.block_info().time);
+        app.execute_contract(
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Unstake {
+                callback: None,
+                max_fee: AmountU128::new(Uint128::from(10000u128)),
+                min_receive: None,
+                deadline: None,
+                referrer: None,
+            },
+            &coins(10000u128, "base"),
+        )
         .unwrap();
-    assert_eq!(delegates.delegates.len(), 1);
-    let (delegate, _) = delegates.delegates[0].clone();
-
-    // 2 weeks later, ghost debt rate should have increased
-    app.update_block(|x| {
-        x.time = x.time.plus_days(21);
-    });
-    let ghost_balances = query_balances(&app, contracts.ghost.clone());
-    println!("{:?}", ghost_balances);
-
-    app.execute_contract(
-        api.addr_make("random"),
-        delegate.clone(),
-        &unstake::delegate::ExecuteMsg::Complete {},
-        &[],
-    )
-    .unwrap();
+        app.update_block(|x| {
+            x.height += 1;
+            x.time = x.time.plus_days(1);
+        });
+    }
 
-    let reserve_balances = query_balances(&app, contracts.reserve);
-    let delegate_balances = query_balances(&app, delegate);
-    let provider_balances = query_balances(&app, contracts.provider);
-    let ghost_balances = query_balances(&app, contracts.ghost.clone());
+    let query = |app: &CustomApp,
+                 start_after: Option<Addr>,
+                 limit: Option<u32>,
+                 filter: Option<DelegatesFilter>|
+     -> DelegatesResponse {
+        app.wrap()
+            .query_wasm_smart(
+                contracts.controller.clone(),
+                &QueryMsg::Delegates {
+                    start_after,
+                    limit,
+                    filter,
+                },
+            )
+            .unwrap()
+    };
 
-    // 21 days will be a total of 5.7534% interest
-    // So we'll pay 5.7534 on (10326 - 823) = 547,
-    // but have an allocated fee of 3.8356 on the total value = 10737 * 3.8356 = 411
-    // so we have a loss  here of 411 - 547 = -136 (rounding)
-    assert_eq!(
-        reserve_balances,
-        coins(19864u128, format!("factory/{}/urcpt", contracts.ghost))
-    );
+    // Ordered by maturity
+    let all = query(&app, None, None, None);
+    assert_eq!(all.delegates.len(), 2);
+    assert_eq!(all.delegates[0].1, starts[0]);
+    assert_eq!(all.delegates[1].1, starts[1]);
+    assert_eq!(all.next, None);
 
-    // delegate should now be empty
-    assert_eq!(delegate_balances, vec![]);
+    let first = query(&app, None, Some(1), None);
+    assert_eq!(first.delegates, vec![all.delegates[0].clone()]);
+    assert_eq!(first.next, Some(all.delegates[0].0.clone()));
+    let second = query(&app, first.next, Some(1), None);
+    assert_eq!(second.delegates, vec![all.delegates[1].clone()]);
+    let third = query(&app, second.next, Some(1), None);
+    assert_eq!(third.delegates, vec![]);
+    assert_eq!(third.next, None);
 
-    // Provider should have received the base for unbonding, plus the surplus from the original funding
-    // (500,000 - (10000 * 1.07375))
-    assert_eq!(
-        provider_balances,
-        vec![coin(10000u128, "base"), coin(489263u128, "quote")]
-    );
+    // 2 days after the second unstake, neither has matured
+    let matured = query(&app, None, None, Some(DelegatesFilter::Matured));
+    assert_eq!(matured.delegates, vec![]);
+    let unbonding = query(&app, None, None, Some(DelegatesFilter::Unbonding));
+    assert_eq!(unbonding.delegates, all.delegates);
 
-    // And ghost should have the borrowed amount returned with interest
-    // 100000000 + 20000 + 547 - 136 = 100020411 + 1 for rounding
-    // 9503 over 3 weeks at 100% = 3 / 52 = 549.
-    assert_eq!(ghost_balances, coins(100020412u128, "quote"));
+    // The first matures 14 days after it was created
+    app.update_block(|x| {
+        x.time = starts[0].plus_days(14);
+    });
+    let matured = query(&app, None, None, Some(DelegatesFilter::Matured));
+    assert_eq!(matured.delegates, vec![all.delegates[0].clone()]);
+    let unbonding = query(&app, None, None, Some(DelegatesFilter::Unbonding));
+    assert_eq!(unbonding.delegates, vec![all.delegates[1].clone()]);
 }
 
 #[test]
-fn reserves() {
-    // Test that minting of the reserve receipt token is correct, and redeemed correctly
+fn complete_matured() {
     let api = MockApiBech32::new("kujira");
     let balances = vec![
         (api.addr_make("funder"), coins(100000000u128, "quote")),
-        (api.addr_make("unstaker"), coins(10000u128, "base")),
+        (api.addr_make("unstaker"), coins(20000u128, "base")),
         (api.addr_make("lender"), coins(100000000u128, "quote")),
     ];
     let (mut app, contracts) = setup(balances, None);
@@ -887,36 +1539,6 @@ fn reserves() {
         20000u128.into(),
         "quote",
     );
-
-    let rct_balance = app
-        .wrap()
-        .query_balance(
-            api.addr_make("funder"),
-            format!("factory/{}/ursv", contracts.reserve),
-        )
-        .unwrap();
-    assert_eq!(rct_balance.amount, Uint128::from(20000u128));
-
-    // Second deposit, scaled with no extra revenue
-    fund_reserve(
-        &mut app,
-        api.addr_make("funder"),
-        contracts.reserve.clone(),
-        20000u128.into(),
-        "quote",
-    );
-
-    let rct_balance = app
-        .wrap()
-        .query_balance(
-            api.addr_make("funder"),
-            format!("factory/{}/ursv", contracts.reserve),
-        )
-        .unwrap();
-    assert_eq!(rct_balance.amount, Uint128::from(40000u128));
-
-    // Do an unstake to increase the amount of reserves
-
     app.execute_contract(
         api.addr_make("lender"),
         contracts.ghost.clone(),
@@ -927,74 +1549,3124 @@ fn reserves() {
     )
     .unwrap();
 
-    let amount = AmountU128::new(Uint128::from(10000u128));
-
-    app.execute_contract(
-        api.addr_make("unstaker"),
-        contracts.controller.clone(),
-        &ExecuteMsg::Unstake {
-            callback: None,
-            max_fee: amount,
-        },
-        &coins(10000u128, "base"),
-    )
-    .unwrap();
+    for _ in 0..2 {
+        app.execute_contract(
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Unstake {
+                callback: None,
+                max_fee: AmountU128::new(Uint128::from(10000u128)),
+                min_receive: None,
+                deadline: None,
+                referrer: None,
+            },
+            &coins(10000u128, "base"),
+        )
+        .unwrap();
+        app.update_block(|x| {
+            x.height += 1;
+            x.time = x.time.plus_days(1);
+        });
+    }
 
-    // Check we can't withdraw everything whilst reserves are being used
-    app.execute_contract(
-        api.addr_make("funder"),
-        contracts.reserve.clone(),
-        &unstake::reserve::ExecuteMsg::Withdraw { callback: None },
-        &coins(40000u128, format!("factory/{}/ursv", contracts.reserve)),
-    )
-    .unwrap_err();
+    let delegates: DelegatesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
+        .unwrap();
+    let first = delegates.delegates[0].0.clone();
+    let second = delegates.delegates[1].0.clone();
 
-    // ...but that a smaller withdrawal also considers the consumed reserves in calculations
+    // Nothing has matured yet
     let res = app
         .execute_contract(
-            api.addr_make("funder"),
-            contracts.reserve.clone(),
-            &unstake::reserve::ExecuteMsg::Withdraw { callback: None },
-            &coins(20000u128, format!("factory/{}/ursv", contracts.reserve)),
+            api.addr_make("keeper"),
+            contracts.controller.clone(),
+            &ExecuteMsg::CompleteMatured {
+                start_after: None,
+                limit: None,
+            },
+            &[],
         )
         .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/complete_matured").add_attributes(vec![
+            ("completed_count", "0".to_string()),
+            ("failed_count", "0".to_string()),
+        ]),
+    );
 
-    res.assert_event(&Event::new("transfer").add_attributes(vec![
-        ("recipient", api.addr_make("funder").to_string()),
-        ("sender", contracts.reserve.to_string()),
-        ("amount", "20000quote".to_string()),
-    ]));
-
-    // 2 weeks later, ghost debt rate should have increased
+    // Both matured, but the provider can't yet return either of the unbondings
     app.update_block(|x| {
-        x.time = x.time.plus_days(14);
+        x.time = x.time.plus_days(13);
     });
-
-    let delegates: DelegatesResponse = app
-        .wrap()
-        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Delegates {})
+    let res = app
+        .execute_contract(
+            api.addr_make("keeper"),
+            contracts.controller.clone(),
+            &ExecuteMsg::CompleteMatured {
+                start_after: None,
+                limit: Some(1),
+            },
+            &[],
+        )
         .unwrap();
-    let (delegate, _) = delegates.delegates[0].clone();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/complete_matured").add_attributes(vec![
+            ("completed_count", "0".to_string()),
+            ("failed_count", "1".to_string()),
+            ("failed", first.to_string()),
+            ("next", first.to_string()),
+        ]),
+    );
 
-    // Make sure that the mocked LSD provider has enough tokens to return once unbonding is complete
+    // The provider only has enough to return one of the unbondings. The stuck first delegate is
+    // paged past, so the second can complete
     app.send_tokens(
         api.addr_make("funder"),
         contracts.provider.clone(),
-        &coins(500000u128, "quote"),
+        &coins(15000u128, "quote"),
     )
     .unwrap();
 
+    let res = app
+        .execute_contract(
+            api.addr_make("keeper"),
+            contracts.controller.clone(),
+            &ExecuteMsg::CompleteMatured {
+                start_after: Some(first.clone()),
+                limit: Some(1),
+            },
+            &[],
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/complete_matured").add_attributes(vec![
+            ("completed_count", "1".to_string()),
+            ("failed_count", "0".to_string()),
+            ("completed", second.to_string()),
+            ("next", second.to_string()),
+        ]),
+    );
+
+    let res = app
+        .execute_contract(
+            api.addr_make("keeper"),
+            contracts.controller.clone(),
+            &ExecuteMsg::CompleteMatured {
+                start_after: None,
+                limit: None,
+            },
+            &[],
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/complete_matured/failure")
+            .add_attributes(vec![("delegate", first.to_string())]),
+    );
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/complete_matured").add_attributes(vec![
+            ("completed_count", "0".to_string()),
+            ("failed_count", "1".to_string()),
+            ("failed", first.to_string()),
+            ("next", "".to_string()),
+        ]),
+    );
+
+    let delegates: DelegatesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: Some(DelegatesFilter::Matured),
+            },
+        )
+        .unwrap();
+    assert_eq!(delegates.delegates.len(), 1);
+    assert_eq!(delegates.delegates[0].0, first);
+
+    // Once the provider has the funds, the remaining delegate can be completed
+    app.send_tokens(
+        api.addr_make("funder"),
+        contracts.provider.clone(),
+        &coins(15000u128, "quote"),
+    )
+    .unwrap();
+
+    let res = app
+        .execute_contract(
+            api.addr_make("keeper"),
+            contracts.controller.clone(),
+            &ExecuteMsg::CompleteMatured {
+                start_after: None,
+                limit: None,
+            },
+            &[],
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/complete_matured").add_attributes(vec![
+            ("completed_count", "1".to_string()),
+            ("failed_count", "0".to_string()),
+            ("completed", first.to_string()),
+        ]),
+    );
+
+    let delegates: DelegatesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller,
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(delegates.delegates, vec![]);
+}
+
+#[test]
+fn keeper_reward() {
+    // Same as close_offer, with a keeper rewarded from the protocol fee
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("unstaker"), coins(10000u128, "base")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+    app.send_tokens(
+        api.addr_make("funder"),
+        contracts.provider.clone(),
+        &coins(500000u128, "quote"),
+    )
+    .unwrap();
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+
+    let res = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::UpdateConfig(ConfigUpdate {
+                keeper_reward: Some(KeeperReward::Fixed {
+                    amount: AmountU128::new(Uint128::from(5u128)),
+                }),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap();
+    res.assert_event(&Event::new("wasm-unstake/controller/update_config"));
+
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        contracts.controller.clone(),
+        &ExecuteMsg::Unstake {
+            callback: None,
+            max_fee: AmountU128::new(Uint128::from(10000u128)),
+            min_receive: None,
+            deadline: None,
+            referrer: None,
+        },
+        &coins(10000u128, "base"),
+    )
+    .unwrap();
+
+    let delegates: DelegatesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
+        .unwrap();
+    let (delegate, _) = delegates.delegates[0].clone();
+
+    app.update_block(|x| {
+        x.time = x.time.plus_days(14);
+    });
+
+    // A keeper can't claim the reward on behalf of another address
+    let res = app
+        .execute_contract(
+            api.addr_make("keeper"),
+            delegate,
+            &unstake::delegate::ExecuteMsg::Complete {
+                keeper: Some(api.addr_make("other")),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // 46 fee revenue, of which 11 is protocol fee. 5 of that goes to the keeper
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/complete").add_attributes(vec![
+            ("protocol_fee_amount", "6".to_string()),
+            ("reserve_fee", "35".to_string()),
+            ("keeper", api.addr_make("keeper").to_string()),
+            ("keeper_reward", "5".to_string()),
+        ]),
+    );
+    assert_eq!(
+        query_balances(&app, api.addr_make("keeper")),
+        coins(5u128, "quote")
+    );
+    assert_eq!(query_balances(&app, api.addr_make("other")), vec![]);
+    assert_eq!(query_balances(&app, fee_address()), coins(6u128, "quote"));
+}
+
+#[test]
+fn delegate_queries() {
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("unstaker"), coins(10000u128, "base")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+    app.send_tokens(
+        api.addr_make("funder"),
+        contracts.provider.clone(),
+        &coins(500000u128, "quote"),
+    )
+    .unwrap();
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+
+    let start = app.block_info().time;
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        contracts.controller.clone(),
+        &ExecuteMsg::Unstake {
+            callback: None,
+            max_fee: AmountU128::new(Uint128::from(10000u128)),
+            min_receive: None,
+            deadline: None,
+            referrer: None,
+        },
+        &coins(10000u128, "base"),
+    )
+    .unwrap();
+
+    let positions: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Positions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let position = positions.positions[0].clone();
+    let delegate = position.delegate.clone();
+
+    let controller: Addr = app
+        .wrap()
+        .query_wasm_smart(
+            delegate.clone(),
+            &unstake::delegate::QueryMsg::Controller {},
+        )
+        .unwrap();
+    assert_eq!(controller, contracts.controller);
+
+    let offer: Offer = app
+        .wrap()
+        .query_wasm_smart(delegate.clone(), &unstake::delegate::QueryMsg::Offer {})
+        .unwrap();
+    assert_eq!(offer, position.offer);
+
+    let adapter: Adapter = app
+        .wrap()
+        .query_wasm_smart(delegate.clone(), &unstake::delegate::QueryMsg::Adapter {})
+        .unwrap();
+    assert_eq!(adapter, Adapter::Eris(contracts.provider.clone().into()));
+
+    let created: Option<Timestamp> = app
+        .wrap()
+        .query_wasm_smart(delegate.clone(), &unstake::delegate::QueryMsg::Created {})
+        .unwrap();
+    assert_eq!(created, Some(start));
+
+    let balances: Vec<Coin> = app
+        .wrap()
+        .query_wasm_smart(delegate.clone(), &unstake::delegate::QueryMsg::Balances {})
+        .unwrap();
+    assert_eq!(
+        balances,
+        coins(8484u128, format!("factory/{}/udebt", contracts.ghost))
+    );
+
+    let status: Option<UnbondStatus> = app
+        .wrap()
+        .query_wasm_smart(delegate.clone(), &unstake::delegate::QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(
+        status,
+        Some(UnbondStatus {
+            pending: Uint128::from(10737u128),
+            claimable: Uint128::zero(),
+            estimated_end: Some(start.plus_days(14))
+        })
+    );
+
+    app.update_block(|x| {
+        x.time = x.time.plus_days(14);
+    });
+
+    let status: Option<UnbondStatus> = app
+        .wrap()
+        .query_wasm_smart(delegate.clone(), &unstake::delegate::QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(
+        status,
+        Some(UnbondStatus {
+            pending: Uint128::zero(),
+            claimable: Uint128::from(10737u128),
+            estimated_end: None
+        })
+    );
+
+    app.execute_contract(
+        api.addr_make("random"),
+        delegate.clone(),
+        &unstake::delegate::ExecuteMsg::Complete { keeper: None },
+        &[],
+    )
+    .unwrap();
+
+    let status: Option<UnbondStatus> = app
+        .wrap()
+        .query_wasm_smart(delegate.clone(), &unstake::delegate::QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(status, Some(UnbondStatus::default()));
+
+    let balances: Vec<Coin> = app
+        .wrap()
+        .query_wasm_smart(delegate, &unstake::delegate::QueryMsg::Balances {})
+        .unwrap();
+    assert_eq!(balances, vec![]);
+}
+
+#[test]
+fn close_early_offer() {
+    // Make sure that we bail if the offer attempts to close early, and there's nothing returned
+    // from the provider to repay ghost
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("unstaker"), coins(10000u128, "base")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+
+    // Make sure that the provider has enough tokens to return once unbonding is complete
+    app.send_tokens(
+        api.addr_make("funder"),
+        contracts.provider.clone(),
+        &coins(500000u128, "quote"),
+    )
+    .unwrap();
+
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+
+    let amount = AmountU128::new(Uint128::from(10000u128));
+
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        contracts.controller.clone(),
+        &ExecuteMsg::Unstake {
+            callback: None,
+            max_fee: amount,
+            min_receive: None,
+            deadline: None,
+            referrer: None,
+        },
+        &coins(10000u128, "base"),
+    )
+    .unwrap();
+
+    let delegates: DelegatesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(delegates.delegates.len(), 1);
+    let (delegate, _) = delegates.delegates[0].clone();
+
+    // 2 weeks later, ghost debt rate should have increased
+    app.update_block(|x| {
+        x.time = x.time.plus_days(13);
+    });
+
+    let err = app
+        .execute_contract(
+            api.addr_make("random"),
+            delegate.clone(),
+            &unstake::delegate::ExecuteMsg::Complete { keeper: None },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnbondingIncomplete { pending } if pending == Uint128::from(10737u128)
+    ));
+}
+
+#[test]
+fn close_losing_offer() {
+    // Now let's finally test a loss-making completion
+    // Instead of updating the ghost rate, we will just make it complete the unbonding a week too late
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("unstaker"), coins(10000u128, "base")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+
+    // Make sure that the provider has enough tokens to return once unbonding is complete
+    app.send_tokens(
+        api.addr_make("funder"),
+        contracts.provider.clone(),
+        &coins(500000u128, "quote"),
+    )
+    .unwrap();
+
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+
+    let amount = AmountU128::new(Uint128::from(10000u128));
+
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        contracts.controller.clone(),
+        &ExecuteMsg::Unstake {
+            callback: None,
+            max_fee: amount,
+            min_receive: None,
+            deadline: None,
+            referrer: None,
+        },
+        &coins(10000u128, "base"),
+    )
+    .unwrap();
+
+    let delegates: DelegatesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(delegates.delegates.len(), 1);
+    let (delegate, _) = delegates.delegates[0].clone();
+
+    // 2 weeks later, ghost debt rate should have increased
+    app.update_block(|x| {
+        x.time = x.time.plus_days(21);
+    });
+    let ghost_balances = query_balances(&app, contracts.ghost.clone());
+    println!("{:?}", ghost_balances);
+
+    app.execute_contract(
+        api.addr_make("random"),
+        delegate.clone(),
+        &unstake::delegate::ExecuteMsg::Complete { keeper: None },
+        &[],
+    )
+    .unwrap();
+
+    let reserve_balances = query_balances(&app, contracts.reserve);
+    let delegate_balances = query_balances(&app, delegate);
+    let provider_balances = query_balances(&app, contracts.provider);
+    let ghost_balances = query_balances(&app, contracts.ghost.clone());
+
+    // 21 days will be a total of 5.7534% interest
+    // So we'll pay 5.7534 on (10326 - 823) = 547,
+    // but have an allocated fee of 3.8356 on the total value = 10737 * 3.8356 = 411
+    // so we have a loss  here of 411 - 547 = -136 (rounding)
+    assert_eq!(
+        reserve_balances,
+        coins(19864u128, format!("factory/{}/urcpt", contracts.ghost))
+    );
+
+    // delegate should now be empty
+    assert_eq!(delegate_balances, vec![]);
+
+    // Provider should have received the base for unbonding, plus the surplus from the original funding
+    // (500,000 - (10000 * 1.07375))
+    assert_eq!(
+        provider_balances,
+        vec![coin(10000u128, "base"), coin(489263u128, "quote")]
+    );
+
+    // And ghost should have the borrowed amount returned with interest
+    // 100000000 + 20000 + 547 - 136 = 100020411 + 1 for rounding
+    // 9503 over 3 weeks at 100% = 3 / 52 = 549.
+    assert_eq!(ghost_balances, coins(100020412u128, "quote"));
+}
+
+#[test]
+fn reserves() {
+    // Test that minting of the reserve receipt token is correct, and redeemed correctly
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("unstaker"), coins(10000u128, "base")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+
+    let rct_balance = app
+        .wrap()
+        .query_balance(
+            api.addr_make("funder"),
+            format!("factory/{}/ursv", contracts.reserve),
+        )
+        .unwrap();
+    assert_eq!(rct_balance.amount, Uint128::from(20000u128));
+
+    // Second deposit, scaled with no extra revenue
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+
+    let rct_balance = app
+        .wrap()
+        .query_balance(
+            api.addr_make("funder"),
+            format!("factory/{}/ursv", contracts.reserve),
+        )
+        .unwrap();
+    assert_eq!(rct_balance.amount, Uint128::from(40000u128));
+
+    // Do an unstake to increase the amount of reserves
+
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+
+    let amount = AmountU128::new(Uint128::from(10000u128));
+
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        contracts.controller.clone(),
+        &ExecuteMsg::Unstake {
+            callback: None,
+            max_fee: amount,
+            min_receive: None,
+            deadline: None,
+            referrer: None,
+        },
+        &coins(10000u128, "base"),
+    )
+    .unwrap();
+
+    // Check we can't withdraw everything whilst reserves are being used
+    app.execute_contract(
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        &unstake::reserve::ExecuteMsg::Withdraw { callback: None },
+        &coins(40000u128, format!("factory/{}/ursv", contracts.reserve)),
+    )
+    .unwrap_err();
+
+    // ...but that a smaller withdrawal also considers the consumed reserves in calculations
+    let res = app
+        .execute_contract(
+            api.addr_make("funder"),
+            contracts.reserve.clone(),
+            &unstake::reserve::ExecuteMsg::Withdraw { callback: None },
+            &coins(20000u128, format!("factory/{}/ursv", contracts.reserve)),
+        )
+        .unwrap();
+
+    res.assert_event(&Event::new("transfer").add_attributes(vec![
+        ("recipient", api.addr_make("funder").to_string()),
+        ("sender", contracts.reserve.to_string()),
+        ("amount", "20000quote".to_string()),
+    ]));
+
+    // 2 weeks later, ghost debt rate should have increased
+    app.update_block(|x| {
+        x.time = x.time.plus_days(14);
+    });
+
+    let delegates: DelegatesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
+        .unwrap();
+    let (delegate, _) = delegates.delegates[0].clone();
+
+    // Make sure that the mocked LSD provider has enough tokens to return once unbonding is complete
+    app.send_tokens(
+        api.addr_make("funder"),
+        contracts.provider.clone(),
+        &coins(500000u128, "quote"),
+    )
+    .unwrap();
+
+    app.execute_contract(
+        api.addr_make("random"),
+        delegate.clone(),
+        &unstake::delegate::ExecuteMsg::Complete { keeper: None },
+        &[],
+    )
+    .unwrap();
+
+    // Reserve rate is 1.0 + 35 / 20000 = 1.00175
+
+    // Third deposit, should have slight less receipt token printed as the reserves > supply
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+
+    // 20000 / 1.00175 = 19965
+
+    let rct_balance = app
+        .wrap()
+        .query_balance(
+            api.addr_make("funder"),
+            format!("factory/{}/ursv", contracts.reserve),
+        )
+        .unwrap();
+    assert_eq!(rct_balance.amount, Uint128::from(20000u128 + 19965u128));
+
+    // And now we withdraw some of what's left, ratio should be fractionally greater than 1
+    let res = app
+        .execute_contract(
+            api.addr_make("funder"),
+            contracts.reserve.clone(),
+            &unstake::reserve::ExecuteMsg::Withdraw { callback: None },
+            &coins(20000u128, format!("factory/{}/ursv", contracts.reserve)),
+        )
+        .unwrap();
+
+    // 20000u128 * 1.00175 = 20035
+    res.assert_event(&Event::new("transfer").add_attributes(vec![
+        ("recipient", api.addr_make("funder").to_string()),
+        ("sender", contracts.reserve.to_string()),
+        ("amount", "20035quote".to_string()),
+    ]));
+}
+
+#[test]
+fn generic_adapter() {
+    let api = MockApiBech32::new("kujira");
+    let (mut app, contracts) = setup(vec![], None);
+
+    let provider_code = ContractWrapper::new(
+        unstake_dummy_provider::contract::execute,
+        unstake_dummy_provider::contract::instantiate,
+        unstake_dummy_provider::contract::query,
+    );
+    let provider_code_id = app.store_code(Box::new(provider_code));
+    let provider = app
+        .instantiate_contract(
+            provider_code_id,
+            api.addr_make("provider"),
+            &unstake_dummy_provider::msg::InstantiateMsg {
+                unbond_time: cw_utils::Duration::Time(14 * 24 * 60 * 60),
+                lst: "ulst".to_string(),
+                base: "ubase".to_string(),
+            },
+            &[],
+            "dummy-provider",
+            None,
+        )
+        .unwrap();
+    let lst = format!("factory/{provider}/ulst");
+    let base = format!("factory/{provider}/ubase");
+
+    let generic = Generic {
+        unbond_contract: provider.clone(),
+        unbond_msg: r#"{"queue_unbond":{"receiver":null}}"#.to_string(),
+        withdraw_contract: provider.clone(),
+        withdraw_msg: r#"{"withdraw_unbonded":{"receiver":null}}"#.to_string(),
+        rate_contract: provider.clone(),
+        rate_query: r#"{"state":{}}"#.to_string(),
+        rate_path: vec!["exchange_rate".to_string()],
+    };
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    let controller_code_id = app
+        .wrap()
+        .query_wasm_contract_info(contracts.controller.clone())
+        .unwrap()
+        .code_id;
+    let instantiate_msg = |adapter: Generic| unstake::controller::InstantiateMsg {
+        owner: api.addr_make("owner"),
+        protocol_fee: Decimal::from_str("0.25").unwrap(),
+        protocol_fee_address: fee_address(),
+        delegate_code_id: config.delegate_code_id,
+        vault_address: contracts.ghost.clone(),
+        reserve_address: contracts.reserve.clone(),
+        ask_denom: AskAsset::Native(monetary::Denom::new(&lst)),
+        offer_denom: monetary::Denom::new("quote"),
+        adapter: Adapter::Generic(adapter),
+        unbonding_duration: 2 * 7 * 24 * 60 * 60,
+        min_rate: Decimal::from_str("0.03").unwrap(),
+        pricing: None,
+        vault_max_interest: None,
+        keeper_reward: None,
+        max_rate_change: None,
+        guardian: None,
+        timelock: None,
+        fee_recipients: None,
+    };
+
+    // The rate query is performed at instantiation
+    app.instantiate_contract(
+        controller_code_id,
+        api.addr_make("instantiator"),
+        &instantiate_msg(generic.clone()),
+        &[],
+        "controller",
+        None,
+    )
+    .unwrap();
+
+    app.instantiate_contract(
+        controller_code_id,
+        api.addr_make("instantiator"),
+        &instantiate_msg(Generic {
+            rate_path: vec!["rate".to_string()],
+            ..generic.clone()
+        }),
+        &[],
+        "controller",
+        None,
+    )
+    .unwrap_err();
+
+    app.instantiate_contract(
+        controller_code_id,
+        api.addr_make("instantiator"),
+        &instantiate_msg(Generic {
+            unbond_msg: r#"{"queue_unbond":"#.to_string(),
+            ..generic.clone()
+        }),
+        &[],
+        "controller",
+        None,
+    )
+    .unwrap_err();
+
+    let rate = Adapter::Generic(generic.clone())
+        .redemption_rate(app.wrap())
+        .unwrap();
+    assert_eq!(rate, Decimal::from_str("1.07375").unwrap());
+
+    // Unbond and withdraw through the templates
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        provider.clone(),
+        &unstake_dummy_provider::msg::ExecuteMsg::Mint {
+            denom: "ulst".to_string(),
+            amount: Uint128::from(1000u128),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let mut env = mock_env();
+    env.contract.address = api.addr_make("unstaker");
+    app.execute(
+        api.addr_make("unstaker"),
+        generic.unbond_start(&env, coin(1000u128, &lst)).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(query_balances(&app, api.addr_make("unstaker")), vec![]);
+
+    app.update_block(|x| {
+        x.time = x.time.plus_days(14);
+    });
+
+    app.execute(
+        api.addr_make("unstaker"),
+        generic.unbond_end().unwrap().unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        query_balances(&app, api.addr_make("unstaker")),
+        coins(1073u128, base)
+    );
+}
+
+#[test]
+fn cw20_unstake() {
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("unstaker"), coins(10000u128, "base")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+
+    let token_code = ContractWrapper::new_with_empty(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    let token_code_id = app.store_code(Box::new(token_code));
+    let token = app
+        .instantiate_contract(
+            token_code_id,
+            api.addr_make("minter"),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Liquid Staked Token".to_string(),
+                symbol: "LST".to_string(),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: api.addr_make("unstaker").to_string(),
+                    amount: Uint128::from(10000u128),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "token",
+            None,
+        )
+        .unwrap();
+
+    let provider_code = ContractWrapper::new(
+        unstake_dummy_provider::contract::execute,
+        unstake_dummy_provider::contract::instantiate,
+        unstake_dummy_provider::contract::query,
+    );
+    let provider_code_id = app.store_code(Box::new(provider_code));
+    let provider = app
+        .instantiate_contract(
+            provider_code_id,
+            api.addr_make("provider"),
+            &unstake_dummy_provider::msg::InstantiateMsg {
+                unbond_time: cw_utils::Duration::Time(14 * 24 * 60 * 60),
+                lst: "ulst".to_string(),
+                base: "ubase".to_string(),
+            },
+            &[],
+            "dummy-provider",
+            None,
+        )
+        .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    let controller_code_id = app
+        .wrap()
+        .query_wasm_contract_info(contracts.controller.clone())
+        .unwrap()
+        .code_id;
+    let controller = app
+        .instantiate_contract(
+            controller_code_id,
+            api.addr_make("instantiator"),
+            &unstake::controller::InstantiateMsg {
+                owner: api.addr_make("owner"),
+                protocol_fee: Decimal::from_str("0.25").unwrap(),
+                protocol_fee_address: fee_address(),
+                delegate_code_id: config.delegate_code_id,
+                vault_address: contracts.ghost.clone(),
+                reserve_address: contracts.reserve.clone(),
+                ask_denom: AskAsset::Cw20 {
+                    cw20: token.clone(),
+                },
+                offer_denom: monetary::Denom::new("quote"),
+                adapter: Adapter::Eris(provider.clone().into()),
+                unbonding_duration: 2 * 7 * 24 * 60 * 60,
+                min_rate: Decimal::from_str("0.03").unwrap(),
+                pricing: None,
+                vault_max_interest: None,
+                keeper_reward: None,
+                max_rate_change: None,
+                guardian: None,
+                timelock: None,
+                fee_recipients: None,
+            },
+            &[],
+            "controller",
+            None,
+        )
+        .unwrap();
+    add_controller(&mut app, &contracts.reserve, &controller, None);
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert!(config.ask_denom.is_cw20());
+
+    // CW20 routes are only accepted where the provider can unbond the token
+    let route = Route {
+        ask_denom: AskAsset::Cw20 {
+            cw20: token.clone(),
+        },
+        adapter: Adapter::Gravedigger(provider.clone().into()),
+        unbonding_duration: 21 * 24 * 60 * 60,
+    };
+    let err = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::ScheduleChange {
+                change: Change::Route {
+                    route: route.clone(),
+                },
+                effective: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Cw20Unsupported {}
+    ));
+
+    // And the token must be a CW20
+    app.execute_contract(
+        api.addr_make("owner"),
+        contracts.controller.clone(),
+        &ExecuteMsg::ScheduleChange {
+            change: Change::Route {
+                route: Route {
+                    ask_denom: AskAsset::Cw20 {
+                        cw20: provider.clone(),
+                    },
+                    adapter: Adapter::Eris(provider.clone().into()),
+                    ..route
+                },
+            },
+            effective: None,
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    let unstake_msg = ReceiveMsg::Unstake {
+        max_fee: AmountU128::new(Uint128::from(500u128)),
+        min_receive: None,
+        deadline: None,
+        referrer: None,
+        callback: None,
+    };
+
+    // The token can't be sent natively
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        controller.clone(),
+        &ExecuteMsg::Unstake {
+            max_fee: AmountU128::new(Uint128::from(500u128)),
+            min_receive: None,
+            deadline: None,
+            referrer: None,
+            callback: None,
+        },
+        &coins(10000u128, "base"),
+    )
+    .unwrap_err();
+
+    // Nor can a native denom posing as the CW20 route's token
+    app.init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &api.addr_make("spoofer"),
+                coins(10000u128, token.as_str()),
+            )
+            .unwrap()
+    });
+    let err = app
+        .execute_contract(
+            api.addr_make("spoofer"),
+            controller.clone(),
+            &ExecuteMsg::Unstake {
+                max_fee: AmountU128::new(Uint128::from(500u128)),
+                min_receive: None,
+                deadline: None,
+                referrer: None,
+                callback: None,
+            },
+            &coins(10000u128, token.as_str()),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnknownRoute { ask_denom } if ask_denom == token.as_str()
+    ));
+
+    // Only the ask token can call Receive
+    let err = app
+        .execute_contract(
+            api.addr_make("unstaker"),
+            controller.clone(),
+            &ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+                sender: api.addr_make("unstaker").to_string(),
+                amount: Uint128::from(10000u128),
+                msg: to_json_binary(&unstake_msg).unwrap(),
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        token.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: controller.to_string(),
+            amount: Uint128::from(10000u128),
+            msg: to_json_binary(&unstake_msg).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Same offer as a native unstake
+    assert_eq!(
+        query_balances(&app, api.addr_make("unstaker")),
+        vec![coin(10000u128, "base"), coin(10325u128, "quote")]
+    );
+
+    let positions: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            controller.clone(),
+            &QueryMsg::Positions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let delegate = positions.positions[0].delegate.clone();
+
+    // The tokens have been passed through the delegate to the provider
+    let token_balance = |address: &Addr| -> Uint128 {
+        let res: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance
+    };
+    assert_eq!(token_balance(&api.addr_make("unstaker")), Uint128::zero());
+    assert_eq!(token_balance(&controller), Uint128::zero());
+    assert_eq!(token_balance(&delegate), Uint128::zero());
+    assert_eq!(token_balance(&provider), Uint128::from(10000u128));
+
+    let status: Option<UnbondStatus> = app
+        .wrap()
+        .query_wasm_smart(delegate, &unstake::delegate::QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(status.unwrap().pending, Uint128::from(10737u128));
+}
+
+#[test]
+fn multiple_routes() {
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+
+    let provider_code = ContractWrapper::new(
+        unstake_dummy_provider::contract::execute,
+        unstake_dummy_provider::contract::instantiate,
+        unstake_dummy_provider::contract::query,
+    );
+    let provider_code_id = app.store_code(Box::new(provider_code));
+    let provider = app
+        .instantiate_contract(
+            provider_code_id,
+            api.addr_make("provider"),
+            &unstake_dummy_provider::msg::InstantiateMsg {
+                unbond_time: cw_utils::Duration::Time(21 * 24 * 60 * 60),
+                lst: "ulst".to_string(),
+                base: "ubase".to_string(),
+            },
+            &[],
+            "dummy-provider",
+            None,
+        )
+        .unwrap();
+    let lst = format!("factory/{provider}/ulst");
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        provider.clone(),
+        &unstake_dummy_provider::msg::ExecuteMsg::Mint {
+            denom: "ulst".to_string(),
+            amount: Uint128::from(10000u128),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let route = Route {
+        ask_denom: AskAsset::Native(monetary::Denom::new(&lst)),
+        adapter: Adapter::Eris(provider.clone().into()),
+        // 3 weeks
+        unbonding_duration: 21 * 24 * 60 * 60,
+    };
+
+    let schedule = |app: &mut CustomApp, sender: &str, change: Change| {
+        app.execute_contract(
+            api.addr_make(sender),
+            contracts.controller.clone(),
+            &ExecuteMsg::ScheduleChange {
+                change,
+                effective: None,
+            },
+            &[],
+        )
+    };
+    let err = schedule(
+        &mut app,
+        "unstaker",
+        Change::Route {
+            route: route.clone(),
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    // The primary route can only be changed through the config and broker
+    let err = schedule(
+        &mut app,
+        "owner",
+        Change::Route {
+            route: Route {
+                ask_denom: AskAsset::Native(monetary::Denom::new("base")),
+                ..route.clone()
+            },
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidRoute {}
+    ));
+
+    let res = change(
+        &mut app,
+        &contracts,
+        Change::Route {
+            route: route.clone(),
+        },
+    );
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/add_route").add_attribute("ask_denom", lst.as_str()),
+    );
+
+    let routes: RoutesResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Routes {})
+        .unwrap();
+    assert_eq!(routes.routes.len(), 2);
+    assert_eq!(
+        routes.routes[0].ask_denom,
+        AskAsset::Native(monetary::Denom::new("base"))
+    );
+    assert_eq!(routes.routes[0].unbonding_duration, 2 * 7 * 24 * 60 * 60);
+    assert_eq!(routes.routes[1], route);
+
+    // Both providers redeem at 1.07375, but the longer unbonding costs more
+    let primary: OfferResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Offer {
+                amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    let quote: OfferResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Offer {
+                amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: Some(monetary::Denom::new(&lst)),
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert!(quote.fee.gt(&primary.fee));
+    assert_eq!(quote.amount + quote.fee, primary.amount + primary.fee);
+
+    app.wrap()
+        .query_wasm_smart::<OfferResponse>(
+            contracts.controller.clone(),
+            &QueryMsg::Offer {
+                amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: Some(monetary::Denom::new("unknown")),
+                sender: None,
+            },
+        )
+        .unwrap_err();
+
+    let start = app.block_info().time;
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        contracts.controller.clone(),
+        &ExecuteMsg::Unstake {
+            callback: None,
+            max_fee: quote.fee,
+            min_receive: None,
+            deadline: None,
+            referrer: None,
+        },
+        &coins(10000u128, &lst),
+    )
+    .unwrap();
+    assert_eq!(
+        query_balances(&app, api.addr_make("unstaker")),
+        coins(quote.amount.u128(), "quote")
+    );
+
+    let positions: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Positions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let position = positions.positions[0].clone();
+    assert_eq!(position.ask_denom, Some(monetary::Denom::new(&lst)));
+    assert_eq!(position.maturity, start.plus_days(21));
+
+    // The delegate unbonds through the route's provider
+    let status: Option<UnbondStatus> = app
+        .wrap()
+        .query_wasm_smart(
+            position.delegate.clone(),
+            &unstake::delegate::QueryMsg::Status {},
+        )
+        .unwrap();
+    assert_eq!(status.unwrap().pending, Uint128::from(10737u128));
+
+    let status: StatusResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Status {})
+        .unwrap();
+    // Base amounts are in each route's own ask denom, so only the primary route is totalled
+    assert_eq!(status.total_base, AmountU128::zero());
+    assert_eq!(
+        status.routes,
+        vec![
+            RouteStatus {
+                ask_denom: monetary::Denom::new("base"),
+                total_base: AmountU128::zero(),
+                total_quote: AmountU128::zero(),
+            },
+            RouteStatus {
+                ask_denom: monetary::Denom::new(&lst),
+                total_base: AmountU128::new(Uint128::from(10000u128)),
+                total_quote: AmountU128::zero(),
+            },
+        ]
+    );
+
+    // The route can't be replaced while it has open positions, which is checked once the change
+    // is executed
+    let execute = |app: &mut CustomApp, change: Change| {
+        schedule(app, "owner", change).unwrap();
+        let pending: PendingChangesResponse<Change> = app
+            .wrap()
+            .query_wasm_smart(contracts.controller.clone(), &QueryMsg::PendingChanges {})
+            .unwrap();
+        app.execute_contract(
+            api.addr_make("keeper"),
+            contracts.controller.clone(),
+            &ExecuteMsg::ExecuteChange {
+                id: pending.changes.last().unwrap().id,
+            },
+            &[],
+        )
+    };
+    let err = execute(
+        &mut app,
+        Change::Route {
+            route: route.clone(),
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OpenPositions { count: 1 }
+    ));
+
+    // Nor removed, so that it can't be re-added on other terms
+    let err = execute(
+        &mut app,
+        Change::RemoveRoute {
+            ask_denom: monetary::Denom::new(&lst),
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OpenPositions { count: 1 }
+    ));
+    let err = execute(
+        &mut app,
+        Change::Route {
+            route: Route {
+                unbonding_duration: 28 * 24 * 60 * 60,
+                ..route.clone()
+            },
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OpenPositions { count: 1 }
+    ));
+
+    let err = schedule(
+        &mut app,
+        "owner",
+        Change::RemoveRoute {
+            ask_denom: monetary::Denom::new("base"),
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidRoute {}
+    ));
+
+    // A route without open positions can be removed, after which its LST isn't accepted
+    let res = change(
+        &mut app,
+        &contracts,
+        Change::Route {
+            route: Route {
+                ask_denom: AskAsset::Native(monetary::Denom::new("other")),
+                ..route.clone()
+            },
+        },
+    );
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/add_route").add_attribute("ask_denom", "other"),
+    );
+    let res = change(
+        &mut app,
+        &contracts,
+        Change::RemoveRoute {
+            ask_denom: monetary::Denom::new("other"),
+        },
+    );
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/remove_route").add_attribute("ask_denom", "other"),
+    );
+    app.wrap()
+        .query_wasm_smart::<OfferResponse>(
+            contracts.controller.clone(),
+            &QueryMsg::Offer {
+                amount: AmountU128::new(Uint128::from(10000u128)),
+                ask_denom: Some(monetary::Denom::new("other")),
+                sender: None,
+            },
+        )
+        .unwrap_err();
+
+    let routes: RoutesResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Routes {})
+        .unwrap();
+    assert_eq!(routes.routes.len(), 2);
+    assert_eq!(routes.routes[1], route);
+
+    let positions: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller,
+            &QueryMsg::Positions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(positions.positions, vec![position]);
+}
+
+#[test]
+fn reported_unbonding_duration() {
+    let (mut app, contracts) = setup(vec![], None);
+    let quote = |app: &CustomApp| -> OfferResponse {
+        app.wrap()
+            .query_wasm_smart(
+                contracts.controller.clone(),
+                &QueryMsg::Offer {
+                    amount: AmountU128::new(Uint128::from(10000u128)),
+                    ask_denom: None,
+                    sender: None,
+                },
+            )
+            .unwrap()
+    };
+    let update_duration = |app: &mut CustomApp, duration: u64| {
+        change(
+            app,
+            &contracts,
+            Change::Broker {
+                min_rate: None,
+                duration: Some(duration),
+                pricing: None,
+            },
+        );
+    };
+
+    // The mock provider reports the same 2 weeks as configured
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.unbonding_duration, 2 * 7 * 24 * 60 * 60);
+    let initial = quote(&app);
+
+    // A shorter configured duration is overridden by the provider's
+    update_duration(&mut app, 7 * 24 * 60 * 60);
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.broker.duration, 7 * 24 * 60 * 60);
+    assert_eq!(config.unbonding_duration, 2 * 7 * 24 * 60 * 60);
+    assert_eq!(quote(&app), initial);
+
+    // A longer one is used as configured
+    update_duration(&mut app, 4 * 7 * 24 * 60 * 60);
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.unbonding_duration, 4 * 7 * 24 * 60 * 60);
+    assert!(quote(&app).fee.gt(&initial.fee));
+}
+
+#[test]
+fn rate_guard() {
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.max_rate_change, Decimal::percent(1));
+
+    let provider_code = ContractWrapper::new(
+        unstake_dummy_provider::contract::execute,
+        unstake_dummy_provider::contract::instantiate,
+        unstake_dummy_provider::contract::query,
+    );
+    let provider_code_id = app.store_code(Box::new(provider_code));
+    let provider = app
+        .instantiate_contract(
+            provider_code_id,
+            api.addr_make("provider"),
+            &unstake_dummy_provider::msg::InstantiateMsg {
+                unbond_time: cw_utils::Duration::Time(21 * 24 * 60 * 60),
+                lst: "ulst".to_string(),
+                base: "ubase".to_string(),
+            },
+            &[],
+            "dummy-provider",
+            None,
+        )
+        .unwrap();
+    let lst = format!("factory/{provider}/ulst");
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        provider.clone(),
+        &unstake_dummy_provider::msg::ExecuteMsg::Mint {
+            denom: "ulst".to_string(),
+            amount: Uint128::from(4000u128),
+        },
+        &[],
+    )
+    .unwrap();
+
+    change(
+        &mut app,
+        &contracts,
+        Change::Route {
+            route: Route {
+                ask_denom: AskAsset::Native(monetary::Denom::new(&lst)),
+                adapter: Adapter::Eris(provider.clone().into()),
+                unbonding_duration: 21 * 24 * 60 * 60,
+            },
+        },
+    );
+
+    // Each delegate is labelled by block height, so the block is advanced after each Unstake
+    let unstake = |app: &mut CustomApp| {
+        let res = app.execute_contract(
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Unstake {
+                callback: None,
+                max_fee: AmountU128::new(Uint128::from(1000u128)),
+                min_receive: None,
+                deadline: None,
+                referrer: None,
+            },
+            &coins(1000u128, &lst),
+        );
+        app.update_block(|x| x.height += 1);
+        res
+    };
+    let set_rate = |app: &mut CustomApp, rate: &str| {
+        app.execute_contract(
+            api.addr_make("provider"),
+            provider.clone(),
+            &unstake_dummy_provider::msg::ExecuteMsg::SetRate {
+                rate: Decimal::from_str(rate).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    };
+
+    // The rate was recorded when the route was added
+    set_rate(&mut app, "1.2");
+    unstake(&mut app).unwrap_err();
+    set_rate(&mut app, "1.07375");
+    unstake(&mut app).unwrap();
+
+    // A move within 1% of the recorded rate is accepted
+    set_rate(&mut app, "1.08");
+    unstake(&mut app).unwrap();
+
+    // The provider's rate spikes
+    set_rate(&mut app, "1.2");
+    let err = unstake(&mut app).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RateDeviation { rate, last_rate }
+            if rate == Decimal::from_str("1.2").unwrap()
+                && last_rate == Decimal::from_str("1.07375").unwrap()
+    ));
+
+    let err = app
+        .execute_contract(
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::AcceptRate {
+                ask_denom: monetary::Denom::new(&lst),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    let res = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::AcceptRate {
+                ask_denom: monetary::Denom::new(&lst),
+            },
+            &[],
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/accept_rate")
+            .add_attributes(vec![("ask_denom", lst.as_str()), ("rate", "1.2")]),
+    );
+
+    // Within the block it was accepted in, the rate may only move by an hour's allowance
+    set_rate(&mut app, "1.2004");
+    app.wrap()
+        .query_wasm_smart::<OfferResponse>(
+            contracts.controller.clone(),
+            &QueryMsg::Offer {
+                amount: AmountU128::new(Uint128::from(1000u128)),
+                ask_denom: Some(monetary::Denom::new(&lst)),
+                sender: None,
+            },
+        )
+        .unwrap();
+    set_rate(&mut app, "1.201");
+    let err = unstake(&mut app).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RateDeviation { rate, last_rate }
+            if rate == Decimal::from_str("1.201").unwrap()
+                && last_rate == Decimal::from_str("1.2").unwrap()
+    ));
+    unstake(&mut app).unwrap();
+
+    // The allowance grows with the time since the rate was last accepted, up to a week
+    set_rate(&mut app, "1.28");
+    unstake(&mut app).unwrap_err();
+    app.update_block(|x| x.time = x.time.plus_days(9));
+    unstake(&mut app).unwrap();
+    set_rate(&mut app, "1.4");
+    app.update_block(|x| x.time = x.time.plus_days(30));
+    unstake(&mut app).unwrap_err();
+
+    // Offers are quoted against the same guard
+    let offer = |app: &CustomApp| {
+        app.wrap()
+            .query_wasm_smart::<OfferResponse>(
+                contracts.controller.clone(),
+                &QueryMsg::Offer {
+                    amount: AmountU128::new(Uint128::from(1000u128)),
+                    ask_denom: Some(monetary::Denom::new(&lst)),
+                    sender: None,
+                },
+            )
+            .unwrap_err()
+    };
+    assert!(offer(&app).to_string().contains("RateDeviation"));
+    let err = app
+        .wrap()
+        .query_wasm_smart::<OfferForOutputResponse>(
+            contracts.controller.clone(),
+            &QueryMsg::OfferForOutput {
+                amount: AmountU128::new(Uint128::from(1000u128)),
+                ask_denom: Some(monetary::Denom::new(&lst)),
+                sender: None,
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("RateDeviation"));
+
+    // A provider reporting a zero rate can't be quoted against
+    set_rate(&mut app, "0");
+    assert!(offer(&app).to_string().contains("ZeroRate"));
+}
+
+#[test]
+fn pause() {
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("unstaker"), coins(20000u128, "base")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+    app.execute_contract(
+        api.addr_make("owner"),
+        contracts.controller.clone(),
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            guardian: Some(api.addr_make("guardian")),
+            ..Default::default()
+        }),
+        &[],
+    )
+    .unwrap();
+
+    let execute_unstake = |app: &mut CustomApp| {
+        app.execute_contract(
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Unstake {
+                callback: None,
+                max_fee: AmountU128::new(Uint128::from(10000u128)),
+                min_receive: None,
+                deadline: None,
+                referrer: None,
+            },
+            &coins(10000u128, "base"),
+        )
+    };
+    execute_unstake(&mut app).unwrap();
+    app.update_block(|x| x.height += 1);
+
+    let err = app
+        .execute_contract(
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Pause {
+                operations: vec![Operation::Unstake],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    let res = app
+        .execute_contract(
+            api.addr_make("guardian"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Pause {
+                operations: vec![Operation::Unstake],
+            },
+            &[],
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/pause")
+            .add_attributes(vec![("operations", r#"["unstake"]"#)]),
+    );
+    let err = execute_unstake(&mut app).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Paused {}
+    ));
+
+    // The reserve stops lending too, but the outstanding position can still be completed
+    app.execute_contract(
+        api.addr_make("owner"),
+        contracts.reserve.clone(),
+        &unstake::reserve::ExecuteMsg::Pause {
+            operations: vec![unstake::reserve::Operation::RequestReserves],
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(|x| {
+        x.time = x.time.plus_days(14);
+    });
+    app.send_tokens(
+        api.addr_make("funder"),
+        contracts.provider.clone(),
+        &coins(15000u128, "quote"),
+    )
+    .unwrap();
+    let res = app
+        .execute_contract(
+            api.addr_make("keeper"),
+            contracts.controller.clone(),
+            &ExecuteMsg::CompleteMatured {
+                start_after: None,
+                limit: None,
+            },
+            &[],
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/complete_matured").add_attributes(vec![
+            ("completed_count", "1".to_string()),
+            ("failed_count", "0".to_string()),
+        ]),
+    );
+
+    // Only the owner can resume
+    let unpause = ExecuteMsg::Unpause {
+        operations: vec![Operation::Unstake],
+    };
+    let err = app
+        .execute_contract(
+            api.addr_make("guardian"),
+            contracts.controller.clone(),
+            &unpause,
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    app.execute_contract(
+        api.addr_make("owner"),
+        contracts.controller.clone(),
+        &unpause,
+        &[],
+    )
+    .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.guardian, Some(api.addr_make("guardian")));
+    assert_eq!(config.paused, vec![]);
+
+    // The owner can revoke the guardian
+    let err = app
+        .execute_contract(
+            api.addr_make("guardian"),
+            contracts.controller.clone(),
+            &ExecuteMsg::RemoveGuardian {},
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    let res = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::RemoveGuardian {},
+            &[],
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/remove_guardian")
+            .add_attribute("guardian", api.addr_make("guardian")),
+    );
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.guardian, None);
+    let err = app
+        .execute_contract(
+            api.addr_make("guardian"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Pause {
+                operations: vec![Operation::Unstake],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+}
+
+#[test]
+fn transfer_ownership() {
+    let api = MockApiBech32::new("kujira");
+    let (mut app, contracts) = setup(vec![], None);
+    let expiry = app.block_info().time.plus_days(7);
+
+    let res = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::ProposeOwner {
+                owner: api.addr_make("new_owner"),
+                expiry: Some(expiry),
+            },
+            &[],
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/propose_owner").add_attributes(vec![
+            ("owner", api.addr_make("new_owner").to_string()),
+            ("expiry", expiry.seconds().to_string()),
+        ]),
+    );
+
+    // The owner is unchanged until the proposal is accepted
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.owner, api.addr_make("owner"));
+    assert_eq!(
+        config.pending_owner,
+        Some(PendingOwner {
+            owner: api.addr_make("new_owner"),
+            expiry: Some(expiry),
+        })
+    );
+
+    let err = app
+        .execute_contract(
+            api.addr_make("other"),
+            contracts.controller.clone(),
+            &ExecuteMsg::AcceptOwner {},
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    app.execute_contract(
+        api.addr_make("new_owner"),
+        contracts.controller.clone(),
+        &ExecuteMsg::AcceptOwner {},
+        &[],
+    )
+    .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.owner, api.addr_make("new_owner"));
+    assert_eq!(config.pending_owner, None);
+
+    let err = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::CancelOwner {},
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    let err = app
+        .execute_contract(
+            api.addr_make("new_owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::CancelOwner {},
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoPendingOwner {}
+    ));
+}
+
+#[test]
+fn timelock() {
+    let api = MockApiBech32::new("kujira");
+    let (mut app, contracts) = setup(vec![], None);
+    let schedule = |app: &mut CustomApp, sender: &str, change: Change, effective| {
+        app.execute_contract(
+            api.addr_make(sender),
+            contracts.controller.clone(),
+            &ExecuteMsg::ScheduleChange { change, effective },
+            &[],
+        )
+    };
+    let execute = |app: &mut CustomApp, id: u64| {
+        app.execute_contract(
+            api.addr_make("keeper"),
+            contracts.controller.clone(),
+            &ExecuteMsg::ExecuteChange { id },
+            &[],
+        )
+    };
+    let fee_change = Change::Config {
+        protocol_fee: Some(Decimal::percent(10)),
+        protocol_fee_address: None,
+        delegate_code_id: None,
+        vault_max_interest: None,
+    };
+
+    let err = schedule(&mut app, "unstaker", fee_change.clone(), None).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    // The delay itself can only be changed with notice
+    schedule(&mut app, "owner", Change::Timelock { delay: 86400 }, None).unwrap();
+    execute(&mut app, 0).unwrap();
+
+    let now = app.block_info().time;
+    let err = schedule(
+        &mut app,
+        "owner",
+        fee_change.clone(),
+        Some(now.plus_seconds(3600)),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockTooShort {}
+    ));
+
+    let res = schedule(&mut app, "owner", fee_change.clone(), None).unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/schedule_change").add_attributes(vec![
+            ("id", "1".to_string()),
+            ("effective", now.plus_days(1).seconds().to_string()),
+        ]),
+    );
+    schedule(
+        &mut app,
+        "owner",
+        Change::Broker {
+            min_rate: Some(Decimal::percent(50)),
+            duration: None,
+            pricing: None,
+        },
+        None,
+    )
+    .unwrap();
+
+    let pending: PendingChangesResponse<Change> = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::PendingChanges {})
+        .unwrap();
+    assert_eq!(pending.changes.len(), 2);
+    assert_eq!(pending.changes[0].id, 1);
+    assert_eq!(pending.changes[0].change, fee_change);
+    assert_eq!(pending.changes[0].effective, now.plus_days(1));
+
+    let err = execute(&mut app, 1).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockNotElapsed {}
+    ));
+
+    // The owner can withdraw a change before it's executed
+    let err = app
+        .execute_contract(
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::CancelChange { id: 2 },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    app.execute_contract(
+        api.addr_make("owner"),
+        contracts.controller.clone(),
+        &ExecuteMsg::CancelChange { id: 2 },
+        &[],
+    )
+    .unwrap();
+
+    app.update_block(|x| x.time = x.time.plus_days(1));
+    let err = execute(&mut app, 2).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnknownChange { id: 2 }
+    ));
+    execute(&mut app, 1).unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.protocol_fee, Decimal::percent(10));
+    assert_eq!(config.timelock, 86400);
+    assert_eq!(config.broker.min_rate, Decimal::from_str("0.03").unwrap());
+
+    let pending: PendingChangesResponse<Change> = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::PendingChanges {})
+        .unwrap();
+    assert_eq!(pending.changes, vec![]);
+
+    // A reduced delay only applies once the previous delay has passed
+    schedule(&mut app, "owner", Change::Timelock { delay: 0 }, None).unwrap();
+    app.update_block(|x| x.time = x.time.plus_days(1));
+    execute(&mut app, 3).unwrap();
+
+    let now = app.block_info().time;
+    let err = schedule(&mut app, "owner", fee_change.clone(), Some(now)).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockTooShort {}
+    ));
+    let res = schedule(&mut app, "owner", fee_change.clone(), None).unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/schedule_change").add_attributes(vec![
+            ("id", "4".to_string()),
+            ("effective", now.plus_days(1).seconds().to_string()),
+        ]),
+    );
+    app.update_block(|x| x.time = x.time.plus_days(1));
+    execute(&mut app, 4).unwrap();
+
+    let now = app.block_info().time;
+    let res = schedule(&mut app, "owner", fee_change.clone(), None).unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/schedule_change").add_attributes(vec![
+            ("id", "5".to_string()),
+            ("effective", now.seconds().to_string()),
+        ]),
+    );
+
+    // A change lapses if it isn't executed in time
+    app.update_block(|x| x.time = x.time.plus_days(15));
+    let err = execute(&mut app, 5).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ChangeExpired { id: 5 }
+    ));
+    app.execute_contract(
+        api.addr_make("owner"),
+        contracts.controller.clone(),
+        &ExecuteMsg::CancelChange { id: 5 },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn validated_config() {
+    let api = MockApiBech32::new("kujira");
+    let (mut app, contracts) = setup(vec![], None);
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    let code_info = app
+        .wrap()
+        .query_wasm_code_info(config.delegate_code_id)
+        .unwrap();
+    assert_eq!(config.delegate_code_checksum, Some(code_info.checksum));
+
+    let controller_code_id = app
+        .wrap()
+        .query_wasm_contract_info(contracts.controller.clone())
+        .unwrap()
+        .code_id;
+    let msg = unstake::controller::InstantiateMsg {
+        owner: config.owner.clone(),
+        protocol_fee: config.protocol_fee,
+        protocol_fee_address: config.protocol_fee_address.clone(),
+        delegate_code_id: config.delegate_code_id,
+        vault_address: config.vault_address.clone(),
+        reserve_address: config.reserve_address.clone(),
+        ask_denom: config.ask_denom.clone(),
+        offer_denom: config.offer_denom.clone(),
+        adapter: config.adapter.clone(),
+        unbonding_duration: config.broker.duration,
+        min_rate: config.broker.min_rate,
+        pricing: None,
+        vault_max_interest: None,
+        keeper_reward: None,
+        max_rate_change: None,
+        guardian: None,
+        timelock: None,
+        fee_recipients: None,
+    };
+    let instantiate = |app: &mut CustomApp, msg: &unstake::controller::InstantiateMsg| {
+        app.instantiate_contract(
+            controller_code_id,
+            api.addr_make("instantiator"),
+            msg,
+            &[],
+            "controller",
+            None,
+        )
+    };
+    instantiate(&mut app, &msg).unwrap();
+
+    let err = instantiate(
+        &mut app,
+        &unstake::controller::InstantiateMsg {
+            protocol_fee: Decimal::percent(51),
+            ..msg.clone()
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidProtocolFee { max } if max == Decimal::percent(50)
+    ));
+
+    let err = instantiate(
+        &mut app,
+        &unstake::controller::InstantiateMsg {
+            delegate_code_id: 999,
+            ..msg.clone()
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidDelegateCode { code_id: 999 }
+    ));
+
+    let err = instantiate(
+        &mut app,
+        &unstake::controller::InstantiateMsg {
+            min_rate: Decimal::percent(301),
+            ..msg.clone()
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMinRate { max } if max == Decimal::percent(300)
+    ));
+
+    let err = instantiate(
+        &mut app,
+        &unstake::controller::InstantiateMsg {
+            max_rate_change: Some(Decimal::zero()),
+            ..msg.clone()
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMaxRateChange { max } if max == Decimal::percent(100)
+    ));
+
+    let err = instantiate(
+        &mut app,
+        &unstake::controller::InstantiateMsg {
+            keeper_reward: Some(KeeperReward::Share {
+                share: Decimal::percent(101),
+            }),
+            ..msg.clone()
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidKeeperReward {}
+    ));
+
+    let err = instantiate(
+        &mut app,
+        &unstake::controller::InstantiateMsg {
+            timelock: Some(31 * 24 * 60 * 60),
+            ..msg.clone()
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTimelock { max: 2592000 }
+    ));
+
+    let err = instantiate(
+        &mut app,
+        &unstake::controller::InstantiateMsg {
+            unbonding_duration: 60 * 60,
+            ..msg
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidDuration {
+            min: 86400,
+            max: 7776000
+        }
+    ));
+
+    // Changes are rejected when scheduled, rather than once the timelock has elapsed
+    let schedule = |app: &mut CustomApp, change: Change| {
+        app.execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::ScheduleChange {
+                change,
+                effective: None,
+            },
+            &[],
+        )
+    };
+    let err = schedule(
+        &mut app,
+        Change::Config {
+            protocol_fee: Some(Decimal::percent(60)),
+            protocol_fee_address: None,
+            delegate_code_id: None,
+            vault_max_interest: None,
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidProtocolFee { .. }
+    ));
+
+    let err = schedule(
+        &mut app,
+        Change::Config {
+            protocol_fee: None,
+            protocol_fee_address: Some(Addr::unchecked("treasury")),
+            delegate_code_id: None,
+            vault_max_interest: None,
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Std(_)
+    ));
+
+    let err = schedule(
+        &mut app,
+        Change::Config {
+            protocol_fee: None,
+            protocol_fee_address: None,
+            delegate_code_id: Some(999),
+            vault_max_interest: None,
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidDelegateCode { code_id: 999 }
+    ));
+
+    let err = schedule(
+        &mut app,
+        Change::Broker {
+            min_rate: Some(Decimal::percent(400)),
+            duration: None,
+            pricing: None,
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMinRate { .. }
+    ));
+
+    // A rate that would charge more than the value of an Unstake
+    let err = schedule(
+        &mut app,
+        Change::Broker {
+            min_rate: None,
+            duration: None,
+            pricing: Some(PricingModel::Flat {
+                rate: Decimal::percent(3000),
+            }),
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidPricingModel { .. }
+    ));
+
+    let err = schedule(
+        &mut app,
+        Change::Broker {
+            min_rate: None,
+            duration: Some(0),
+            pricing: None,
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidDuration { .. }
+    ));
+
+    let err = schedule(
+        &mut app,
+        Change::Timelock {
+            delay: 31 * 24 * 60 * 60,
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTimelock { .. }
+    ));
+
+    let update = |app: &mut CustomApp, keeper_reward, max_rate_change| {
+        app.execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::UpdateConfig(ConfigUpdate {
+                keeper_reward,
+                max_rate_change,
+                ..Default::default()
+            }),
+            &[],
+        )
+    };
+    let err = update(&mut app, None, Some(Decimal::zero())).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMaxRateChange { .. }
+    ));
+    let err = update(
+        &mut app,
+        Some(KeeperReward::Share {
+            share: Decimal::percent(101),
+        }),
+        None,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidKeeperReward {}
+    ));
+    let err = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::UpdateConfig(ConfigUpdate {
+                referral_share: Some(Decimal::percent(101)),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidReferralShare {}
+    ));
+
+    let err = schedule(
+        &mut app,
+        Change::Route {
+            route: Route {
+                ask_denom: AskAsset::Native(monetary::Denom::new("ulst2")),
+                adapter: config.adapter,
+                unbonding_duration: 91 * 24 * 60 * 60,
+            },
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidDuration { .. }
+    ));
+
+    let pending: PendingChangesResponse<Change> = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::PendingChanges {})
+        .unwrap();
+    assert_eq!(pending.changes, vec![]);
+
+    // The checksum is recorded again when the delegate code changes
+    let delegate_code_id = app.store_code(Box::new(ContractWrapper::new(
+        unstake_delegate::contract::execute,
+        unstake_delegate::contract::instantiate,
+        unstake_delegate::contract::query,
+    )));
+    change(
+        &mut app,
+        &contracts,
+        Change::Config {
+            protocol_fee: Some(Decimal::percent(50)),
+            protocol_fee_address: Some(api.addr_make("treasury")),
+            delegate_code_id: Some(delegate_code_id),
+            vault_max_interest: None,
+        },
+    );
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller, &QueryMsg::Config {})
+        .unwrap();
+    let code_info = app.wrap().query_wasm_code_info(delegate_code_id).unwrap();
+    assert_eq!(config.protocol_fee, Decimal::percent(50));
+    assert_eq!(config.protocol_fee_address, api.addr_make("treasury"));
+    assert_eq!(config.delegate_code_id, delegate_code_id);
+    assert_eq!(config.delegate_code_checksum, Some(code_info.checksum));
+}
+
+#[test]
+fn fee_recipients() {
+    // Same as close_offer, with the protocol fee split between recipients
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("unstaker"), coins(10000u128, "base")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+    app.send_tokens(
+        api.addr_make("funder"),
+        contracts.provider.clone(),
+        &coins(500000u128, "quote"),
+    )
+    .unwrap();
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+
+    // The protocol fee address receives the whole fee until recipients are set
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(
+        config.fee_recipients,
+        vec![FeeRecipient {
+            address: fee_address(),
+            weight: Decimal::one(),
+            callback: None,
+        }]
+    );
+
+    let recipient = |name: &str, weight: u64| FeeRecipient {
+        address: api.addr_make(name),
+        weight: Decimal::percent(weight),
+        callback: None,
+    };
+
+    let err = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::ScheduleChange {
+                change: Change::FeeRecipients {
+                    recipients: vec![recipient("treasury", 50), recipient("staking", 40)],
+                },
+                effective: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidFeeRecipients {}
+    ));
+
+    // Recipient addresses are validated
+    let err = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::ScheduleChange {
+                change: Change::FeeRecipients {
+                    recipients: vec![FeeRecipient {
+                        address: Addr::unchecked("treasury"),
+                        ..recipient("treasury", 100)
+                    }],
+                },
+                effective: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Std(_)
+    ));
+
+    // The staking recipient's callback fails, as it isn't a contract
+    let recipients = vec![
+        recipient("treasury", 50),
+        FeeRecipient {
+            callback: Some(CallbackData(to_json_binary(&Empty {}).unwrap())),
+            ..recipient("staking", 30)
+        },
+        recipient("referrals", 20),
+    ];
+    change(
+        &mut app,
+        &contracts,
+        Change::FeeRecipients {
+            recipients: recipients.clone(),
+        },
+    );
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.fee_recipients, recipients);
+
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        contracts.controller.clone(),
+        &ExecuteMsg::Unstake {
+            callback: None,
+            max_fee: AmountU128::new(Uint128::from(10000u128)),
+            min_receive: None,
+            deadline: None,
+            referrer: None,
+        },
+        &coins(10000u128, "base"),
+    )
+    .unwrap();
+
+    let delegates: DelegatesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Delegates {
+                start_after: None,
+                limit: None,
+                filter: None,
+            },
+        )
+        .unwrap();
+    let (delegate, _) = delegates.delegates[0].clone();
+
+    app.update_block(|x| {
+        x.time = x.time.plus_days(14);
+    });
+    let res = app
+        .execute_contract(
+            api.addr_make("random"),
+            delegate,
+            &unstake::delegate::ExecuteMsg::Complete { keeper: None },
+            &[],
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/fee_callback_failure").add_attributes(vec![
+            ("recipient", api.addr_make("staking").to_string()),
+            ("amount", "3".to_string()),
+        ]),
+    );
+
+    // 11 protocol fee is split 5.5 / 3.3 / 2.2, with the dust going to the first recipient
+    assert_eq!(
+        query_balances(&app, api.addr_make("treasury")),
+        coins(6u128, "quote")
+    );
+    assert_eq!(query_balances(&app, api.addr_make("staking")), vec![]);
+    assert_eq!(
+        query_balances(&app, api.addr_make("referrals")),
+        coins(2u128, "quote")
+    );
+    assert_eq!(query_balances(&app, fee_address()), vec![]);
+
+    // The failed share is held for the recipient to claim
+    let unclaimed: UnclaimedFeesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::UnclaimedFees {
+                address: api.addr_make("staking"),
+            },
+        )
+        .unwrap();
+    assert_eq!(unclaimed.amount, AmountU128::new(Uint128::from(3u128)));
+    let claim = |app: &mut CustomApp| {
+        app.execute_contract(
+            api.addr_make("staking"),
+            contracts.controller.clone(),
+            &ExecuteMsg::ClaimFees {},
+            &[],
+        )
+    };
+    claim(&mut app).unwrap();
+    assert_eq!(
+        query_balances(&app, api.addr_make("staking")),
+        coins(3u128, "quote")
+    );
+    let err = claim(&mut app).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NothingToClaim {}
+    ));
+
+    // Clearing the recipients pays the protocol fee address again
+    change(
+        &mut app,
+        &contracts,
+        Change::FeeRecipients { recipients: vec![] },
+    );
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(
+        config.fee_recipients,
+        vec![FeeRecipient {
+            address: fee_address(),
+            weight: Decimal::one(),
+            callback: None,
+        }]
+    );
+}
+
+#[test]
+fn referrals() {
+    // Same as close_offer, with a referrer paid a share of the protocol fee
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (api.addr_make("unstaker"), coins(20000u128, "base")),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
+    fund_reserve(
+        &mut app,
+        api.addr_make("funder"),
+        contracts.reserve.clone(),
+        20000u128.into(),
+        "quote",
+    );
+    app.send_tokens(
+        api.addr_make("funder"),
+        contracts.provider.clone(),
+        &coins(500000u128, "quote"),
+    )
+    .unwrap();
     app.execute_contract(
-        api.addr_make("random"),
-        delegate.clone(),
-        &unstake::delegate::ExecuteMsg::Complete {},
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
+
+    let set_tier = |app: &mut CustomApp, sender: &str, share: Option<Decimal>| {
+        app.execute_contract(
+            api.addr_make(sender),
+            contracts.controller.clone(),
+            &ExecuteMsg::SetReferralTier {
+                referrer: api.addr_make("frontend"),
+                share,
+            },
+            &[],
+        )
+    };
+    let err = set_tier(&mut app, "frontend", Some(Decimal::percent(20))).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    let err = set_tier(&mut app, "owner", Some(Decimal::percent(150))).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidReferralShare {}
+    ));
+    set_tier(&mut app, "owner", Some(Decimal::percent(20))).unwrap();
+
+    // The referrer's share is capped by their tier
+    app.execute_contract(
+        api.addr_make("owner"),
+        contracts.controller.clone(),
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            referral_share: Some(Decimal::percent(50)),
+            ..Default::default()
+        }),
         &[],
     )
     .unwrap();
 
-    // Reserve rate is 1.0 + 35 / 20000 = 1.00175
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        contracts.controller.clone(),
+        &ExecuteMsg::Unstake {
+            callback: None,
+            max_fee: AmountU128::new(Uint128::from(10000u128)),
+            min_receive: None,
+            deadline: None,
+            referrer: Some(api.addr_make("frontend")),
+        },
+        &coins(10000u128, "base"),
+    )
+    .unwrap();
 
-    // Third deposit, should have slight less receipt token printed as the reserves > supply
+    let positions: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Positions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let position = positions.positions[0].clone();
+    assert_eq!(position.referrer, Some(api.addr_make("frontend")));
+    assert_eq!(position.referral_share, Decimal::percent(20));
+
+    // The share is fixed at the Unstake, so lowering the tier doesn't affect the position
+    set_tier(&mut app, "owner", Some(Decimal::percent(10))).unwrap();
+
+    app.update_block(|x| {
+        x.height += 1;
+        x.time = x.time.plus_days(14);
+    });
+    let res = app
+        .execute_contract(
+            api.addr_make("random"),
+            position.delegate,
+            &unstake::delegate::ExecuteMsg::Complete { keeper: None },
+            &[],
+        )
+        .unwrap();
+
+    // 11 protocol fee, of which 20% goes to the referrer
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/complete").add_attributes(vec![
+            ("protocol_fee_amount", "9".to_string()),
+            ("referrer", api.addr_make("frontend").to_string()),
+            ("referral_fee", "2".to_string()),
+        ]),
+    );
+    assert_eq!(
+        query_balances(&app, api.addr_make("frontend")),
+        coins(2u128, "quote")
+    );
+    assert_eq!(query_balances(&app, fee_address()), coins(9u128, "quote"));
+
+    let query_referrals = |app: &CustomApp| -> Vec<Referral> {
+        let res: ReferralsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.controller.clone(),
+                &QueryMsg::Referrals {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        res.referrals
+    };
+    let referral = Referral {
+        referrer: api.addr_make("frontend"),
+        tier: Decimal::percent(10),
+        volume: position.offer.offer_amount,
+        earnings: AmountU128::new(Uint128::from(2u128)),
+    };
+    assert_eq!(query_referrals(&app), vec![referral.clone()]);
+
+    // A referrer without a tier is paid nothing, and its volume isn't recorded
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        contracts.controller.clone(),
+        &ExecuteMsg::Unstake {
+            callback: None,
+            max_fee: AmountU128::new(Uint128::from(10000u128)),
+            min_receive: None,
+            deadline: None,
+            referrer: Some(api.addr_make("other")),
+        },
+        &coins(10000u128, "base"),
+    )
+    .unwrap();
+    let positions: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Positions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let position = positions
+        .positions
+        .into_iter()
+        .find(|x| x.status == PositionStatus::Unbonding)
+        .unwrap();
+    assert_eq!(position.referrer, Some(api.addr_make("other")));
+    assert_eq!(position.referral_share, Decimal::zero());
+    assert_eq!(query_referrals(&app), vec![referral]);
+
+    // Removing the tier removes the referrer
+    set_tier(&mut app, "owner", None).unwrap();
+    assert_eq!(query_referrals(&app), vec![]);
+}
+
+#[test]
+fn fee_tiers() {
+    // Same as close_offer, with the unstaker locking enough of the governance token for a rebate
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(100000000u128, "quote")),
+        (
+            api.addr_make("unstaker"),
+            vec![coin(10000u128, "base"), coin(1000u128, "gov")],
+        ),
+        (api.addr_make("lender"), coins(100000000u128, "quote")),
+    ];
+    let (mut app, contracts) = setup(balances, None);
     fund_reserve(
         &mut app,
         api.addr_make("funder"),
@@ -1002,32 +4674,300 @@ fn reserves() {
         20000u128.into(),
         "quote",
     );
+    app.send_tokens(
+        api.addr_make("funder"),
+        contracts.provider.clone(),
+        &coins(500000u128, "quote"),
+    )
+    .unwrap();
+    app.execute_contract(
+        api.addr_make("lender"),
+        contracts.ghost.clone(),
+        &kujira_ghost::receipt_vault::ExecuteMsg::Deposit(
+            kujira_ghost::receipt_vault::DepositMsg { callback: None },
+        ),
+        &coins(100000000u128, "quote"),
+    )
+    .unwrap();
 
-    // 20000 / 1.00175 = 19965
+    let partners = FeeTier {
+        name: "partners".to_string(),
+        min_balance: None,
+        min_rate: Some(Decimal::percent(120)),
+        rebate: Decimal::zero(),
+    };
+    let holders = FeeTier {
+        name: "holders".to_string(),
+        min_balance: Some(coin(1000u128, "gov")),
+        min_rate: None,
+        rebate: Decimal::percent(50),
+    };
 
-    let rct_balance = app
+    let err = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::ScheduleChange {
+                change: Change::FeeTiers {
+                    tiers: vec![FeeTier {
+                        min_rate: Some(Decimal::percent(400)),
+                        ..partners.clone()
+                    }],
+                },
+                effective: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidFeeTier { name } if name == "partners"
+    ));
+
+    change(
+        &mut app,
+        &contracts,
+        Change::FeeTiers {
+            tiers: vec![partners.clone(), holders.clone()],
+        },
+    );
+    let config: ConfigResponse = app
         .wrap()
-        .query_balance(
-            api.addr_make("funder"),
-            format!("factory/{}/ursv", contracts.reserve),
+        .query_wasm_smart(contracts.controller.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.fee_tiers, vec![partners.clone(), holders]);
+
+    let err = app
+        .execute_contract(
+            api.addr_make("owner"),
+            contracts.controller.clone(),
+            &ExecuteMsg::ScheduleChange {
+                change: Change::FeeTiers {
+                    tiers: vec![partners.clone(), partners],
+                },
+                effective: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidFeeTier { name } if name == "partners"
+    ));
+
+    let set_member = |app: &mut CustomApp, sender: &str, address: Addr, tier: Option<&str>| {
+        app.execute_contract(
+            api.addr_make(sender),
+            contracts.controller.clone(),
+            &ExecuteMsg::SetFeeTierMember {
+                address,
+                tier: tier.map(|x| x.to_string()),
+            },
+            &[],
+        )
+    };
+    let err = set_member(
+        &mut app,
+        "partner",
+        api.addr_make("partner"),
+        Some("partners"),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    let err = set_member(&mut app, "owner", api.addr_make("partner"), Some("vip")).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidFeeTier { name } if name == "vip"
+    ));
+    set_member(
+        &mut app,
+        "owner",
+        Addr::unchecked("partner"),
+        Some("partners"),
+    )
+    .unwrap_err();
+    set_member(
+        &mut app,
+        "owner",
+        api.addr_make("partner"),
+        Some("partners"),
+    )
+    .unwrap();
+    let members: FeeTierMembersResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::FeeTierMembers {
+                start_after: None,
+                limit: None,
+            },
         )
         .unwrap();
-    assert_eq!(rct_balance.amount, Uint128::from(20000u128 + 19965u128));
+    assert_eq!(
+        members.members,
+        vec![FeeTierMember {
+            address: api.addr_make("partner"),
+            tier: "partners".to_string(),
+        }]
+    );
+
+    let offer_tier = |app: &CustomApp, sender: &str| -> Option<String> {
+        let offer: OfferResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.controller.clone(),
+                &QueryMsg::Offer {
+                    amount: AmountU128::new(Uint128::from(10000u128)),
+                    ask_denom: None,
+                    sender: Some(api.addr_make(sender)),
+                },
+            )
+            .unwrap();
+        offer.tier
+    };
+
+    // Holding the balance isn't enough, it must be locked for the lock period
+    assert_eq!(offer_tier(&app, "unstaker"), None);
+    app.execute_contract(
+        api.addr_make("unstaker"),
+        contracts.controller.clone(),
+        &ExecuteMsg::Lock {},
+        &coins(1000u128, "gov"),
+    )
+    .unwrap();
+    assert_eq!(offer_tier(&app, "unstaker"), None);
+    app.update_block(|x| {
+        x.height += 1;
+        x.time = x.time.plus_days(7);
+    });
+    assert_eq!(offer_tier(&app, "unstaker"), Some("holders".to_string()));
 
-    // And now we withdraw some of what's left, ratio should be fractionally greater than 1
     let res = app
         .execute_contract(
-            api.addr_make("funder"),
-            contracts.reserve.clone(),
-            &unstake::reserve::ExecuteMsg::Withdraw { callback: None },
-            &coins(20000u128, format!("factory/{}/ursv", contracts.reserve)),
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Unstake {
+                callback: None,
+                max_fee: AmountU128::new(Uint128::from(10000u128)),
+                min_receive: None,
+                deadline: None,
+                referrer: None,
+            },
+            &coins(10000u128, "base"),
         )
         .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/unstake")
+            .add_attributes(vec![("fee_tier", "holders")]),
+    );
 
-    // 20000u128 * 1.00175 = 20035
-    res.assert_event(&Event::new("transfer").add_attributes(vec![
-        ("recipient", api.addr_make("funder").to_string()),
-        ("sender", contracts.reserve.to_string()),
-        ("amount", "20035quote".to_string()),
-    ]));
+    let positions: PositionsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Positions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let position = positions.positions[0].clone();
+    assert_eq!(position.rebate, Decimal::percent(50));
+
+    app.update_block(|x| {
+        x.time = x.time.plus_days(14);
+    });
+    let res = app
+        .execute_contract(
+            api.addr_make("random"),
+            position.delegate,
+            &unstake::delegate::ExecuteMsg::Complete { keeper: None },
+            &[],
+        )
+        .unwrap();
+
+    // 11 protocol fee, of which 50% is rebated to the unstaker
+    res.assert_event(
+        &Event::new("wasm-unstake/controller/complete")
+            .add_attributes(vec![("protocol_fee_amount", "6"), ("rebate", "5")]),
+    );
+    assert_eq!(query_balances(&app, fee_address()), coins(6u128, "quote"));
+
+    // Partners are quoted against their lower floor
+    change(
+        &mut app,
+        &contracts,
+        Change::Broker {
+            min_rate: Some(Decimal::percent(150)),
+            duration: None,
+            pricing: None,
+        },
+    );
+    let quote = |app: &CustomApp, sender: Option<Addr>| -> OfferResponse {
+        app.wrap()
+            .query_wasm_smart(
+                contracts.controller.clone(),
+                &QueryMsg::Offer {
+                    amount: AmountU128::new(Uint128::from(10000u128)),
+                    ask_denom: None,
+                    sender,
+                },
+            )
+            .unwrap()
+    };
+    let public = quote(&app, None);
+    assert_eq!(public.rate, Decimal::percent(150));
+    assert_eq!(public.tier, None);
+
+    let partner = quote(&app, Some(api.addr_make("partner")));
+    assert_eq!(partner.rate, Decimal::percent(120));
+    assert_eq!(partner.tier, Some("partners".to_string()));
+    assert!(partner.amount > public.amount);
+
+    let holder = quote(&app, Some(api.addr_make("unstaker")));
+    assert_eq!(holder.rate, Decimal::percent(150));
+    assert_eq!(holder.tier, Some("holders".to_string()));
+
+    // Unlocking returns the tokens, and the tier with them
+    let locks: LocksResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contracts.controller.clone(),
+            &QueryMsg::Locks {
+                address: api.addr_make("unstaker"),
+            },
+        )
+        .unwrap();
+    assert_eq!(locks.locks.len(), 1);
+    assert_eq!(locks.locks[0].amount, coin(1000u128, "gov"));
+    let unlock = |app: &mut CustomApp, amount: u128| {
+        app.execute_contract(
+            api.addr_make("unstaker"),
+            contracts.controller.clone(),
+            &ExecuteMsg::Unlock {
+                amount: coin(amount, "gov"),
+            },
+            &[],
+        )
+    };
+    let err = unlock(&mut app, 1001).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficentFunds {}
+    ));
+    unlock(&mut app, 1000).unwrap();
+    assert_eq!(
+        app.wrap()
+            .query_balance(api.addr_make("unstaker"), "gov")
+            .unwrap(),
+        coin(1000u128, "gov")
+    );
+    assert_eq!(offer_tier(&app, "unstaker"), None);
+
+    // Removing the partner from its tier quotes it as the public
+    set_member(&mut app, "owner", api.addr_make("partner"), None).unwrap();
+    assert_eq!(offer_tier(&app, "partner"), None);
 }
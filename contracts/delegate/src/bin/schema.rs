@@ -1,11 +1,12 @@
 use cosmwasm_schema::write_api;
 
-use unstake::delegate::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use unstake::delegate::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
         query: QueryMsg,
+        sudo: SudoMsg,
     }
 }
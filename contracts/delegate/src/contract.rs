@@ -1,34 +1,62 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    ensure_eq, Addr, Binary, Coins, Deps, DepsMut, Env, Event, MessageInfo, Response,
+    ensure, ensure_eq, to_json_binary, Addr, Binary, Coin, Coins, CosmosMsg, Deps, DepsMut, Env,
+    Event, MessageInfo, Response, Timestamp,
 };
 use cw_storage_plus::Item;
 use kujira::{KujiraMsg, KujiraQuery};
+use unstake::adapter::stride::IbcLifecycleComplete;
 use unstake::adapter::{Adapter, Unstake};
 use unstake::broker::Offer;
-use unstake::delegate::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use unstake::delegate::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg};
 use unstake::helpers::{Controller, Delegate};
 use unstake::ContractError;
 
 static CONTROLLER: Item<Addr> = Item::new("controller");
 static OFFER: Item<Offer> = Item::new("offer");
 static ADAPTER: Item<Adapter> = Item::new("adapter");
+static CREATED: Item<Timestamp> = Item::new("created");
+static UNBOND_AMOUNT: Item<Coin> = Item::new("unbond_amount");
+static CW20: Item<bool> = Item::new("cw20");
+static RETRIES: Item<u32> = Item::new("retries");
+
+/// The number of times a failed IBC transfer is retried automatically, before waiting on
+/// `RetryUnbond`
+const MAX_RETRIES: u32 = 3;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut<KujiraQuery>,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response<KujiraMsg>, ContractError> {
     CONTROLLER.save(deps.storage, &msg.controller)?;
+    CREATED.save(deps.storage, &env.block.time)?;
     OFFER.save(deps.storage, &msg.offer)?;
     ADAPTER.save(deps.storage, &msg.adapter)?;
-    let unbond_msg = msg.adapter.unbond_start(msg.unbond_amount.into());
+    UNBOND_AMOUNT.save(deps.storage, &msg.unbond_amount.into())?;
+    CW20.save(deps.storage, &msg.cw20)?;
+    let unbond_msg = unbond_start_msg(deps.as_ref(), &env)?;
     Ok(Response::default().add_message(unbond_msg))
 }
 
+fn unbond_start_msg(
+    deps: Deps<KujiraQuery>,
+    env: &Env,
+) -> Result<CosmosMsg<KujiraMsg>, ContractError> {
+    let adapter = ADAPTER.load(deps.storage)?;
+    let unbond_amount = UNBOND_AMOUNT.load(deps.storage)?;
+    let msg = if CW20.may_load(deps.storage)?.unwrap_or_default() {
+        let token = deps.api.addr_validate(&unbond_amount.denom)?;
+        adapter.unbond_start_cw20(env, &token, unbond_amount.amount)?
+    } else {
+        adapter.unbond_start(env, unbond_amount)?
+    };
+    Ok(msg)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut<KujiraQuery>,
@@ -37,17 +65,37 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response<KujiraMsg>, ContractError> {
     match msg {
-        ExecuteMsg::Complete {} => {
+        ExecuteMsg::Complete { keeper } => {
+            let controller = CONTROLLER.load(deps.storage)?;
+            let keeper = match keeper {
+                Some(keeper) if info.sender == controller => keeper,
+                _ => info.sender,
+            };
             let adapter = ADAPTER.load(deps.storage)?;
-            let claim_msg = adapter.unbond_end();
-            let callback_msg =
-                Delegate(env.contract.address).call(ExecuteMsg::Callback {}, vec![])?;
+            // Fail early where the provider reports the unbonding as still in progress. A failed
+            // status query shouldn't block the withdrawal, which is the final arbiter
+            let status = adapter
+                .unbond_status(deps.querier, &env, &env.contract.address)
+                .ok()
+                .flatten();
+            if let Some(status) = status.filter(|x| !x.pending.is_zero()) {
+                return Err(ContractError::UnbondingIncomplete {
+                    pending: status.pending,
+                });
+            }
+            let claim_msg = adapter.unbond_end()?;
+            let callback_msg = Delegate(env.contract.address).call(
+                ExecuteMsg::Callback {
+                    keeper: Some(keeper),
+                },
+                vec![],
+            )?;
 
             Ok(Response::default()
-                .add_message(claim_msg)
+                .add_messages(claim_msg)
                 .add_message(callback_msg))
         }
-        ExecuteMsg::Callback {} => {
+        ExecuteMsg::Callback { keeper } => {
             ensure_eq!(
                 info.sender,
                 env.contract.address,
@@ -58,6 +106,7 @@ pub fn execute(
             let controller_msg = Controller(CONTROLLER.load(deps.storage)?).call(
                 unstake::controller::ExecuteMsg::Complete {
                     offer: offer.clone(),
+                    keeper,
                 },
                 funds.clone(),
             )?;
@@ -72,12 +121,78 @@ pub fn execute(
                 .add_event(event)
                 .add_message(controller_msg))
         }
+        ExecuteMsg::RetryUnbond {} => {
+            let retries = RETRIES.may_load(deps.storage)?.unwrap_or_default();
+            ensure!(retries >= MAX_RETRIES, ContractError::RetryUnavailable {});
+            RETRIES.save(deps.storage, &0)?;
+            let unbond_msg = unbond_start_msg(deps.as_ref(), &env)?;
+            let event =
+                Event::new("unstake/delegate/retry_unbond").add_attribute("sender", info.sender);
+            Ok(Response::default().add_event(event).add_message(unbond_msg))
+        }
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(_deps: Deps<KujiraQuery>, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
-    match msg {}
+pub fn sudo(
+    deps: DepsMut<KujiraQuery>,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response<KujiraMsg>, ContractError> {
+    match msg {
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel,
+            sequence,
+            success: true,
+            ..
+        }) => {
+            let event = Event::new("unstake/delegate/ibc_ack")
+                .add_attribute("channel", channel)
+                .add_attribute("sequence", sequence.to_string());
+            Ok(Response::default().add_event(event))
+        }
+        SudoMsg::IbcLifecycleComplete(
+            IbcLifecycleComplete::IbcAck {
+                channel, sequence, ..
+            }
+            | IbcLifecycleComplete::IbcTimeout { channel, sequence },
+        ) => {
+            // The tokens have been refunded, so the unbond is retried until the retries run out.
+            // After that the tokens are held until `RetryUnbond` is called
+            let retries = RETRIES.may_load(deps.storage)?.unwrap_or_default();
+            if retries >= MAX_RETRIES {
+                let event = Event::new("unstake/delegate/ibc_failed")
+                    .add_attribute("channel", channel)
+                    .add_attribute("sequence", sequence.to_string());
+                return Ok(Response::default().add_event(event));
+            }
+            RETRIES.save(deps.storage, &(retries + 1))?;
+            let unbond_msg = unbond_start_msg(deps.as_ref(), &env)?;
+            let event = Event::new("unstake/delegate/ibc_retry")
+                .add_attribute("channel", channel)
+                .add_attribute("sequence", sequence.to_string())
+                .add_attribute("attempt", (retries + 1).to_string());
+            Ok(Response::default().add_event(event).add_message(unbond_msg))
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<KujiraQuery>, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Controller {} => Ok(to_json_binary(&CONTROLLER.load(deps.storage)?)?),
+        QueryMsg::Offer {} => Ok(to_json_binary(&OFFER.load(deps.storage)?)?),
+        QueryMsg::Adapter {} => Ok(to_json_binary(&ADAPTER.load(deps.storage)?)?),
+        QueryMsg::Created {} => Ok(to_json_binary(&CREATED.may_load(deps.storage)?)?),
+        QueryMsg::Balances {} => Ok(to_json_binary(
+            &deps.querier.query_all_balances(env.contract.address)?,
+        )?),
+        QueryMsg::Status {} => {
+            let adapter = ADAPTER.load(deps.storage)?;
+            let status = adapter.unbond_status(deps.querier, &env, &env.contract.address)?;
+            Ok(to_json_binary(&status)?)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,29 @@
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response};
+use kujira::{KujiraMsg, KujiraQuery};
+use unstake::{controller::ExecuteMsg, ContractError};
+
+/// Accepts the delegate's completion, keeping the funds it is sent
+pub fn instantiate(
+    _deps: DepsMut<KujiraQuery>,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: (),
+) -> Result<Response<KujiraMsg>, ContractError> {
+    Ok(Response::default())
+}
+
+pub fn execute(
+    _deps: DepsMut<KujiraQuery>,
+    _env: Env,
+    _info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<KujiraMsg>, ContractError> {
+    match msg {
+        ExecuteMsg::Complete { .. } => Ok(Response::default()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+pub fn query(_deps: Deps<KujiraQuery>, _env: Env, _msg: ()) -> Result<Binary, ContractError> {
+    Err(ContractError::Unauthorized {})
+}
@@ -0,0 +1,105 @@
+use anyhow::{bail, Result as AnyResult};
+use cosmwasm_std::{
+    to_json_binary, Addr, Api, BankMsg, Binary, BlockInfo, CustomMsg, CustomQuery, Empty, IbcMsg,
+    IbcQuery, Querier, Storage, WasmMsg,
+};
+use cw_multi_test::{AppResponse, CosmosRouter, Ibc, Module};
+use serde::de::DeserializeOwned;
+
+use super::stride::ExecuteMsg;
+
+/// Stands in for the counterparty chain, delivering IBC transfers to the contract at
+/// `to_address` on this chain. A transfer the contract rejects leaves the tokens with the sender,
+/// as a refund would. Acks are left to the test to relay
+pub struct LoopbackIbc;
+
+impl Ibc for LoopbackIbc {}
+
+impl Module for LoopbackIbc {
+    type ExecT = IbcMsg;
+    type QueryT = IbcQuery;
+    type SudoT = Empty;
+
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        msg: IbcMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        let IbcMsg::Transfer {
+            to_address,
+            amount,
+            memo,
+            ..
+        } = msg
+        else {
+            bail!("Unsupported IBC message");
+        };
+
+        let delivery = router.execute(
+            api,
+            storage,
+            block,
+            sender.clone(),
+            WasmMsg::Execute {
+                contract_addr: to_address.clone(),
+                msg: to_json_binary(&ExecuteMsg::IbcTransfer {
+                    sender: sender.to_string(),
+                    amount: amount.clone(),
+                    memo: memo.unwrap_or_default(),
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+
+        if delivery.is_ok() {
+            router.execute(
+                api,
+                storage,
+                block,
+                sender,
+                BankMsg::Send {
+                    to_address,
+                    amount: vec![amount],
+                }
+                .into(),
+            )?;
+        }
+
+        Ok(AppResponse::default())
+    }
+
+    fn query(
+        &self,
+        _api: &dyn Api,
+        _storage: &dyn Storage,
+        _querier: &dyn Querier,
+        _block: &BlockInfo,
+        _request: IbcQuery,
+    ) -> AnyResult<Binary> {
+        bail!("Unsupported IBC query")
+    }
+
+    fn sudo<ExecC, QueryC>(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        _block: &BlockInfo,
+        _msg: Empty,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        bail!("Unsupported IBC sudo")
+    }
+}
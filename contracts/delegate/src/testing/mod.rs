@@ -0,0 +1,4 @@
+mod controller;
+mod ibc;
+mod stride;
+mod tests;
@@ -0,0 +1,114 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    from_json, to_json_binary, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Order, Response, StdResult, Timestamp, Uint128,
+};
+use cw_storage_plus::{Item, Map};
+use kujira::{KujiraMsg, KujiraQuery};
+use unstake::{
+    adapter::stride::{Memo, OracleQueryMsg, RedemptionRateResponse},
+    ContractError,
+};
+
+/// Simulates the redemption on the provider's chain. LST received over IBC is redeemed at `rate`,
+/// and paid out to the `ibc_receiver` in `denom` once `unbonding_period` has passed
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub rate: Decimal,
+    pub denom: String,
+    pub unbonding_period: u64,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Delivered by the loopback IBC module
+    IbcTransfer {
+        sender: String,
+        amount: Coin,
+        memo: String,
+    },
+    /// Rejects incoming transfers whilst set
+    SetHalted { halted: bool },
+    /// Returns all matured redemptions
+    Payout {},
+}
+
+static INIT: Item<InstantiateMsg> = Item::new("init");
+static HALTED: Item<bool> = Item::new("halted");
+static PENDING: Map<String, (Timestamp, Uint128)> = Map::new("pending");
+
+pub fn instantiate(
+    deps: DepsMut<KujiraQuery>,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response<KujiraMsg>, ContractError> {
+    INIT.save(deps.storage, &msg)?;
+    HALTED.save(deps.storage, &false)?;
+    Ok(Response::default())
+}
+
+pub fn execute(
+    deps: DepsMut<KujiraQuery>,
+    env: Env,
+    _info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<KujiraMsg>, ContractError> {
+    let init = INIT.load(deps.storage)?;
+    match msg {
+        ExecuteMsg::IbcTransfer { amount, memo, .. } => {
+            if HALTED.load(deps.storage)? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let memo: Memo = from_json(memo)?;
+            if memo.autopilot.stakeibc.action != "RedeemStake" {
+                return Err(ContractError::Unauthorized {});
+            }
+            let end = env.block.time.plus_seconds(init.unbonding_period);
+            PENDING.save(
+                deps.storage,
+                memo.autopilot.stakeibc.ibc_receiver,
+                &(end, amount.amount.mul_floor(init.rate)),
+            )?;
+
+            Ok(Response::default())
+        }
+        ExecuteMsg::SetHalted { halted } => {
+            HALTED.save(deps.storage, &halted)?;
+            Ok(Response::default())
+        }
+        ExecuteMsg::Payout {} => {
+            let matured = PENDING
+                .range(deps.storage, None, None, Order::Ascending)
+                .filter(|x| {
+                    x.as_ref()
+                        .map_or(true, |(_, (end, _))| *end <= env.block.time)
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+
+            let mut msgs = vec![];
+            for (receiver, (_, amount)) in matured {
+                PENDING.remove(deps.storage, receiver.clone());
+                msgs.push(BankMsg::Send {
+                    to_address: receiver,
+                    amount: vec![Coin::new(amount, init.denom.clone())],
+                });
+            }
+
+            Ok(Response::default().add_messages(msgs))
+        }
+    }
+}
+
+pub fn query(
+    deps: Deps<KujiraQuery>,
+    env: Env,
+    msg: OracleQueryMsg,
+) -> Result<Binary, ContractError> {
+    match msg {
+        OracleQueryMsg::RedemptionRate { .. } => Ok(to_json_binary(&RedemptionRateResponse {
+            redemption_rate: INIT.load(deps.storage)?.rate,
+            update_time: env.block.time.seconds(),
+        })?),
+    }
+}
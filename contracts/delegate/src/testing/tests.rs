@@ -0,0 +1,378 @@
+use std::str::FromStr;
+
+use cosmwasm_std::{
+    coins, testing::MockApi, testing::MockStorage, Addr, Coin, Decimal, Empty, Event, Uint128,
+};
+use cw_multi_test::{
+    App, BankKeeper, BasicAppBuilder, ContractWrapper, DistributionKeeper, Executor, FailingModule,
+    StakeKeeper, WasmKeeper,
+};
+use kujira::{KujiraMsg, KujiraQuery};
+use monetary::{AmountU128, CheckedCoin};
+use unstake::{
+    adapter::{
+        stride::{IbcLifecycleComplete, Stride},
+        Adapter, UnbondStatus, Unstake,
+    },
+    broker::Offer,
+    delegate::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg},
+    ContractError,
+};
+
+use super::ibc::LoopbackIbc;
+
+const LST: &str = "ibc/STATOM";
+const NATIVE: &str = "ibc/ATOM";
+
+type StrideApp = App<
+    BankKeeper,
+    MockApi,
+    MockStorage,
+    FailingModule<KujiraMsg, KujiraQuery, Empty>,
+    WasmKeeper<KujiraMsg, KujiraQuery>,
+    StakeKeeper,
+    DistributionKeeper,
+    LoopbackIbc,
+>;
+
+struct Contracts {
+    pub controller: Addr,
+    pub stride: Addr,
+    pub delegate_code_id: u64,
+}
+
+fn setup() -> (StrideApp, Contracts) {
+    let mut app = BasicAppBuilder::<KujiraMsg, KujiraQuery>::new_custom()
+        .with_ibc(LoopbackIbc)
+        .build(|router, _, storage| {
+            let api = MockApi::default();
+            router
+                .bank
+                .init_balance(storage, &api.addr_make("unstaker"), coins(1000u128, LST))
+                .unwrap();
+            router
+                .bank
+                .init_balance(storage, &api.addr_make("funder"), coins(100000u128, NATIVE))
+                .unwrap();
+        });
+
+    let delegate_code = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_sudo(crate::contract::sudo);
+    let controller_code = ContractWrapper::new(
+        crate::testing::controller::execute,
+        crate::testing::controller::instantiate,
+        crate::testing::controller::query,
+    );
+    let stride_code = ContractWrapper::new(
+        crate::testing::stride::execute,
+        crate::testing::stride::instantiate,
+        crate::testing::stride::query,
+    );
+
+    let delegate_code_id = app.store_code(Box::new(delegate_code));
+    let controller_code_id = app.store_code(Box::new(controller_code));
+    let stride_code_id = app.store_code(Box::new(stride_code));
+
+    let controller = app
+        .instantiate_contract(
+            controller_code_id,
+            app.api().addr_make("controller"),
+            &(),
+            &[],
+            "controller",
+            None,
+        )
+        .unwrap();
+
+    let stride = app
+        .instantiate_contract(
+            stride_code_id,
+            app.api().addr_make("stride"),
+            &crate::testing::stride::InstantiateMsg {
+                rate: Decimal::from_str("1.2").unwrap(),
+                denom: NATIVE.to_string(),
+                // 3 weeks
+                unbonding_period: 21 * 24 * 60 * 60,
+            },
+            &[],
+            "stride",
+            None,
+        )
+        .unwrap();
+
+    // Make sure the provider can return the redeemed tokens
+    app.send_tokens(
+        app.api().addr_make("funder"),
+        stride.clone(),
+        &coins(100000u128, NATIVE),
+    )
+    .unwrap();
+
+    (
+        app,
+        Contracts {
+            controller,
+            stride,
+            delegate_code_id,
+        },
+    )
+}
+
+fn adapter(contracts: &Contracts) -> Adapter {
+    Adapter::Stride(Stride {
+        oracle: contracts.stride.clone(),
+        oracle_denom: "stuatom".to_string(),
+        channel: "channel-0".to_string(),
+        receiver: contracts.stride.to_string(),
+        timeout: 600,
+    })
+}
+
+fn instantiate_delegate(app: &mut StrideApp, contracts: &Contracts) -> Addr {
+    let amount = AmountU128::new(Uint128::from(1000u128));
+    app.instantiate_contract(
+        contracts.delegate_code_id,
+        app.api().addr_make("unstaker"),
+        &InstantiateMsg {
+            controller: contracts.controller.clone(),
+            unbond_amount: CheckedCoin::new(monetary::Denom::new(LST), amount),
+            offer: Offer {
+                unbond_amount: amount,
+                offer_amount: AmountU128::new(Uint128::from(1150u128)),
+                fee: AmountU128::new(Uint128::from(50u128)),
+                reserve_allocation: AmountU128::zero(),
+            },
+            adapter: adapter(contracts),
+            cw20: false,
+        },
+        &coins(1000u128, LST),
+        "delegate",
+        None,
+    )
+    .unwrap()
+}
+
+fn balance(app: &StrideApp, address: &Addr, denom: &str) -> Uint128 {
+    app.wrap().query_balance(address, denom).unwrap().amount
+}
+
+#[test]
+fn redemption_rate() {
+    let (app, contracts) = setup();
+    let rate = adapter(&contracts).redemption_rate(app.wrap()).unwrap();
+    assert_eq!(rate, Decimal::from_str("1.2").unwrap());
+}
+
+#[test]
+fn unbond() {
+    let (mut app, contracts) = setup();
+    let delegate = instantiate_delegate(&mut app, &contracts);
+
+    // The LST has been sent to the provider to redeem
+    assert_eq!(balance(&app, &delegate, LST), Uint128::zero());
+    assert_eq!(
+        balance(&app, &contracts.stride, LST),
+        Uint128::from(1000u128)
+    );
+
+    let res = app
+        .wasm_sudo(
+            delegate.clone(),
+            &SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+                channel: "channel-0".to_string(),
+                sequence: 1,
+                ack: "".to_string(),
+                success: true,
+            }),
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/delegate/ibc_ack")
+            .add_attributes(vec![("channel", "channel-0"), ("sequence", "1")]),
+    );
+
+    // The redemption is tracked on the provider's chain
+    let status: Option<UnbondStatus> = app
+        .wrap()
+        .query_wasm_smart(delegate.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(status, None);
+
+    app.update_block(|x| {
+        x.time = x.time.plus_days(21);
+    });
+
+    app.execute_contract(
+        app.api().addr_make("relayer"),
+        contracts.stride.clone(),
+        &crate::testing::stride::ExecuteMsg::Payout {},
+        &[],
+    )
+    .unwrap();
+    assert_eq!(balance(&app, &delegate, NATIVE), Uint128::from(1200u128));
+
+    // There's nothing to claim, so the returned funds are passed straight to the controller
+    app.execute_contract(
+        app.api().addr_make("keeper"),
+        delegate.clone(),
+        &ExecuteMsg::Complete { keeper: None },
+        &[],
+    )
+    .unwrap();
+
+    let balances: Vec<Coin> = app
+        .wrap()
+        .query_wasm_smart(delegate, &QueryMsg::Balances {})
+        .unwrap();
+    assert_eq!(balances, vec![]);
+    assert_eq!(
+        balance(&app, &contracts.controller, NATIVE),
+        Uint128::from(1200u128)
+    );
+}
+
+#[test]
+fn retry_failed_transfer() {
+    let (mut app, contracts) = setup();
+    app.execute_contract(
+        app.api().addr_make("stride"),
+        contracts.stride.clone(),
+        &crate::testing::stride::ExecuteMsg::SetHalted { halted: true },
+        &[],
+    )
+    .unwrap();
+
+    let delegate = instantiate_delegate(&mut app, &contracts);
+
+    // The transfer was rejected, and the LST refunded
+    assert_eq!(balance(&app, &delegate, LST), Uint128::from(1000u128));
+
+    // A failed ack retries the transfer, which is rejected again whilst the provider is halted
+    let res = app
+        .wasm_sudo(
+            delegate.clone(),
+            &SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+                channel: "channel-0".to_string(),
+                sequence: 1,
+                ack: "".to_string(),
+                success: false,
+            }),
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/delegate/ibc_retry")
+            .add_attributes(vec![("channel", "channel-0"), ("sequence", "1")]),
+    );
+    assert_eq!(balance(&app, &delegate, LST), Uint128::from(1000u128));
+
+    app.execute_contract(
+        app.api().addr_make("stride"),
+        contracts.stride.clone(),
+        &crate::testing::stride::ExecuteMsg::SetHalted { halted: false },
+        &[],
+    )
+    .unwrap();
+
+    let res = app
+        .wasm_sudo(
+            delegate.clone(),
+            &SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout {
+                channel: "channel-0".to_string(),
+                sequence: 2,
+            }),
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/delegate/ibc_retry")
+            .add_attributes(vec![("channel", "channel-0"), ("sequence", "2")]),
+    );
+    assert_eq!(balance(&app, &delegate, LST), Uint128::zero());
+    assert_eq!(
+        balance(&app, &contracts.stride, LST),
+        Uint128::from(1000u128)
+    );
+}
+
+#[test]
+fn retries_exhausted() {
+    let (mut app, contracts) = setup();
+    app.execute_contract(
+        app.api().addr_make("stride"),
+        contracts.stride.clone(),
+        &crate::testing::stride::ExecuteMsg::SetHalted { halted: true },
+        &[],
+    )
+    .unwrap();
+
+    let delegate = instantiate_delegate(&mut app, &contracts);
+    let timeout = |app: &mut StrideApp, sequence: u64| {
+        app.wasm_sudo(
+            delegate.clone(),
+            &SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout {
+                channel: "channel-0".to_string(),
+                sequence,
+            }),
+        )
+        .unwrap()
+    };
+
+    // The automatic retries are still available
+    let err = app
+        .execute_contract(
+            app.api().addr_make("keeper"),
+            delegate.clone(),
+            &ExecuteMsg::RetryUnbond {},
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RetryUnavailable {}
+    ));
+
+    for sequence in 1..=3 {
+        let res = timeout(&mut app, sequence);
+        res.assert_event(
+            &Event::new("wasm-unstake/delegate/ibc_retry")
+                .add_attributes(vec![("attempt", sequence.to_string())]),
+        );
+    }
+
+    // Once they run out, the refunded LST is held by the delegate
+    let res = timeout(&mut app, 4);
+    res.assert_event(
+        &Event::new("wasm-unstake/delegate/ibc_failed")
+            .add_attributes(vec![("channel", "channel-0"), ("sequence", "4")]),
+    );
+    assert_eq!(balance(&app, &delegate, LST), Uint128::from(1000u128));
+
+    // And is sent on by a keeper once the provider has recovered
+    app.execute_contract(
+        app.api().addr_make("stride"),
+        contracts.stride.clone(),
+        &crate::testing::stride::ExecuteMsg::SetHalted { halted: false },
+        &[],
+    )
+    .unwrap();
+    let res = app
+        .execute_contract(
+            app.api().addr_make("keeper"),
+            delegate.clone(),
+            &ExecuteMsg::RetryUnbond {},
+            &[],
+        )
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/delegate/retry_unbond")
+            .add_attributes(vec![("sender", app.api().addr_make("keeper").to_string())]),
+    );
+    assert_eq!(balance(&app, &delegate, LST), Uint128::zero());
+    assert_eq!(
+        balance(&app, &contracts.stride, LST),
+        Uint128::from(1000u128)
+    );
+}
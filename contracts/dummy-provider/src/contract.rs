@@ -3,17 +3,31 @@ use std::str::FromStr;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, Uint128,
+    from_json, to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    Uint128,
 };
 use cw_storage_plus::{Item, Map};
-use cw_utils::{must_pay, Expiration};
+use cw_utils::{must_pay, Duration, Expiration};
 use kujira::{DenomMsg, KujiraMsg, KujiraQuery};
-use unstake::{adapter::eris::ExecuteMsg as UnstakeExecuteMsg, ContractError};
+use unstake::{
+    adapter::eris::{
+        Batch, ExecuteMsg as UnstakeExecuteMsg, ReceiveMsg, UnbondRequestsByUserResponseItemDetails,
+    },
+    ContractError,
+};
 
 use crate::msg::{ExecuteMsg, InstantiateMsg};
 
 const PENDING: Map<Addr, (Expiration, Uint128)> = Map::new("pending");
 const INIT: Item<InstantiateMsg> = Item::new("init");
+const RATE: Item<Decimal> = Item::new("rate");
+
+fn rate(deps: Deps<KujiraQuery>) -> Result<Decimal, ContractError> {
+    match RATE.may_load(deps.storage)? {
+        Some(rate) => Ok(rate),
+        None => Ok(Decimal::from_str("1.07375")?),
+    }
+}
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -41,7 +55,7 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response<KujiraMsg>, ContractError> {
-    let rate = Decimal::from_str("1.07375")?;
+    let rate = rate(deps.as_ref())?;
     let init = INIT.load(deps.storage)?;
     let lst = format!("factory/{}/{}", env.contract.address, init.lst);
     let base = format!("factory/{}/{}", env.contract.address, init.base);
@@ -53,6 +67,8 @@ pub fn execute(
                 return Ok(Response::default());
             }
 
+            PENDING.remove(deps.storage, info.sender.clone());
+
             let mint_msg = DenomMsg::Mint {
                 denom: base.clone().into(),
                 amount,
@@ -68,6 +84,18 @@ pub fn execute(
 
             Ok(Response::default())
         }
+        ExecuteMsg::Receive(cw20_msg) => {
+            let ReceiveMsg::QueueUnbond { .. } = from_json(&cw20_msg.msg)?;
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            let time = init.unbond_time.after(&env.block);
+            PENDING.save(deps.storage, sender, &(time, cw20_msg.amount))?;
+
+            Ok(Response::default())
+        }
+        ExecuteMsg::SetRate { rate } => {
+            RATE.save(deps.storage, &rate)?;
+            Ok(Response::default())
+        }
         ExecuteMsg::Mint { denom, amount } => {
             let mint_msg = DenomMsg::Mint {
                 denom: format!("factory/{}/{}", env.contract.address, denom).into(),
@@ -82,15 +110,49 @@ pub fn execute(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(
-    _deps: Deps<KujiraQuery>,
-    _env: Env,
+    deps: Deps<KujiraQuery>,
+    env: Env,
     msg: unstake::adapter::eris::ContractQueryMsg,
 ) -> Result<Binary, ContractError> {
     match msg {
         unstake::adapter::eris::ContractQueryMsg::State {} => Ok(to_json_binary(
             &unstake::adapter::eris::ContractStateResponse {
-                exchange_rate: Decimal::from_str("1.07375")?,
+                exchange_rate: rate(deps)?,
             },
         )?),
+        unstake::adapter::eris::ContractQueryMsg::Config {} => {
+            let unbond_period = match INIT.load(deps.storage)?.unbond_time {
+                Duration::Time(seconds) => seconds,
+                Duration::Height(_) => 0,
+            };
+            Ok(to_json_binary(
+                &unstake::adapter::eris::ContractConfigResponse {
+                    epoch_period: 0,
+                    unbond_period,
+                },
+            )?)
+        }
+        unstake::adapter::eris::ContractQueryMsg::UnbondRequestsByUserDetails { user, .. } => {
+            let pending = PENDING.may_load(deps.storage, deps.api.addr_validate(&user)?)?;
+            let requests: Vec<UnbondRequestsByUserResponseItemDetails> = pending
+                .map(|(time, shares)| UnbondRequestsByUserResponseItemDetails {
+                    id: 1,
+                    shares,
+                    state: if time.is_expired(&env.block) {
+                        "COMPLETED".to_string()
+                    } else {
+                        "UNBONDING".to_string()
+                    },
+                    batch: match time {
+                        Expiration::AtTime(end) => Some(Batch {
+                            est_unbond_end_time: end.seconds(),
+                        }),
+                        _ => None,
+                    },
+                })
+                .into_iter()
+                .collect();
+            Ok(to_json_binary(&requests)?)
+        }
     }
 }
@@ -2,7 +2,8 @@ use cosmwasm_schema::{
     cw_serde,
     serde::{Deserialize, Serialize},
 };
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
 use cw_utils::Duration;
 use unstake::adapter::eris::ExecuteMsg as AdapterExecute;
 
@@ -25,6 +26,12 @@ pub enum ExecuteMsg {
         denom: String,
         amount: Uint128,
     },
+    /// Sets the redemption rate, which defaults to 1.07375
+    SetRate {
+        rate: Decimal,
+    },
+    /// Unbonds a CW20 LST, sent with `eris::ReceiveMsg::QueueUnbond`
+    Receive(Cw20ReceiveMsg),
     /// Rewards interfaces
     #[serde(untagged)]
     Execute(AdapterExecute),
@@ -1,11 +1,13 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Env, QuerierWrapper, StdResult, Storage};
+use cosmwasm_std::{ensure, Addr, Env, QuerierWrapper, StdResult, Storage};
 use cw_storage_plus::Item;
 use kujira::KujiraQuery;
 use monetary::Denom;
 use unstake::{
     denoms::{Base, Rcpt, Rsv},
-    reserve::{ConfigResponse, InstantiateMsg},
+    ownership::PendingOwner,
+    reserve::{ConfigResponse, InstantiateMsg, Operation},
+    timelock, ContractError,
 };
 
 use kujira_ghost::receipt_vault::{
@@ -23,6 +25,18 @@ pub struct Config {
     pub rsv_denom: Denom<Rsv>,
     pub ghost_denom: Denom<Rcpt>,
     pub ghost_vault_addr: Addr,
+    #[serde(default)]
+    pub guardian: Option<Addr>,
+    #[serde(default)]
+    pub paused: Vec<Operation>,
+    #[serde(default)]
+    pub pending_owner: Option<PendingOwner>,
+    #[serde(default = "default_timelock")]
+    pub timelock: u64,
+}
+
+fn default_timelock() -> u64 {
+    timelock::DEFAULT_DELAY
 }
 
 impl Config {
@@ -41,6 +55,10 @@ impl Config {
             rsv_denom,
             ghost_denom,
             ghost_vault_addr: msg.ghost_vault_addr,
+            guardian: msg.guardian,
+            paused: vec![],
+            pending_owner: None,
+            timelock: msg.timelock.unwrap_or(timelock::DEFAULT_DELAY),
         })
     }
     pub fn load(storage: &dyn Storage) -> StdResult<Self> {
@@ -51,11 +69,29 @@ impl Config {
         CONFIG.save(storage, self)
     }
 
-    pub fn update(&mut self, owner: Option<Addr>) {
-        if let Some(owner) = owner {
-            self.owner = owner
+    pub fn update(&mut self, guardian: Option<Addr>) {
+        if let Some(guardian) = guardian {
+            self.guardian = Some(guardian)
+        }
+    }
+
+    pub fn pause(&mut self, operations: &[Operation]) {
+        for operation in operations {
+            if !self.paused.contains(operation) {
+                self.paused.push(operation.clone())
+            }
         }
     }
+
+    pub fn unpause(&mut self, operations: &[Operation]) {
+        self.paused.retain(|x| !operations.contains(x))
+    }
+
+    /// Errors if `operation` has been paused
+    pub fn ensure_active(&self, operation: Operation) -> Result<(), ContractError> {
+        ensure!(!self.paused.contains(&operation), ContractError::Paused {});
+        Ok(())
+    }
 }
 
 impl From<Config> for ConfigResponse {
@@ -66,6 +102,10 @@ impl From<Config> for ConfigResponse {
             rsv_denom: value.rsv_denom,
             ghost_denom: value.ghost_denom,
             ghost_vault_addr: value.ghost_vault_addr,
+            guardian: value.guardian,
+            paused: value.paused,
+            pending_owner: value.pending_owner,
+            timelock: value.timelock,
         }
     }
 }
@@ -3,9 +3,9 @@ use crate::state::{State, LEGACY_DENOMS};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coins, ensure, ensure_eq, to_json_binary, wasm_execute, Addr, BankMsg, Binary, CosmosMsg,
-    CustomQuery, Decimal, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order, QuerierWrapper,
-    Response, StdResult,
+    coins, ensure, ensure_eq, to_json_binary, to_json_string, wasm_execute, Addr, BankMsg, Binary,
+    CosmosMsg, CustomQuery, Decimal, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order,
+    QuerierWrapper, Response, StdResult,
 };
 use cw2::set_contract_version;
 use cw_storage_plus::Map;
@@ -18,10 +18,12 @@ use kujira_ghost::receipt_vault::{
 };
 use monetary::{must_pay, AmountU128, Exchange, Rate};
 use unstake::denoms::{Base, LegacyRsv, Rcpt, Rsv};
+use unstake::ownership::PendingOwner;
 use unstake::reserve::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StatusResponse, WhitelistItem,
-    WhitelistResponse,
+    Change, ConfigResponse, ExecuteMsg, InstantiateMsg, Operation, QueryMsg, StatusResponse,
+    WhitelistItem, WhitelistResponse,
 };
+use unstake::timelock::{self, PendingChangesResponse, Timelock};
 use unstake::ContractError;
 
 // version info for migration info
@@ -31,6 +33,7 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const URSV: &str = "ursv";
 pub const WHITELISTED_CONTROLLERS: Map<&Addr, (AmountU128<Base>, Option<AmountU128<Base>>)> =
     Map::new("whitelisted_controllers");
+const TIMELOCK: Timelock<Change> = Timelock::new();
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -41,6 +44,7 @@ pub fn instantiate(
 ) -> Result<Response<KujiraMsg>, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let config = Config::new(msg, &deps.querier, &env)?;
+    timelock::validate_delay(config.timelock)?;
     config.save(deps.storage)?;
 
     let state = State::default();
@@ -68,6 +72,7 @@ pub fn execute(
 
     match msg {
         ExecuteMsg::Fund { callback } => {
+            config.ensure_active(Operation::Fund)?;
             // Deposit to GHOST vault
             let base_amount = must_pay(&info, &config.base_denom)?;
             let ghost_deposit_msg = wasm_execute(
@@ -116,6 +121,7 @@ pub fn execute(
                 .add_event(event))
         }
         ExecuteMsg::Withdraw { callback } => {
+            config.ensure_active(Operation::Withdraw)?;
             let reserve_amount = must_pay(&info, &config.rsv_denom)?;
 
             // Ensure we have enough liquidity to withdraw
@@ -171,6 +177,7 @@ pub fn execute(
             requested_amount,
             callback,
         } => {
+            config.ensure_active(Operation::RequestReserves)?;
             let maybe_limit = WHITELISTED_CONTROLLERS.may_load(deps.storage, &info.sender)?;
             ensure!(maybe_limit.is_some(), ContractError::Unauthorized {});
             ensure!(!requested_amount.is_zero(), ContractError::RequestZero {});
@@ -272,18 +279,66 @@ pub fn execute(
                 .add_messages(return_msg)
                 .add_event(event))
         }
-        ExecuteMsg::AddController { controller, limit } => {
+        ExecuteMsg::ScheduleChange { change, effective } => {
             ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
-            WHITELISTED_CONTROLLERS.update(deps.storage, &controller, |c| {
-                StdResult::Ok(c.map_or((AmountU128::zero(), limit), |(lent, _)| (lent, limit)))
-            })?;
+            // Rejected changes are caught now, rather than once the timelock has elapsed
+            if let Change::Timelock { delay } = &change {
+                timelock::validate_delay(*delay)?;
+            }
+            let pending = TIMELOCK.schedule(
+                deps.storage,
+                change,
+                effective,
+                config.timelock,
+                env.block.time,
+            )?;
 
-            let event = Event::new("unstake/reserve/add_controller").add_attributes(vec![
-                ("controller", controller.to_string()),
-                ("limit", limit.map_or("null".to_string(), |l| l.to_string())),
+            let event = Event::new("unstake/reserve/schedule_change").add_attributes(vec![
+                ("id", pending.id.to_string()),
+                ("change", to_json_string(&pending.change)?),
+                ("effective", pending.effective.seconds().to_string()),
             ]);
             Ok(Response::default().add_event(event))
         }
+        ExecuteMsg::ExecuteChange { id } => {
+            let event = match TIMELOCK.take(deps.storage, id, env.block.time)? {
+                Change::Controller { controller, limit } => {
+                    WHITELISTED_CONTROLLERS.update(deps.storage, &controller, |c| {
+                        StdResult::Ok(
+                            c.map_or((AmountU128::zero(), limit), |(lent, _)| (lent, limit)),
+                        )
+                    })?;
+
+                    Event::new("unstake/reserve/add_controller").add_attributes(vec![
+                        ("controller", controller.to_string()),
+                        ("limit", limit.map_or("null".to_string(), |l| l.to_string())),
+                    ])
+                }
+                Change::Timelock { delay } => {
+                    let mut config = Config::load(deps.storage)?;
+                    TIMELOCK.update_delay(deps.storage, config.timelock, delay, env.block.time)?;
+                    config.timelock = delay;
+                    config.save(deps.storage)?;
+
+                    Event::new("unstake/reserve/update_timelock")
+                        .add_attributes(vec![("delay", delay.to_string())])
+                }
+            };
+
+            let execute_event = Event::new("unstake/reserve/execute_change")
+                .add_attributes(vec![("id", id.to_string())]);
+            Ok(Response::default()
+                .add_event(execute_event)
+                .add_event(event))
+        }
+        ExecuteMsg::CancelChange { id } => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            TIMELOCK.cancel(deps.storage, id)?;
+
+            let event = Event::new("unstake/reserve/cancel_change")
+                .add_attributes(vec![("id", id.to_string())]);
+            Ok(Response::default().add_event(event))
+        }
         ExecuteMsg::RemoveController { controller } => {
             ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
             WHITELISTED_CONTROLLERS.remove(deps.storage, &controller);
@@ -292,13 +347,91 @@ pub fn execute(
                 .add_attributes(vec![("controller", controller.to_string())]);
             Ok(Response::default().add_event(event))
         }
-        ExecuteMsg::UpdateConfig { owner } => {
+        ExecuteMsg::UpdateConfig { guardian } => {
             ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
             let mut config = Config::load(deps.storage)?;
-            config.update(owner);
+            config.update(guardian);
             config.save(deps.storage)?;
             Ok(Response::default())
         }
+        ExecuteMsg::ProposeOwner { owner, expiry } => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let mut config = Config::load(deps.storage)?;
+            config.pending_owner = Some(PendingOwner::new(owner.clone(), expiry, env.block.time)?);
+            config.save(deps.storage)?;
+
+            let event = Event::new("unstake/reserve/propose_owner").add_attributes(vec![
+                ("owner", owner.to_string()),
+                (
+                    "expiry",
+                    expiry.map_or("null".to_string(), |x| x.seconds().to_string()),
+                ),
+            ]);
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::AcceptOwner {} => {
+            let mut config = Config::load(deps.storage)?;
+            let previous = config.owner;
+            config.owner = config
+                .pending_owner
+                .take()
+                .ok_or(ContractError::NoPendingOwner {})?
+                .accept(&info.sender, env.block.time)?;
+            config.save(deps.storage)?;
+
+            let event = Event::new("unstake/reserve/accept_owner").add_attributes(vec![
+                ("previous_owner", previous.to_string()),
+                ("owner", config.owner.to_string()),
+            ]);
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::CancelOwner {} => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let mut config = Config::load(deps.storage)?;
+            let pending = config
+                .pending_owner
+                .take()
+                .ok_or(ContractError::NoPendingOwner {})?;
+            config.save(deps.storage)?;
+
+            let event = Event::new("unstake/reserve/cancel_owner")
+                .add_attributes(vec![("owner", pending.owner.to_string())]);
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::Pause { operations } => {
+            ensure!(
+                info.sender == config.owner || config.guardian.as_ref() == Some(&info.sender),
+                ContractError::Unauthorized {}
+            );
+            let mut config = Config::load(deps.storage)?;
+            config.pause(&operations);
+            config.save(deps.storage)?;
+
+            let event = Event::new("unstake/reserve/pause")
+                .add_attribute("operations", to_json_string(&operations)?)
+                .add_attribute("sender", info.sender);
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::Unpause { operations } => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let mut config = Config::load(deps.storage)?;
+            config.unpause(&operations);
+            config.save(deps.storage)?;
+
+            let event = Event::new("unstake/reserve/unpause")
+                .add_attribute("operations", to_json_string(&operations)?);
+            Ok(Response::default().add_event(event))
+        }
+        ExecuteMsg::RemoveGuardian {} => {
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            let mut config = Config::load(deps.storage)?;
+            let guardian = config.guardian.take().ok_or(ContractError::NoGuardian {})?;
+            config.save(deps.storage)?;
+
+            let event =
+                Event::new("unstake/reserve/remove_guardian").add_attribute("guardian", guardian);
+            Ok(Response::default().add_event(event))
+        }
         ExecuteMsg::MigrateLegacyReserve {
             reserves_deployed,
             legacy_denom,
@@ -419,6 +552,9 @@ pub fn query(deps: Deps<KujiraQuery>, _env: Env, msg: QueryMsg) -> Result<Binary
             })?)
         }
         QueryMsg::Config {} => Ok(to_json_binary(&ConfigResponse::from(config))?),
+        QueryMsg::PendingChanges {} => Ok(to_json_binary(&PendingChangesResponse {
+            changes: TIMELOCK.all(deps.storage)?,
+        })?),
     }
 }
 
@@ -1,4 +1,4 @@
-use cosmwasm_std::{coins, Addr, Coin, Decimal, Uint128};
+use cosmwasm_std::{coins, Addr, Coin, Decimal, Event, Uint128};
 use cw_multi_test::{ContractWrapper, Executor};
 use kujira::{Denom, HumanPrice};
 use kujira_ghost::common::OracleType;
@@ -7,7 +7,9 @@ use kujira_rs_testing::{
     mock::{mock_app, CustomApp},
 };
 use monetary::AmountU128;
-use unstake::reserve::{ExecuteMsg, InstantiateMsg, QueryMsg, StatusResponse};
+use unstake::ownership::PendingOwner;
+use unstake::reserve::{Change, ExecuteMsg, InstantiateMsg, Operation, QueryMsg, StatusResponse};
+use unstake::ContractError;
 
 use super::util::*;
 
@@ -68,6 +70,8 @@ fn setup(balances: Vec<(Addr, Vec<Coin>)>) -> (CustomApp, Contracts) {
                 owner: app.api().addr_make("owner"),
                 base_denom: monetary::Denom::new("base"),
                 ghost_vault_addr: vault_address.clone(),
+                guardian: Some(app.api().addr_make("guardian")),
+                timelock: Some(0),
             },
             &[],
             "reserve",
@@ -231,12 +235,242 @@ fn test_update_config() {
     let (mut app, contracts) = setup(balances);
 
     let owner = app.api().addr_make("owner");
-    let new_owner = app.api().addr_make("new_owner");
+    let guardian = app.api().addr_make("new_guardian");
 
-    update_config(&mut app, &contracts, &owner, new_owner.clone()).unwrap();
+    update_config(&mut app, &contracts, &owner, guardian.clone()).unwrap();
     let config = query_config(&app, &contracts);
 
+    assert_eq!(config.guardian, Some(guardian));
+}
+
+#[test]
+fn test_transfer_ownership() {
+    let (mut app, contracts) = setup(vec![]);
+
+    let owner = app.api().addr_make("owner");
+    let new_owner = app.api().addr_make("new_owner");
+    let expiry = app.block_info().time.plus_days(1);
+
+    let err = propose_owner(&mut app, &contracts, &new_owner, &new_owner, None).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    let err = accept_owner(&mut app, &contracts, &new_owner).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoPendingOwner {}
+    ));
+
+    // A cancelled proposal can't be accepted
+    propose_owner(&mut app, &contracts, &owner, &new_owner, None).unwrap();
+    let res = cancel_owner(&mut app, &contracts, &owner).unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/reserve/cancel_owner")
+            .add_attributes(vec![("owner", new_owner.to_string())]),
+    );
+    accept_owner(&mut app, &contracts, &new_owner).unwrap_err();
+
+    // Nor can an expired one
+    propose_owner(&mut app, &contracts, &owner, &new_owner, Some(expiry)).unwrap();
+    app.update_block(|x| x.time = x.time.plus_days(1));
+    let err = accept_owner(&mut app, &contracts, &new_owner).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OwnershipExpired {}
+    ));
+
+    let expiry = app.block_info().time.plus_days(1);
+    propose_owner(&mut app, &contracts, &owner, &new_owner, Some(expiry)).unwrap();
+    let config = query_config(&app, &contracts);
+    assert_eq!(config.owner, owner);
+    assert_eq!(
+        config.pending_owner,
+        Some(PendingOwner {
+            owner: new_owner.clone(),
+            expiry: Some(expiry),
+        })
+    );
+
+    let err = accept_owner(&mut app, &contracts, &owner).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    let res = accept_owner(&mut app, &contracts, &new_owner).unwrap();
+    res.assert_event(
+        &Event::new("wasm-unstake/reserve/accept_owner").add_attributes(vec![
+            ("previous_owner", owner.to_string()),
+            ("owner", new_owner.to_string()),
+        ]),
+    );
+
+    let config = query_config(&app, &contracts);
     assert_eq!(config.owner, new_owner);
+    assert_eq!(config.pending_owner, None);
+}
+
+#[test]
+fn test_timelocked_limits() {
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(1000000u128, "base")),
+        (api.addr_make("controller"), vec![]),
+    ];
+    let (mut app, contracts) = setup(balances);
+
+    let funder = app.api().addr_make("funder");
+    let controller = app.api().addr_make("controller");
+    let owner = app.api().addr_make("owner");
+    let keeper = app.api().addr_make("keeper");
+
+    fund(&mut app, &contracts, &funder, Uint128::new(1000)).unwrap();
+    add_controller(&mut app, &contracts, &owner, &controller, Uint128::new(100)).unwrap();
+
+    schedule_change(
+        &mut app,
+        &contracts,
+        &owner,
+        Change::Timelock { delay: 86400 },
+        None,
+    )
+    .unwrap();
+    let id = query_pending_changes(&app, &contracts).changes[0].id;
+    execute_change(&mut app, &contracts, &keeper, id).unwrap();
+    assert_eq!(query_config(&app, &contracts).timelock, 86400);
+
+    let change = Change::Controller {
+        controller: controller.clone(),
+        limit: Some(AmountU128::new(Uint128::new(500))),
+    };
+    let err = schedule_change(&mut app, &contracts, &controller, change.clone(), None).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    schedule_change(&mut app, &contracts, &owner, change.clone(), None).unwrap();
+    let pending = query_pending_changes(&app, &contracts);
+    assert_eq!(pending.changes.len(), 1);
+    assert_eq!(pending.changes[0].change, change);
+    let id = pending.changes[0].id;
+
+    // The raised limit isn't available until the delay has passed
+    let err = execute_change(&mut app, &contracts, &keeper, id).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockNotElapsed {}
+    ));
+    request_reserves(&mut app, &contracts, &controller, Uint128::new(500)).unwrap_err();
+
+    app.update_block(|x| x.time = x.time.plus_days(1));
+    execute_change(&mut app, &contracts, &keeper, id).unwrap();
+    request_reserves(&mut app, &contracts, &controller, Uint128::new(500)).unwrap();
+
+    let whitelist = query_whitelist(&app, &contracts);
+    assert_eq!(
+        whitelist.controllers[0].limit,
+        Some(AmountU128::new(Uint128::new(500)))
+    );
+    assert_eq!(query_pending_changes(&app, &contracts).changes, vec![]);
+}
+
+#[test]
+fn test_pause() {
+    let api = MockApiBech32::new("kujira");
+    let balances = vec![
+        (api.addr_make("funder"), coins(1000000u128, "base")),
+        (api.addr_make("controller"), vec![]),
+    ];
+    let (mut app, contracts) = setup(balances);
+
+    let funder = app.api().addr_make("funder");
+    let controller = app.api().addr_make("controller");
+    let owner = app.api().addr_make("owner");
+    let guardian = app.api().addr_make("guardian");
+
+    fund(&mut app, &contracts, &funder, Uint128::new(1000)).unwrap();
+    add_controller(
+        &mut app,
+        &contracts,
+        &owner,
+        &controller,
+        Uint128::new(1000),
+    )
+    .unwrap();
+    request_reserves(&mut app, &contracts, &controller, Uint128::new(500)).unwrap();
+
+    let err = pause(&mut app, &contracts, &funder, vec![Operation::Fund]).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    pause(
+        &mut app,
+        &contracts,
+        &guardian,
+        vec![Operation::Fund, Operation::RequestReserves],
+    )
+    .unwrap();
+    let config = query_config(&app, &contracts);
+    assert_eq!(
+        config.paused,
+        vec![Operation::Fund, Operation::RequestReserves]
+    );
+
+    let err = fund(&mut app, &contracts, &funder, Uint128::new(1000)).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Paused {}
+    ));
+    let err = request_reserves(&mut app, &contracts, &controller, Uint128::new(100)).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Paused {}
+    ));
+
+    // Withdrawals weren't paused, and reserves can always be returned
+    withdraw(&mut app, &contracts, &funder, Uint128::new(100)).unwrap();
+    return_reserves(
+        &mut app,
+        &contracts,
+        &controller,
+        Uint128::new(500),
+        Uint128::new(500),
+    )
+    .unwrap();
+
+    // Only the owner can resume
+    let err = unpause(&mut app, &contracts, &guardian, vec![Operation::Fund]).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    unpause(&mut app, &contracts, &owner, vec![Operation::Fund]).unwrap();
+    fund(&mut app, &contracts, &funder, Uint128::new(1000)).unwrap();
+
+    let config = query_config(&app, &contracts);
+    assert_eq!(config.paused, vec![Operation::RequestReserves]);
+
+    // The owner can revoke the guardian
+    let err = remove_guardian(&mut app, &contracts, &guardian).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    remove_guardian(&mut app, &contracts, &owner).unwrap();
+    let config = query_config(&app, &contracts);
+    assert_eq!(config.guardian, None);
+    let err = pause(&mut app, &contracts, &guardian, vec![Operation::Fund]).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    let err = remove_guardian(&mut app, &contracts, &owner).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoGuardian {}
+    ));
 }
 
 // Edge and error cases
@@ -485,14 +719,12 @@ fn test_basic_request_reserves() {
     )
     .unwrap();
 
-    app.execute_contract(
-        owner.clone(),
-        contracts.reserve.clone(),
-        &ExecuteMsg::AddController {
-            controller: controller.clone(),
-            limit: Some(AmountU128::new(1000u128.into())),
-        },
-        &[],
+    add_controller(
+        &mut app,
+        &contracts,
+        &owner,
+        &controller,
+        Uint128::new(1000),
     )
     .unwrap();
 
@@ -537,14 +769,12 @@ fn test_basic_return_reserves() {
     )
     .unwrap();
 
-    app.execute_contract(
-        owner.clone(),
-        contracts.reserve.clone(),
-        &ExecuteMsg::AddController {
-            controller: controller.clone(),
-            limit: Some(AmountU128::new(1000u128.into())),
-        },
-        &[],
+    add_controller(
+        &mut app,
+        &contracts,
+        &owner,
+        &controller,
+        Uint128::new(1000),
     )
     .unwrap();
 
@@ -630,14 +860,12 @@ fn test_return_reserves_with_different_amount() {
     )
     .unwrap();
 
-    app.execute_contract(
-        owner.clone(),
-        contracts.reserve.clone(),
-        &ExecuteMsg::AddController {
-            controller: controller.clone(),
-            limit: Some(AmountU128::new(1000u128.into())),
-        },
-        &[],
+    add_controller(
+        &mut app,
+        &contracts,
+        &owner,
+        &controller,
+        Uint128::new(1000),
     )
     .unwrap();
 
@@ -1,8 +1,11 @@
-use cosmwasm_std::{coins, Addr, Uint128};
+use cosmwasm_std::{coins, Addr, Timestamp, Uint128};
 use cw_multi_test::{AppResponse, Executor};
 use kujira_rs_testing::mock::CustomApp;
 use monetary::AmountU128;
-use unstake::reserve::{ConfigResponse, ExecuteMsg, QueryMsg, StatusResponse, WhitelistResponse};
+use unstake::reserve::{
+    Change, ConfigResponse, ExecuteMsg, Operation, QueryMsg, StatusResponse, WhitelistResponse,
+};
+use unstake::timelock::PendingChangesResponse;
 
 use super::tests::Contracts;
 
@@ -70,24 +73,57 @@ pub fn return_reserves(
     )
 }
 
-pub fn add_controller(
+pub fn schedule_change(
     app: &mut CustomApp,
     contracts: &Contracts,
-    owner: &Addr,
-    controller: &Addr,
-    limit: Uint128,
+    sender: &Addr,
+    change: Change,
+    effective: Option<Timestamp>,
 ) -> anyhow::Result<AppResponse> {
     app.execute_contract(
-        owner.clone(),
+        sender.clone(),
         contracts.reserve.clone(),
-        &ExecuteMsg::AddController {
-            controller: controller.clone(),
-            limit: Some(AmountU128::new(limit)),
-        },
+        &ExecuteMsg::ScheduleChange { change, effective },
+        &[],
+    )
+}
+
+pub fn execute_change(
+    app: &mut CustomApp,
+    contracts: &Contracts,
+    sender: &Addr,
+    id: u64,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        sender.clone(),
+        contracts.reserve.clone(),
+        &ExecuteMsg::ExecuteChange { id },
         &[],
     )
 }
 
+/// Schedules and executes a change to the controller's limit, which takes effect immediately
+/// without a timelock
+pub fn add_controller(
+    app: &mut CustomApp,
+    contracts: &Contracts,
+    owner: &Addr,
+    controller: &Addr,
+    limit: Uint128,
+) -> anyhow::Result<AppResponse> {
+    let change = Change::Controller {
+        controller: controller.clone(),
+        limit: Some(AmountU128::new(limit)),
+    };
+    schedule_change(app, contracts, owner, change, None)?;
+    let id = query_pending_changes(app, contracts)
+        .changes
+        .last()
+        .unwrap()
+        .id;
+    execute_change(app, contracts, owner, id)
+}
+
 pub fn remove_controller(
     app: &mut CustomApp,
     contracts: &Contracts,
@@ -108,18 +144,103 @@ pub fn update_config(
     app: &mut CustomApp,
     contracts: &Contracts,
     owner: &Addr,
-    new_owner: Addr,
+    guardian: Addr,
 ) -> anyhow::Result<AppResponse> {
     app.execute_contract(
         owner.clone(),
         contracts.reserve.clone(),
         &ExecuteMsg::UpdateConfig {
-            owner: Some(new_owner),
+            guardian: Some(guardian),
         },
         &[],
     )
 }
 
+pub fn remove_guardian(
+    app: &mut CustomApp,
+    contracts: &Contracts,
+    sender: &Addr,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        sender.clone(),
+        contracts.reserve.clone(),
+        &ExecuteMsg::RemoveGuardian {},
+        &[],
+    )
+}
+
+pub fn propose_owner(
+    app: &mut CustomApp,
+    contracts: &Contracts,
+    sender: &Addr,
+    owner: &Addr,
+    expiry: Option<Timestamp>,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        sender.clone(),
+        contracts.reserve.clone(),
+        &ExecuteMsg::ProposeOwner {
+            owner: owner.clone(),
+            expiry,
+        },
+        &[],
+    )
+}
+
+pub fn accept_owner(
+    app: &mut CustomApp,
+    contracts: &Contracts,
+    sender: &Addr,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        sender.clone(),
+        contracts.reserve.clone(),
+        &ExecuteMsg::AcceptOwner {},
+        &[],
+    )
+}
+
+pub fn cancel_owner(
+    app: &mut CustomApp,
+    contracts: &Contracts,
+    sender: &Addr,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        sender.clone(),
+        contracts.reserve.clone(),
+        &ExecuteMsg::CancelOwner {},
+        &[],
+    )
+}
+
+pub fn pause(
+    app: &mut CustomApp,
+    contracts: &Contracts,
+    sender: &Addr,
+    operations: Vec<Operation>,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        sender.clone(),
+        contracts.reserve.clone(),
+        &ExecuteMsg::Pause { operations },
+        &[],
+    )
+}
+
+pub fn unpause(
+    app: &mut CustomApp,
+    contracts: &Contracts,
+    sender: &Addr,
+    operations: Vec<Operation>,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        sender.clone(),
+        contracts.reserve.clone(),
+        &ExecuteMsg::Unpause { operations },
+        &[],
+    )
+}
+
 pub fn query_status(app: &CustomApp, contracts: &Contracts) -> StatusResponse {
     app.wrap()
         .query_wasm_smart(contracts.reserve.clone(), &QueryMsg::Status {})
@@ -137,3 +258,12 @@ pub fn query_config(app: &CustomApp, contracts: &Contracts) -> ConfigResponse {
         .query_wasm_smart(contracts.reserve.clone(), &QueryMsg::Config {})
         .unwrap()
 }
+
+pub fn query_pending_changes(
+    app: &CustomApp,
+    contracts: &Contracts,
+) -> PendingChangesResponse<Change> {
+    app.wrap()
+        .query_wasm_smart(contracts.reserve.clone(), &QueryMsg::PendingChanges {})
+        .unwrap()
+}
@@ -1,11 +1,13 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{
-    to_json_binary, Addr, Coin, CosmosMsg, CustomQuery, Decimal, QuerierWrapper, StdResult, WasmMsg,
+    to_json_binary, Addr, Coin, CosmosMsg, CustomQuery, Decimal, Env, QuerierWrapper, StdResult,
+    Timestamp, Uint128, WasmMsg,
 };
+use cw20::Cw20ExecuteMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use super::interface::Unstake;
+use super::interface::{UnbondStatus, Unstake};
 #[cw_serde]
 pub struct Eris(Addr);
 
@@ -16,20 +18,79 @@ impl Unstake for Eris {
         Ok(state.exchange_rate)
     }
 
-    fn unbond_start<T>(&self, funds: Coin) -> CosmosMsg<T> {
-        CosmosMsg::Wasm(WasmMsg::Execute {
+    fn unbond_start<T>(&self, _env: &Env, funds: Coin) -> StdResult<CosmosMsg<T>> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: self.0.to_string(),
-            msg: to_json_binary(&ExecuteMsg::QueueUnbond { receiver: None }).unwrap(),
+            msg: to_json_binary(&ExecuteMsg::QueueUnbond { receiver: None })?,
             funds: vec![funds],
-        })
+        }))
     }
 
-    fn unbond_end<T>(&self) -> CosmosMsg<T> {
-        CosmosMsg::Wasm(WasmMsg::Execute {
+    fn supports_cw20(&self) -> bool {
+        true
+    }
+
+    fn unbond_start_cw20<T>(
+        &self,
+        _env: &Env,
+        token: &Addr,
+        amount: Uint128,
+    ) -> StdResult<CosmosMsg<T>> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: self.0.to_string(),
+                amount,
+                msg: to_json_binary(&ReceiveMsg::QueueUnbond { receiver: None })?,
+            })?,
+            funds: vec![],
+        }))
+    }
+
+    fn unbond_end<T>(&self) -> StdResult<Option<CosmosMsg<T>>> {
+        Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: self.0.to_string(),
-            msg: to_json_binary(&ExecuteMsg::WithdrawUnbonded { receiver: None }).unwrap(),
+            msg: to_json_binary(&ExecuteMsg::WithdrawUnbonded { receiver: None })?,
             funds: vec![],
-        })
+        })))
+    }
+
+    fn unbond_status<T: CustomQuery>(
+        &self,
+        querier: QuerierWrapper<T>,
+        _env: &Env,
+        address: &Addr,
+    ) -> StdResult<Option<UnbondStatus>> {
+        let requests: Vec<UnbondRequestsByUserResponseItemDetails> = querier.query_wasm_smart(
+            self.0.to_string(),
+            &ContractQueryMsg::UnbondRequestsByUserDetails {
+                user: address.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )?;
+        let rate = self.redemption_rate(querier)?;
+
+        let requests = requests.into_iter().map(|x| {
+            (
+                x.shares.mul_floor(rate),
+                x.state == "COMPLETED",
+                x.batch
+                    .map(|b| Timestamp::from_seconds(b.est_unbond_end_time)),
+            )
+        });
+
+        Ok(Some(UnbondStatus::from_requests(requests)))
+    }
+
+    // Requests wait in the pending batch for up to an epoch before it is submitted
+    fn unbonding_duration<T: CustomQuery>(
+        &self,
+        querier: QuerierWrapper<T>,
+    ) -> StdResult<Option<u64>> {
+        let config: ContractConfigResponse =
+            querier.query_wasm_smart(self.0.to_string(), &ContractQueryMsg::Config {})?;
+        Ok(Some(config.unbond_period + config.epoch_period))
     }
 }
 
@@ -45,6 +106,18 @@ pub enum ContractQueryMsg {
     /// The contract's current state. Response: `StateResponse`
     #[returns(ContractStateResponse)]
     State {},
+
+    /// The contract's configuration. Response: `ContractConfigResponse`
+    #[returns(ContractConfigResponse)]
+    Config {},
+
+    /// The unbonding requests made by a user, with the batches they're in
+    #[returns(Vec<UnbondRequestsByUserResponseItemDetails>)]
+    UnbondRequestsByUserDetails {
+        user: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -53,8 +126,121 @@ pub struct ContractStateResponse {
     pub exchange_rate: Decimal,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractConfigResponse {
+    /// The interval in seconds at which unbond requests are submitted in a batch
+    pub epoch_period: u64,
+
+    /// The unbonding period of the chain, in seconds
+    pub unbond_period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondRequestsByUserResponseItemDetails {
+    /// The ID of the batch
+    pub id: u64,
+
+    /// The amount of the staked token requested for unbonding
+    pub shares: Uint128,
+
+    /// One of PENDING, UNBONDING or COMPLETED
+    pub state: String,
+
+    /// The submitted batch. `None` whilst the request is in the pending batch
+    pub batch: Option<Batch>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Batch {
+    /// Estimated time when this batch will finish unbonding
+    pub est_unbond_end_time: u64,
+}
+
 #[cw_serde]
 pub enum ExecuteMsg {
     WithdrawUnbonded { receiver: Option<String> },
     QueueUnbond { receiver: Option<String> },
 }
+
+/// The hook messages accepted with a CW20 LST
+#[cw_serde]
+pub enum ReceiveMsg {
+    QueueUnbond { receiver: Option<String> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, testing::mock_env, to_json_string, Binary, Empty};
+
+    fn eris() -> Eris {
+        Eris(Addr::unchecked("eris"))
+    }
+
+    #[test]
+    fn unbond_start() {
+        let msg: CosmosMsg<Empty> = eris()
+            .unbond_start(&mock_env(), coin(100, "ampkuji"))
+            .unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "eris".to_string(),
+                msg: Binary::from(r#"{"queue_unbond":{"receiver":null}}"#.as_bytes()),
+                funds: vec![coin(100, "ampkuji")],
+            })
+        );
+    }
+
+    #[test]
+    fn unbond_start_cw20() {
+        let msg: CosmosMsg<Empty> = eris()
+            .unbond_start_cw20(&mock_env(), &Addr::unchecked("ampluna"), 100u128.into())
+            .unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "ampluna".to_string(),
+                msg: Binary::from(
+                    r#"{"send":{"contract":"eris","amount":"100","msg":"eyJxdWV1ZV91bmJvbmQiOnsicmVjZWl2ZXIiOm51bGx9fQ=="}}"#
+                        .as_bytes()
+                ),
+                funds: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn unbond_end() {
+        let msg: Option<CosmosMsg<Empty>> = eris().unbond_end().unwrap();
+        assert_eq!(
+            msg,
+            Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "eris".to_string(),
+                msg: Binary::from(r#"{"withdraw_unbonded":{"receiver":null}}"#.as_bytes()),
+                funds: vec![],
+            }))
+        );
+    }
+
+    #[test]
+    fn queries() {
+        assert_eq!(
+            to_json_string(&ContractQueryMsg::State {}).unwrap(),
+            r#"{"state":{}}"#
+        );
+        assert_eq!(
+            to_json_string(&ContractQueryMsg::Config {}).unwrap(),
+            r#"{"config":{}}"#
+        );
+        assert_eq!(
+            to_json_string(&ContractQueryMsg::UnbondRequestsByUserDetails {
+                user: "delegate".to_string(),
+                start_after: None,
+                limit: None,
+            })
+            .unwrap(),
+            r#"{"unbond_requests_by_user_details":{"user":"delegate","start_after":null,"limit":null}}"#
+        );
+    }
+}
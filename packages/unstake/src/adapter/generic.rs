@@ -0,0 +1,333 @@
+use std::str::{from_utf8, FromStr};
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    from_json, to_json_vec, Addr, Binary, Coin, ContractResult, CosmosMsg, CustomQuery, Decimal,
+    Empty, Env, QuerierWrapper, QueryRequest, StdError, StdResult, SystemResult, WasmMsg,
+    WasmQuery,
+};
+use serde::de::IgnoredAny;
+
+use super::interface::{UnbondStatus, Unstake};
+
+/// Placeholder in `unbond_msg` replaced with the address of the unbonding contract
+pub const ADDRESS_PLACEHOLDER: &str = "$address";
+
+/// A provider integrated through configuration alone. Messages are sent as stored, and the
+/// redemption rate is read from the query response at `rate_path`
+#[cw_serde]
+pub struct Generic {
+    /// The contract that receives `unbond_msg`, along with the LST
+    pub unbond_contract: Addr,
+
+    /// The JSON message that starts an unbond. `$address` is replaced with the unbonding address
+    pub unbond_msg: String,
+
+    /// The contract that receives `withdraw_msg`
+    pub withdraw_contract: Addr,
+
+    /// The JSON message that withdraws the unbonded tokens
+    pub withdraw_msg: String,
+
+    /// The contract queried with `rate_query`
+    pub rate_contract: Addr,
+
+    /// The JSON query for the redemption rate
+    pub rate_query: String,
+
+    /// The keys leading to the redemption rate in the query response. Array elements are
+    /// selected by their index
+    pub rate_path: Vec<String>,
+}
+
+impl Generic {
+    /// Checks that the templates are valid JSON
+    pub fn validate(&self) -> StdResult<()> {
+        for template in [&self.unbond_msg, &self.withdraw_msg, &self.rate_query] {
+            from_json::<IgnoredAny>(template.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl Unstake for Generic {
+    fn redemption_rate<T: CustomQuery>(&self, querier: QuerierWrapper<T>) -> StdResult<Decimal> {
+        let request: QueryRequest<Empty> = WasmQuery::Smart {
+            contract_addr: self.rate_contract.to_string(),
+            msg: Binary::from(self.rate_query.as_bytes()),
+        }
+        .into();
+        let response = match querier.raw_query(&to_json_vec(&request)?) {
+            SystemResult::Ok(ContractResult::Ok(response)) => response,
+            SystemResult::Ok(ContractResult::Err(err)) => {
+                return Err(StdError::generic_err(format!(
+                    "Querier contract error: {err}"
+                )))
+            }
+            SystemResult::Err(err) => {
+                return Err(StdError::generic_err(format!(
+                    "Querier system error: {err}"
+                )))
+            }
+        };
+
+        let value = select(response.as_slice(), &self.rate_path)?;
+        match value.first() {
+            Some(b'"') => from_json(value),
+            _ => Decimal::from_str(from_utf8(value)?),
+        }
+    }
+
+    fn unbond_start<T>(&self, env: &Env, funds: Coin) -> StdResult<CosmosMsg<T>> {
+        let msg = self
+            .unbond_msg
+            .replace(ADDRESS_PLACEHOLDER, env.contract.address.as_str());
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: self.unbond_contract.to_string(),
+            msg: Binary::from(msg.into_bytes()),
+            funds: vec![funds],
+        }))
+    }
+
+    fn unbond_end<T>(&self) -> StdResult<Option<CosmosMsg<T>>> {
+        Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: self.withdraw_contract.to_string(),
+            msg: Binary::from(self.withdraw_msg.as_bytes()),
+            funds: vec![],
+        })))
+    }
+
+    // There's no template for the unbondings of an individual address
+    fn unbond_status<T: CustomQuery>(
+        &self,
+        _querier: QuerierWrapper<T>,
+        _env: &Env,
+        _address: &Addr,
+    ) -> StdResult<Option<UnbondStatus>> {
+        Ok(None)
+    }
+}
+
+/// Selects the raw JSON value at `path`. The JSON is scanned rather than parsed into a generic
+/// value, which would bring float operations into the contract
+fn select<'a>(json: &'a [u8], path: &[String]) -> StdResult<&'a [u8]> {
+    let not_found = || StdError::generic_err(format!("Rate path {} not found", path.join(".")));
+    let mut i = skip_whitespace(json, 0);
+    for key in path {
+        i = match json.get(i) {
+            Some(b'{') => {
+                let mut j = skip_whitespace(json, i + 1);
+                loop {
+                    if json.get(j) != Some(&b'"') {
+                        return Err(not_found());
+                    }
+                    let key_end = value_end(json, j)?;
+                    let member: String = from_json(&json[j..key_end])?;
+                    j = skip_whitespace(json, key_end);
+                    if json.get(j) != Some(&b':') {
+                        return Err(invalid());
+                    }
+                    j = skip_whitespace(json, j + 1);
+                    if &member == key {
+                        break j;
+                    }
+                    j = next_element(json, j)?.ok_or_else(not_found)?;
+                }
+            }
+            Some(b'[') => {
+                let index = usize::from_str(key).map_err(|_| not_found())?;
+                let mut j = skip_whitespace(json, i + 1);
+                for _ in 0..index {
+                    j = next_element(json, j)?.ok_or_else(not_found)?;
+                }
+                if json.get(j) == Some(&b']') {
+                    return Err(not_found());
+                }
+                j
+            }
+            _ => return Err(not_found()),
+        };
+    }
+
+    Ok(&json[i..value_end(json, i)?])
+}
+
+fn invalid() -> StdError {
+    StdError::generic_err("Invalid JSON")
+}
+
+fn skip_whitespace(json: &[u8], mut i: usize) -> usize {
+    while json.get(i).is_some_and(|x| x.is_ascii_whitespace()) {
+        i += 1;
+    }
+    i
+}
+
+/// Skips the value at `i` and its separator, returning the start of the next element of the
+/// enclosing object or array, or `None` at its end
+fn next_element(json: &[u8], i: usize) -> StdResult<Option<usize>> {
+    let i = skip_whitespace(json, value_end(json, i)?);
+    match json.get(i) {
+        Some(b',') => Ok(Some(skip_whitespace(json, i + 1))),
+        Some(b'}') | Some(b']') => Ok(None),
+        _ => Err(invalid()),
+    }
+}
+
+/// The index after the value starting at `i`
+fn value_end(json: &[u8], i: usize) -> StdResult<usize> {
+    match json.get(i) {
+        Some(b'"') => {
+            let mut j = i + 1;
+            loop {
+                match json.get(j) {
+                    Some(b'\\') => j += 2,
+                    Some(b'"') => return Ok(j + 1),
+                    Some(_) => j += 1,
+                    None => return Err(invalid()),
+                }
+            }
+        }
+        Some(b'{') | Some(b'[') => {
+            let mut depth = 0;
+            let mut j = i;
+            loop {
+                match json.get(j) {
+                    Some(b'"') => {
+                        j = value_end(json, j)?;
+                        continue;
+                    }
+                    Some(b'{') | Some(b'[') => depth += 1,
+                    Some(b'}') | Some(b']') => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Ok(j + 1);
+                        }
+                    }
+                    Some(_) => {}
+                    None => return Err(invalid()),
+                }
+                j += 1;
+            }
+        }
+        Some(_) => {
+            let mut j = i;
+            while json
+                .get(j)
+                .is_some_and(|x| !matches!(x, b',' | b'}' | b']') && !x.is_ascii_whitespace())
+            {
+                j += 1;
+            }
+            Ok(j)
+        }
+        None => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, testing::mock_env, Empty};
+
+    fn path(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn select_path() {
+        let json = br#" {"total": "12", "nested": {"rates": [ {"rate": "1.5"}, {"a": [1, "]"], "rate": 2.25 } ]}, "escaped\"key": true} "#;
+        assert_eq!(select(json, &path(&["total"])).unwrap(), br#""12""#);
+        assert_eq!(
+            select(json, &path(&["nested", "rates", "0", "rate"])).unwrap(),
+            br#""1.5""#
+        );
+        assert_eq!(
+            select(json, &path(&["nested", "rates", "1", "rate"])).unwrap(),
+            b"2.25"
+        );
+        assert_eq!(
+            select(json, &path(&["nested", "rates", "1", "a"])).unwrap(),
+            br#"[1, "]"]"#
+        );
+        assert_eq!(select(json, &path(&["escaped\"key"])).unwrap(), b"true");
+        assert_eq!(select(json, &path(&[])).unwrap(), &json[1..json.len() - 1]);
+
+        select(json, &path(&["missing"])).unwrap_err();
+        select(json, &path(&["nested", "rates", "2"])).unwrap_err();
+        select(json, &path(&["nested", "rates", "x"])).unwrap_err();
+        select(json, &path(&["total", "x"])).unwrap_err();
+        select(br#"{"total": "12""#, &path(&["other"])).unwrap_err();
+    }
+
+    #[test]
+    fn select_escapes() {
+        // Escaped quotes and backslashes don't end a string, in keys or values
+        let json = br#"{"a\"}": "x\"],{", "b": "\\", "c\\": "1.5"}"#;
+        assert_eq!(select(json, &path(&["a\"}"])).unwrap(), br#""x\"],{""#);
+        assert_eq!(select(json, &path(&["b"])).unwrap(), br#""\\""#);
+        assert_eq!(select(json, &path(&["c\\"])).unwrap(), br#""1.5""#);
+        select(br#"{"a": "\"}"#, &path(&["a"])).unwrap_err();
+    }
+
+    #[test]
+    fn select_nested_arrays() {
+        let json = br#"[[1, [2, 3]], [[4], "[5]"], []]"#;
+        assert_eq!(select(json, &path(&["0", "1", "0"])).unwrap(), b"2");
+        assert_eq!(select(json, &path(&["0", "1"])).unwrap(), b"[2, 3]");
+        assert_eq!(select(json, &path(&["1", "0", "0"])).unwrap(), b"4");
+        assert_eq!(select(json, &path(&["1", "1"])).unwrap(), br#""[5]""#);
+        select(json, &path(&["2", "0"])).unwrap_err();
+        select(json, &path(&["3"])).unwrap_err();
+    }
+
+    #[test]
+    fn select_whitespace() {
+        let json = b"\n{ \"rates\" :\n\t[ 1.1 ,\r\n 2.2\t] ,\"rate\"\n:\n\"3.3\"  }\n";
+        assert_eq!(select(json, &path(&["rates", "0"])).unwrap(), b"1.1");
+        assert_eq!(select(json, &path(&["rates", "1"])).unwrap(), b"2.2");
+        assert_eq!(select(json, &path(&["rate"])).unwrap(), br#""3.3""#);
+    }
+
+    #[test]
+    fn messages() {
+        let generic = Generic {
+            unbond_contract: Addr::unchecked("hub"),
+            unbond_msg: r#"{"unbond":{"receiver":"$address"}}"#.to_string(),
+            withdraw_contract: Addr::unchecked("claims"),
+            withdraw_msg: r#"{"withdraw":{}}"#.to_string(),
+            rate_contract: Addr::unchecked("hub"),
+            rate_query: r#"{"state":{}}"#.to_string(),
+            rate_path: path(&["exchange_rate"]),
+        };
+        generic.validate().unwrap();
+
+        let env = mock_env();
+        let msg: CosmosMsg<Empty> = generic.unbond_start(&env, coin(100, "lst")).unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "hub".to_string(),
+                msg: Binary::from(
+                    format!(r#"{{"unbond":{{"receiver":"{}"}}}}"#, env.contract.address).as_bytes()
+                ),
+                funds: vec![coin(100, "lst")],
+            })
+        );
+
+        let msg: Option<CosmosMsg<Empty>> = generic.unbond_end().unwrap();
+        assert_eq!(
+            msg,
+            Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "claims".to_string(),
+                msg: Binary::from(r#"{"withdraw":{}}"#.as_bytes()),
+                funds: vec![],
+            }))
+        );
+
+        let invalid = Generic {
+            withdraw_msg: r#"{"withdraw":"#.to_string(),
+            ..generic
+        };
+        invalid.validate().unwrap_err();
+    }
+}
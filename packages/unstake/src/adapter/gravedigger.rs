@@ -1,11 +1,12 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{
-    to_json_binary, Addr, Coin, CosmosMsg, CustomQuery, Decimal, QuerierWrapper, StdResult, WasmMsg,
+    to_json_binary, Addr, Coin, CosmosMsg, CustomQuery, Decimal, Env, QuerierWrapper, StdResult,
+    Timestamp, Uint128, WasmMsg,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use super::interface::Unstake;
+use super::interface::{UnbondStatus, Unstake};
 #[cw_serde]
 pub struct Gravedigger(Addr);
 
@@ -16,20 +17,80 @@ impl Unstake for Gravedigger {
         Ok(state.exchange_rate)
     }
 
-    fn unbond_start<T>(&self, funds: Coin) -> CosmosMsg<T> {
-        CosmosMsg::Wasm(WasmMsg::Execute {
+    fn unbond_start<T>(&self, _env: &Env, funds: Coin) -> StdResult<CosmosMsg<T>> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: self.0.to_string(),
-            msg: to_json_binary(&ExecuteMsg::Unbond { receiver: None }).unwrap(),
+            msg: to_json_binary(&ExecuteMsg::Unbond { receiver: None })?,
             funds: vec![funds],
-        })
+        }))
     }
 
-    fn unbond_end<T>(&self) -> CosmosMsg<T> {
-        CosmosMsg::Wasm(WasmMsg::Execute {
+    fn unbond_end<T>(&self) -> StdResult<Option<CosmosMsg<T>>> {
+        Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: self.0.to_string(),
-            msg: to_json_binary(&ExecuteMsg::WithdrawUnbonded { receiver: None }).unwrap(),
+            msg: to_json_binary(&ExecuteMsg::WithdrawUnbonded { receiver: None })?,
             funds: vec![],
-        })
+        })))
+    }
+
+    fn unbond_status<T: CustomQuery>(
+        &self,
+        querier: QuerierWrapper<T>,
+        env: &Env,
+        address: &Addr,
+    ) -> StdResult<Option<UnbondStatus>> {
+        let requests: Vec<UnbondRequestsByUserResponseItem> = querier.query_wasm_smart(
+            self.0.to_string(),
+            &ContractQueryMsg::UnbondRequestsByUser {
+                user: address.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )?;
+        let rate = self.redemption_rate(querier)?;
+
+        let mut tally = vec![];
+        for request in requests {
+            // The pending batch isn't returned as a previous batch until it is submitted
+            let batch = querier
+                .query_wasm_smart::<Batch>(
+                    self.0.to_string(),
+                    &ContractQueryMsg::PreviousBatch(request.id),
+                )
+                .ok();
+            tally.push(match batch {
+                Some(batch)
+                    if batch.reconciled
+                        && batch.est_unbond_end_time <= env.block.time.seconds() =>
+                {
+                    (
+                        batch
+                            .amount_unclaimed
+                            .multiply_ratio(request.shares, batch.total_shares),
+                        true,
+                        None,
+                    )
+                }
+                Some(batch) => (
+                    request.shares.mul_floor(rate),
+                    false,
+                    Some(Timestamp::from_seconds(batch.est_unbond_end_time)),
+                ),
+                None => (request.shares.mul_floor(rate), false, None),
+            });
+        }
+
+        Ok(Some(UnbondStatus::from_requests(tally)))
+    }
+
+    // Unbonds are queued until the epoch's batch is submitted, so can wait up to an epoch longer
+    fn unbonding_duration<T: CustomQuery>(
+        &self,
+        querier: QuerierWrapper<T>,
+    ) -> StdResult<Option<u64>> {
+        let config: ContractConfigResponse =
+            querier.query_wasm_smart(self.0.to_string(), &ContractQueryMsg::Config {})?;
+        Ok(Some(config.unbond_period + config.epoch_period))
     }
 }
 
@@ -45,6 +106,22 @@ pub enum ContractQueryMsg {
     /// The contract's current state. Response: `StateResponse`
     #[returns(ContractStateResponse)]
     State {},
+
+    /// The contract's configuration. Response: `ContractConfigResponse`
+    #[returns(ContractConfigResponse)]
+    Config {},
+
+    /// The unbond requests of a user. Response: `Vec<UnbondRequestsByUserResponseItem>`
+    #[returns(Vec<UnbondRequestsByUserResponseItem>)]
+    UnbondRequestsByUser {
+        user: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// A batch that has been submitted for unbonding. Response: `Batch`
+    #[returns(Batch)]
+    PreviousBatch(u64),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -53,8 +130,104 @@ pub struct ContractStateResponse {
     pub exchange_rate: Decimal,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractConfigResponse {
+    /// The interval in seconds at which unbond requests are submitted in a batch
+    pub epoch_period: u64,
+
+    /// The unbonding period of the chain, in seconds
+    pub unbond_period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondRequestsByUserResponseItem {
+    /// The ID of the batch
+    pub id: u64,
+
+    /// The amount of the staked token requested for unbonding
+    pub shares: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Batch {
+    /// Whether the amount unbonded has been reconciled with the amount received
+    pub reconciled: bool,
+
+    /// The total amount of the staked token in the batch
+    pub total_shares: Uint128,
+
+    /// The amount of the underlying token in the batch yet to be withdrawn
+    pub amount_unclaimed: Uint128,
+
+    /// The estimated time when the unbonding completes
+    pub est_unbond_end_time: u64,
+}
+
 #[cw_serde]
 pub enum ExecuteMsg {
     WithdrawUnbonded { receiver: Option<String> },
     Unbond { receiver: Option<String> },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, testing::mock_env, to_json_string, Binary, Empty};
+
+    fn gravedigger() -> Gravedigger {
+        Gravedigger(Addr::unchecked("gravedigger"))
+    }
+
+    #[test]
+    fn unbond_start() {
+        let msg: CosmosMsg<Empty> = gravedigger()
+            .unbond_start(&mock_env(), coin(100, "ampkuji"))
+            .unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "gravedigger".to_string(),
+                msg: Binary::from(r#"{"unbond":{"receiver":null}}"#.as_bytes()),
+                funds: vec![coin(100, "ampkuji")],
+            })
+        );
+    }
+
+    #[test]
+    fn unbond_end() {
+        let msg: Option<CosmosMsg<Empty>> = gravedigger().unbond_end().unwrap();
+        assert_eq!(
+            msg,
+            Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "gravedigger".to_string(),
+                msg: Binary::from(r#"{"withdraw_unbonded":{"receiver":null}}"#.as_bytes()),
+                funds: vec![],
+            }))
+        );
+    }
+
+    #[test]
+    fn queries() {
+        assert_eq!(
+            to_json_string(&ContractQueryMsg::State {}).unwrap(),
+            r#"{"state":{}}"#
+        );
+        assert_eq!(
+            to_json_string(&ContractQueryMsg::Config {}).unwrap(),
+            r#"{"config":{}}"#
+        );
+        assert_eq!(
+            to_json_string(&ContractQueryMsg::UnbondRequestsByUser {
+                user: "delegate".to_string(),
+                start_after: None,
+                limit: None,
+            })
+            .unwrap(),
+            r#"{"unbond_requests_by_user":{"user":"delegate","start_after":null,"limit":null}}"#
+        );
+        assert_eq!(
+            to_json_string(&ContractQueryMsg::PreviousBatch(5)).unwrap(),
+            r#"{"previous_batch":5}"#
+        );
+    }
+}
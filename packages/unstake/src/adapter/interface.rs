@@ -1,12 +1,91 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Coin, CosmosMsg, CustomQuery, Decimal, QuerierWrapper, StdResult};
+use cosmwasm_std::{
+    Addr, Coin, CosmosMsg, CustomQuery, Decimal, Env, QuerierWrapper, StdError, StdResult,
+    Timestamp, Uint128,
+};
 
-use super::{eris::Eris, gravedigger::Gravedigger, quark::Quark};
+use super::{eris::Eris, generic::Generic, gravedigger::Gravedigger, quark::Quark, stride::Stride};
 
 pub trait Unstake {
     fn redemption_rate<T: CustomQuery>(&self, querier: QuerierWrapper<T>) -> StdResult<Decimal>;
-    fn unbond_start<T>(&self, funds: Coin) -> CosmosMsg<T>;
-    fn unbond_end<T>(&self) -> CosmosMsg<T>;
+    fn unbond_start<T>(&self, env: &Env, funds: Coin) -> StdResult<CosmosMsg<T>>;
+
+    /// Starts the unbond of a CW20 LST, by sending `amount` of `token` to the provider's
+    /// receive hook
+    fn unbond_start_cw20<T>(
+        &self,
+        _env: &Env,
+        _token: &Addr,
+        _amount: Uint128,
+    ) -> StdResult<CosmosMsg<T>> {
+        Err(StdError::generic_err(
+            "CW20 unbonding is not supported by the provider",
+        ))
+    }
+
+    /// Whether the provider implements `unbond_start_cw20`
+    fn supports_cw20(&self) -> bool {
+        false
+    }
+
+    /// The message to claim the unbonded tokens. `None` where the provider returns them unasked
+    fn unbond_end<T>(&self) -> StdResult<Option<CosmosMsg<T>>>;
+
+    /// The progress of the unbondings started by `address`, as reported by the provider.
+    /// `None` if the provider doesn't report the progress of individual unbondings
+    fn unbond_status<T: CustomQuery>(
+        &self,
+        querier: QuerierWrapper<T>,
+        env: &Env,
+        address: &Addr,
+    ) -> StdResult<Option<UnbondStatus>>;
+
+    /// The longest time in seconds that an unbond started now can take, including any wait for
+    /// a batch to be submitted. `None` if the provider doesn't report its unbonding period
+    fn unbonding_duration<T: CustomQuery>(
+        &self,
+        _querier: QuerierWrapper<T>,
+    ) -> StdResult<Option<u64>> {
+        Ok(None)
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct UnbondStatus {
+    /// The amount of the underlying token still unbonding
+    pub pending: Uint128,
+
+    /// The amount of the underlying token that can be withdrawn
+    pub claimable: Uint128,
+
+    /// When the pending amount is expected to be claimable, if the provider is able to predict it
+    pub estimated_end: Option<Timestamp>,
+}
+
+impl UnbondStatus {
+    /// Tallies a set of unbond requests of `(amount, claimable, estimated_end)`
+    pub fn from_requests(
+        requests: impl IntoIterator<Item = (Uint128, bool, Option<Timestamp>)>,
+    ) -> Self {
+        let mut status = Self::default();
+        // Requests without an estimate haven't been submitted for unbonding yet, so the estimate
+        // is only known when all pending requests have one
+        let mut estimates = Some(vec![]);
+        for (amount, claimable, estimated_end) in requests {
+            if claimable {
+                status.claimable += amount;
+                continue;
+            }
+            status.pending += amount;
+            match (estimates.as_mut(), estimated_end) {
+                (Some(x), Some(end)) => x.push(end),
+                _ => estimates = None,
+            }
+        }
+        status.estimated_end = estimates.and_then(|x| x.into_iter().max());
+        status
+    }
 }
 
 impl Unstake for Adapter {
@@ -15,22 +94,81 @@ impl Unstake for Adapter {
             Adapter::Eris(eris) => eris.redemption_rate(querier),
             Adapter::Gravedigger(gravedigger) => gravedigger.redemption_rate(querier),
             Adapter::Quark(quark) => quark.redemption_rate(querier),
+            Adapter::Stride(stride) => stride.redemption_rate(querier),
+            Adapter::Generic(generic) => generic.redemption_rate(querier),
         }
     }
 
-    fn unbond_start<T>(&self, funds: Coin) -> CosmosMsg<T> {
+    fn unbond_start<T>(&self, env: &Env, funds: Coin) -> StdResult<CosmosMsg<T>> {
         match self {
-            Adapter::Eris(eris) => eris.unbond_start(funds),
-            Adapter::Gravedigger(gravedigger) => gravedigger.unbond_start(funds),
-            Adapter::Quark(quark) => quark.unbond_start(funds),
+            Adapter::Eris(eris) => eris.unbond_start(env, funds),
+            Adapter::Gravedigger(gravedigger) => gravedigger.unbond_start(env, funds),
+            Adapter::Quark(quark) => quark.unbond_start(env, funds),
+            Adapter::Stride(stride) => stride.unbond_start(env, funds),
+            Adapter::Generic(generic) => generic.unbond_start(env, funds),
         }
     }
 
-    fn unbond_end<T>(&self) -> CosmosMsg<T> {
+    fn unbond_start_cw20<T>(
+        &self,
+        env: &Env,
+        token: &Addr,
+        amount: Uint128,
+    ) -> StdResult<CosmosMsg<T>> {
+        match self {
+            Adapter::Eris(eris) => eris.unbond_start_cw20(env, token, amount),
+            Adapter::Gravedigger(gravedigger) => gravedigger.unbond_start_cw20(env, token, amount),
+            Adapter::Quark(quark) => quark.unbond_start_cw20(env, token, amount),
+            Adapter::Stride(stride) => stride.unbond_start_cw20(env, token, amount),
+            Adapter::Generic(generic) => generic.unbond_start_cw20(env, token, amount),
+        }
+    }
+
+    fn supports_cw20(&self) -> bool {
+        match self {
+            Adapter::Eris(eris) => eris.supports_cw20(),
+            Adapter::Gravedigger(gravedigger) => gravedigger.supports_cw20(),
+            Adapter::Quark(quark) => quark.supports_cw20(),
+            Adapter::Stride(stride) => stride.supports_cw20(),
+            Adapter::Generic(generic) => generic.supports_cw20(),
+        }
+    }
+
+    fn unbond_end<T>(&self) -> StdResult<Option<CosmosMsg<T>>> {
         match self {
             Adapter::Eris(eris) => eris.unbond_end(),
             Adapter::Gravedigger(gravedigger) => gravedigger.unbond_end(),
             Adapter::Quark(quark) => quark.unbond_end(),
+            Adapter::Stride(stride) => stride.unbond_end(),
+            Adapter::Generic(generic) => generic.unbond_end(),
+        }
+    }
+
+    fn unbond_status<T: CustomQuery>(
+        &self,
+        querier: QuerierWrapper<T>,
+        env: &Env,
+        address: &Addr,
+    ) -> StdResult<Option<UnbondStatus>> {
+        match self {
+            Adapter::Eris(eris) => eris.unbond_status(querier, env, address),
+            Adapter::Gravedigger(gravedigger) => gravedigger.unbond_status(querier, env, address),
+            Adapter::Quark(quark) => quark.unbond_status(querier, env, address),
+            Adapter::Stride(stride) => stride.unbond_status(querier, env, address),
+            Adapter::Generic(generic) => generic.unbond_status(querier, env, address),
+        }
+    }
+
+    fn unbonding_duration<T: CustomQuery>(
+        &self,
+        querier: QuerierWrapper<T>,
+    ) -> StdResult<Option<u64>> {
+        match self {
+            Adapter::Eris(eris) => eris.unbonding_duration(querier),
+            Adapter::Gravedigger(gravedigger) => gravedigger.unbonding_duration(querier),
+            Adapter::Quark(quark) => quark.unbonding_duration(querier),
+            Adapter::Stride(stride) => stride.unbonding_duration(querier),
+            Adapter::Generic(generic) => generic.unbonding_duration(querier),
         }
     }
 }
@@ -40,4 +178,18 @@ pub enum Adapter {
     Eris(Eris),
     Gravedigger(Gravedigger),
     Quark(Quark),
+    Stride(Stride),
+    Generic(Generic),
+}
+
+impl Adapter {
+    /// Checks that the adapter is able to read the provider's redemption rate, and that the
+    /// templates of a Generic adapter are well formed
+    pub fn validate<T: CustomQuery>(&self, querier: QuerierWrapper<T>) -> StdResult<()> {
+        if let Adapter::Generic(generic) = self {
+            generic.validate()?;
+        }
+        self.redemption_rate(querier)?;
+        Ok(())
+    }
 }
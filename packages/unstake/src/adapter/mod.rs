@@ -1,6 +1,8 @@
 pub mod eris;
+pub mod generic;
 pub mod gravedigger;
 pub mod interface;
 pub mod quark;
+pub mod stride;
 
 pub use crate::adapter::interface::*;
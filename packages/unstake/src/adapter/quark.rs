@@ -1,9 +1,9 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    wasm_execute, Addr, Coin, CosmosMsg, CustomQuery, Decimal, QuerierWrapper, StdResult,
+    wasm_execute, Addr, Coin, CosmosMsg, CustomQuery, Decimal, Env, QuerierWrapper, StdResult,
 };
 
-use super::interface::Unstake;
+use super::interface::{UnbondStatus, Unstake};
 #[cw_serde]
 pub struct Quark {
     liq: Addr,
@@ -23,30 +23,56 @@ impl Unstake for Quark {
         Ok(state.rate)
     }
 
-    fn unbond_start<T>(&self, funds: Coin) -> CosmosMsg<T> {
-        wasm_execute(
+    fn unbond_start<T>(&self, _env: &Env, funds: Coin) -> StdResult<CosmosMsg<T>> {
+        Ok(wasm_execute(
             &self.liq,
             &liquifier::ExecuteMsg::Unwrap(liquifier::UnwrapMsg {
                 target: Some(liquifier::UnwrapTarget::Underlying),
                 callback: None,
             }),
             vec![funds],
-        )
-        .unwrap()
-        .into()
+        )?
+        .into())
     }
 
-    fn unbond_end<T>(&self) -> CosmosMsg<T> {
-        wasm_execute(
+    fn unbond_end<T>(&self) -> StdResult<Option<CosmosMsg<T>>> {
+        Ok(Some(
+            wasm_execute(
+                &self.hub,
+                &hub::ExecuteMsg::Withdraw(hub::WithdrawMsg {
+                    recipient: None,
+                    callback: None,
+                }),
+                vec![],
+            )?
+            .into(),
+        ))
+    }
+
+    // The liquifier starts the unstake on the hub on behalf of the address that unwrapped, so the
+    // hub reports it against that address
+    fn unbond_status<T: CustomQuery>(
+        &self,
+        querier: QuerierWrapper<T>,
+        env: &Env,
+        address: &Addr,
+    ) -> StdResult<Option<UnbondStatus>> {
+        let res: hub::UnstakesResponse = querier.query_wasm_smart(
             &self.hub,
-            &hub::ExecuteMsg::Withdraw(hub::WithdrawMsg {
-                recipient: None,
-                callback: None,
-            }),
-            vec![],
-        )
-        .unwrap()
-        .into()
+            &hub::QueryMsg::Unstakes {
+                address: address.clone(),
+            },
+        )?;
+
+        let requests = res.unstakes.into_iter().map(|x| {
+            (
+                x.amount,
+                x.end.is_some_and(|end| end <= env.block.time),
+                x.end,
+            )
+        });
+
+        Ok(Some(UnbondStatus::from_requests(requests)))
     }
 }
 
@@ -88,10 +114,31 @@ mod liquifier {
 }
 
 mod hub {
-    use cosmwasm_schema::cw_serde;
-    use cosmwasm_std::Addr;
+    use cosmwasm_schema::{cw_serde, QueryResponses};
+    use cosmwasm_std::{Addr, Timestamp, Uint128};
     use kujira::CallbackData;
 
+    #[cw_serde]
+    #[derive(QueryResponses)]
+    pub enum QueryMsg {
+        /// The unstakes in progress, or left to withdraw, for an address
+        #[returns(UnstakesResponse)]
+        Unstakes { address: Addr },
+    }
+
+    #[cw_serde]
+    pub struct UnstakesResponse {
+        pub unstakes: Vec<UnstakeResponse>,
+    }
+
+    #[cw_serde]
+    pub struct UnstakeResponse {
+        /// The amount of the underlying token being unstaked
+        pub amount: Uint128,
+        /// When the unstake completes. `None` until its batch has been submitted
+        pub end: Option<Timestamp>,
+    }
+
     #[cw_serde]
     pub enum ExecuteMsg {
         /// Withdraw unbonded tokens after the unbond period.
@@ -104,3 +151,58 @@ mod hub {
         pub callback: Option<CallbackData>,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, testing::mock_env, to_json_string, Binary, Empty, WasmMsg};
+
+    fn quark() -> Quark {
+        Quark::new(Addr::unchecked("liquifier"), Addr::unchecked("hub"))
+    }
+
+    #[test]
+    fn unbond_start() {
+        let msg: CosmosMsg<Empty> = quark()
+            .unbond_start(&mock_env(), coin(100, "qckuji"))
+            .unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquifier".to_string(),
+                msg: Binary::from(
+                    r#"{"unwrap":{"target":"underlying","callback":null}}"#.as_bytes()
+                ),
+                funds: vec![coin(100, "qckuji")],
+            })
+        );
+    }
+
+    #[test]
+    fn unbond_end() {
+        let msg: Option<CosmosMsg<Empty>> = quark().unbond_end().unwrap();
+        assert_eq!(
+            msg,
+            Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "hub".to_string(),
+                msg: Binary::from(r#"{"withdraw":{"recipient":null,"callback":null}}"#.as_bytes()),
+                funds: vec![],
+            }))
+        );
+    }
+
+    #[test]
+    fn queries() {
+        assert_eq!(
+            to_json_string(&liquifier::QueryMsg::State {}).unwrap(),
+            r#"{"state":{}}"#
+        );
+        assert_eq!(
+            to_json_string(&hub::QueryMsg::Unstakes {
+                address: Addr::unchecked("delegate")
+            })
+            .unwrap(),
+            r#"{"unstakes":{"address":"delegate"}}"#
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{
+    to_json_string, Addr, Binary, Coin, CosmosMsg, CustomQuery, Decimal, Env, IbcMsg, IbcTimeout,
+    QuerierWrapper, StdResult,
+};
+
+use super::interface::{UnbondStatus, Unstake};
+
+/// A liquid staking provider on a remote chain, redeemed by sending the LST over IBC to an
+/// Autopilot-style receiver. The redeemed tokens are transferred back to the unbonding address
+/// by the provider, so there is nothing to claim on this chain
+#[cw_serde]
+pub struct Stride {
+    /// The contract publishing the provider's redemption rate on this chain
+    pub oracle: Addr,
+
+    /// The LST denom as known to the oracle, eg `stuatom`
+    pub oracle_denom: String,
+
+    /// The channel to the provider's chain
+    pub channel: String,
+
+    /// The address on the provider's chain that receives the LST and starts the redemption
+    pub receiver: String,
+
+    /// The number of seconds after which the IBC transfer times out
+    pub timeout: u64,
+}
+
+impl Unstake for Stride {
+    fn redemption_rate<T: CustomQuery>(&self, querier: QuerierWrapper<T>) -> StdResult<Decimal> {
+        let response: RedemptionRateResponse = querier.query_wasm_smart(
+            self.oracle.to_string(),
+            &OracleQueryMsg::RedemptionRate {
+                denom: self.oracle_denom.clone(),
+                params: None,
+            },
+        )?;
+        Ok(response.redemption_rate)
+    }
+
+    fn unbond_start<T>(&self, env: &Env, funds: Coin) -> StdResult<CosmosMsg<T>> {
+        let address = env.contract.address.to_string();
+        let memo = Memo {
+            autopilot: Autopilot {
+                receiver: self.receiver.clone(),
+                stakeibc: StakeIbc {
+                    action: "RedeemStake".to_string(),
+                    ibc_receiver: address.clone(),
+                },
+            },
+            ibc_callback: address,
+        };
+
+        Ok(CosmosMsg::Ibc(IbcMsg::Transfer {
+            channel_id: self.channel.clone(),
+            to_address: self.receiver.clone(),
+            amount: funds,
+            timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(self.timeout)),
+            memo: Some(to_json_string(&memo)?),
+        }))
+    }
+
+    fn unbond_end<T>(&self) -> StdResult<Option<CosmosMsg<T>>> {
+        Ok(None)
+    }
+
+    // Redemptions are tracked on the provider's chain
+    fn unbond_status<T: CustomQuery>(
+        &self,
+        _querier: QuerierWrapper<T>,
+        _env: &Env,
+        _address: &Addr,
+    ) -> StdResult<Option<UnbondStatus>> {
+        Ok(None)
+    }
+}
+
+/// The memo attached to the IBC transfer. `autopilot` instructs the provider to redeem the LST,
+/// and `ibc_callback` requests the ack or timeout of the transfer be sent to the unbonding address
+#[cw_serde]
+pub struct Memo {
+    pub autopilot: Autopilot,
+    pub ibc_callback: String,
+}
+
+#[cw_serde]
+pub struct Autopilot {
+    pub receiver: String,
+    pub stakeibc: StakeIbc,
+}
+
+#[cw_serde]
+pub struct StakeIbc {
+    pub action: String,
+    pub ibc_receiver: String,
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum OracleQueryMsg {
+    /// The latest redemption rate of an LST. Response: `RedemptionRateResponse`
+    #[returns(RedemptionRateResponse)]
+    RedemptionRate {
+        denom: String,
+        params: Option<Binary>,
+    },
+}
+
+#[cw_serde]
+pub struct RedemptionRateResponse {
+    pub redemption_rate: Decimal,
+    pub update_time: u64,
+}
+
+/// The sudo message sent by the IBC hooks middleware once an IBC transfer with an `ibc_callback`
+/// has been acknowledged or has timed out
+#[cw_serde]
+pub enum IbcLifecycleComplete {
+    IbcAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    IbcTimeout {
+        channel: String,
+        sequence: u64,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, testing::mock_env, Empty, Timestamp};
+
+    fn stride() -> Stride {
+        Stride {
+            oracle: Addr::unchecked("oracle"),
+            oracle_denom: "stuatom".to_string(),
+            channel: "channel-0".to_string(),
+            receiver: "stride1receiver".to_string(),
+            timeout: 600,
+        }
+    }
+
+    #[test]
+    fn unbond_start() {
+        let env = mock_env();
+        let msg: CosmosMsg<Empty> = stride()
+            .unbond_start(&env, coin(100, "ibc/STATOM"))
+            .unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Ibc(IbcMsg::Transfer {
+                channel_id: "channel-0".to_string(),
+                to_address: "stride1receiver".to_string(),
+                amount: coin(100, "ibc/STATOM"),
+                timeout: IbcTimeout::with_timestamp(Timestamp::from_nanos(
+                    env.block.time.nanos() + 600_000_000_000
+                )),
+                memo: Some(format!(
+                    r#"{{"autopilot":{{"receiver":"stride1receiver","stakeibc":{{"action":"RedeemStake","ibc_receiver":"{0}"}}}},"ibc_callback":"{0}"}}"#,
+                    env.contract.address
+                )),
+            })
+        );
+    }
+
+    #[test]
+    fn unbond_end() {
+        assert_eq!(stride().unbond_end::<Empty>().unwrap(), None);
+    }
+
+    #[test]
+    fn queries() {
+        assert_eq!(
+            to_json_string(&OracleQueryMsg::RedemptionRate {
+                denom: "stuatom".to_string(),
+                params: None,
+            })
+            .unwrap(),
+            r#"{"redemption_rate":{"denom":"stuatom","params":null}}"#
+        );
+    }
+
+    #[test]
+    fn lifecycle() {
+        let msg: IbcLifecycleComplete = cosmwasm_std::from_json(
+            r#"{"ibc_ack":{"channel":"channel-0","sequence":1,"ack":"","success":false}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            msg,
+            IbcLifecycleComplete::IbcAck {
+                channel: "channel-0".to_string(),
+                sequence: 1,
+                ack: "".to_string(),
+                success: false
+            }
+        );
+    }
+}
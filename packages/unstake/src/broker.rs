@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{coin, Coin, CustomQuery, Decimal, DepsMut, StdResult, Storage};
+use cosmwasm_std::{coin, ensure, Coin, Decimal, StdResult, Storage, Timestamp};
 use cw_storage_plus::Item;
 use cw_utils::NativeBalance;
 use monetary::{AmountU128, CheckedCoin, Exchange};
@@ -11,18 +11,31 @@ use std::{
 use crate::{
     controller::InstantiateMsg,
     denoms::{Ask, Base, Debt},
-    rates::Rates,
+    pricing::{PricingContext, PricingModel, RateAverage},
+    rates::{Rates, VaultRates},
     reserve::StatusResponse as ReserveStatus,
     ContractError,
 };
 
 const BROKER: Item<Broker> = Item::new("broker");
 
-// The total amount of (base, quote) tokens that have been (initiated, returned) from unbonding
+// The total amount of (base, quote) tokens that had been (initiated, returned) from unbonding,
+// before the totals were tallied by route
 const TOTALS: Item<(AmountU128<Ask>, AmountU128<Base>)> = Item::new("totals");
 
 const YEAR_SECONDS: u128 = 365 * 24 * 60 * 60;
 
+// The number of times the upper bound is doubled when solving for an output amount,
+// before concluding that the amount can't be reached
+const MAX_OUTPUT_DOUBLINGS: u32 = 16;
+
+/// The highest `min_rate` that can be configured
+pub const MAX_MIN_RATE: Decimal = Decimal::percent(300);
+
+/// The bounds in seconds on an unbonding duration that offers are priced over
+pub const MIN_DURATION: u64 = 24 * 60 * 60;
+pub const MAX_DURATION: u64 = 90 * 24 * 60 * 60;
+
 /// The Broker is responsible for managing protocol reserves, and making Unstaking offers
 #[cw_serde]
 pub struct Broker {
@@ -33,6 +46,10 @@ pub struct Broker {
 
     /// The length of time in seconds that an unbonding request must wait
     pub duration: u64,
+
+    /// The model used to price the interest charged on an Unstake
+    #[serde(default)]
+    pub pricing: PricingModel,
 }
 
 impl From<InstantiateMsg> for Broker {
@@ -40,15 +57,12 @@ impl From<InstantiateMsg> for Broker {
         Self {
             min_rate: value.min_rate,
             duration: value.unbonding_duration,
+            pricing: value.pricing.unwrap_or_default(),
         }
     }
 }
 
 impl Broker {
-    pub fn init(&self, storage: &mut dyn Storage) -> StdResult<()> {
-        TOTALS.save(storage, &(AmountU128::zero(), AmountU128::zero()))
-    }
-
     pub fn save(&self, storage: &mut dyn Storage) -> StdResult<()> {
         BROKER.save(storage, self)
     }
@@ -57,7 +71,12 @@ impl Broker {
         BROKER.load(storage)
     }
 
-    pub fn update(&mut self, min_rate: Option<Decimal>, duration: Option<u64>) {
+    pub fn update(
+        &mut self,
+        min_rate: Option<Decimal>,
+        duration: Option<u64>,
+        pricing: Option<PricingModel>,
+    ) {
         if let Some(min_rate) = min_rate {
             self.min_rate = min_rate
         }
@@ -65,6 +84,49 @@ impl Broker {
         if let Some(duration) = duration {
             self.duration = duration
         }
+
+        if let Some(pricing) = pricing {
+            self.pricing = pricing
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ContractError> {
+        ensure!(
+            self.min_rate <= MAX_MIN_RATE,
+            ContractError::InvalidMinRate { max: MAX_MIN_RATE }
+        );
+        self.pricing.validate()?;
+        validate_duration(self.duration)
+    }
+
+    /// The time-weighted average GHOST borrow rate at `now`. This is only tracked whilst the
+    /// TimeWeighted pricing model is in use, otherwise the current rate is returned
+    pub fn average_interest(
+        &self,
+        storage: &dyn Storage,
+        now: Timestamp,
+        current: Decimal,
+    ) -> StdResult<Decimal> {
+        let PricingModel::TimeWeighted { window } = self.pricing else {
+            return Ok(current);
+        };
+        Ok(RateAverage::load(storage)?.map_or(current, |x| x.average_at(now, window)))
+    }
+
+    /// Records an observation of the current GHOST borrow rate
+    pub fn record_interest(
+        &self,
+        storage: &mut dyn Storage,
+        now: Timestamp,
+        current: Decimal,
+    ) -> StdResult<()> {
+        let average = self.average_interest(storage, now, current)?;
+        RateAverage {
+            time: now,
+            rate: current,
+            average,
+        }
+        .save(storage)
     }
 
     /// Make an offer for a givan `amount` of the staked token
@@ -72,27 +134,45 @@ impl Broker {
         &self,
         reserve_status: &ReserveStatus,
         rates: &Rates,
+        vault_interest_average: Decimal,
         unbond_amount: AmountU128<Ask>,
     ) -> Result<Offer, ContractError> {
-        let current_rate = rates.vault_interest;
+        self.quote(reserve_status, rates, vault_interest_average, unbond_amount)
+            .map(|x| x.offer)
+    }
+
+    /// Make an offer for a given `amount` of the staked token, along with the terms it was priced on
+    pub fn quote(
+        &self,
+        reserve_status: &ReserveStatus,
+        rates: &Rates,
+        vault_interest_average: Decimal,
+        unbond_amount: AmountU128<Ask>,
+    ) -> Result<Quote, ContractError> {
         let max_rate = rates.vault_max_interest;
 
         // Calculate the value of the Unstaked amount, in terms of the underlying asset. I.e. the max amount we'll need to borrow
         let value = unbond_amount.mul_floor(&rates.provider_redemption);
+        let reserve_available = reserve_status.available.mul_floor(&rates.vault_deposit);
 
-        // For now we'll naively assume that the borrow rate will stay fixed for the duration of the unbond.
-        // During periods of high interest, Unstakes will cost more and a user will have to wait for the rate
-        // to fall if they want a more favourable rate.
-        let offer_rate = max(current_rate, self.min_rate);
-        let max_rate_shortfall = max_rate
-            .checked_sub(offer_rate)
-            .map_err(ContractError::RateOverflow)?;
+        // For now we'll naively assume that the rate chosen by the pricing model will stay fixed for the duration
+        // of the unbond. During periods of high interest, Unstakes will cost more and a user will have to wait
+        // for the rate to fall if they want a more favourable rate.
+        let model_rate = self.pricing.rate(&PricingContext {
+            vault_interest: rates.vault_interest,
+            vault_utilization: rates.vault_utilization,
+            vault_interest_average,
+            value,
+            reserve_available,
+        })?;
+        let offer_rate = max(model_rate, self.min_rate);
+        // A model may price above the vault's max rate, in which case there's no shortfall to cover
+        let max_rate_shortfall = max_rate.saturating_sub(offer_rate);
 
         // Ensure we have enough reserves available to cover the max potential shortfall - ie the lend APR spiking to max
         // in the following block, and remaining there for the whole period
         // This is something that we can look to relax in due course, but for now it provides an absolute guarantee of solvency
         let reserve_requirement = self.interest_amount(value, max_rate_shortfall);
-        let reserve_available = reserve_status.available.mul_floor(&rates.vault_deposit);
 
         // Calculate the total that we'll charge in up-front interest
         let fee = self.interest_amount(value, offer_rate);
@@ -103,14 +183,16 @@ impl Broker {
             // calculated the total interest amount on. The larger the current_rate, the larger the fee, the less we're actually
             // borrowing, so the actual amount of interest paid will be lower.
             // Therefore when the unbonded tokens return, we will have a surplus after the debt has been repaid.
+            // A rate high enough to charge more than the value can't be offered
+            let offer_amount = value.uint128().checked_sub(fee.uint128())?;
             let offer = Offer {
                 unbond_amount,
-                offer_amount: value.sub(fee),
+                offer_amount: AmountU128::new(offer_amount),
                 reserve_allocation: reserve_requirement,
                 fee,
             };
 
-            return Ok(offer);
+            return Ok(self.terms(offer, offer_rate, false));
         }
 
         // We can't offer at the current rate, calculate the best rate we can offer given the reserves available
@@ -118,36 +200,92 @@ impl Broker {
         let reserve_allocation = reserve_available;
         // Allocate the shortfall to fee, deduct from the amount returned
         let reserve_shortfall = reserve_requirement.sub(reserve_available);
+        // A long enough unbonding at a high enough max rate can leave nothing to offer
+        let offer_amount = value
+            .uint128()
+            .checked_sub(fee.uint128())?
+            .checked_sub(reserve_shortfall.uint128())?;
         let offer = Offer {
             unbond_amount,
-            offer_amount: value.sub(fee).sub(reserve_shortfall),
+            offer_amount: AmountU128::new(offer_amount),
             reserve_allocation,
             fee: fee.add(reserve_shortfall),
         };
 
-        Ok(offer)
+        Ok(self.terms(offer, offer_rate, true))
+    }
+
+    fn terms(&self, offer: Offer, rate: Decimal, clamped: bool) -> Quote {
+        // The fee as a share of the amount received, annualised over the unbonding period
+        let effective_rate =
+            Decimal::checked_from_ratio(offer.fee.uint128(), offer.offer_amount.uint128())
+                .ok()
+                .zip(Decimal::checked_from_ratio(YEAR_SECONDS, self.duration).ok())
+                .and_then(|(share, periods)| share.checked_mul(periods).ok())
+                .unwrap_or_default();
+
+        Quote {
+            offer,
+            rate,
+            clamped,
+            effective_rate,
+        }
     }
 
-    /// Tallies an accepted offer and updates the running totals.
-    pub fn accept_offer(
+    /// Find the smallest amount of the staked token that must be unstaked to receive at least
+    /// `amount` of the underlying token. The offer amount is monotonic in the unbond amount across
+    /// both the unclamped and reserve-clamped branches of `offer`, so we can bisect over it
+    pub fn offer_for_output(
         &self,
-        store: &mut dyn Storage,
-        offer: &Offer,
-    ) -> Result<(), ContractError> {
-        let (mut total_base, total_quote) = TOTALS.may_load(store)?.unwrap_or_default();
-        total_base += offer.unbond_amount;
-        TOTALS.save(store, &(total_base, total_quote))?;
+        reserve_status: &ReserveStatus,
+        rates: &Rates,
+        vault_interest_average: Decimal,
+        amount: AmountU128<Base>,
+    ) -> Result<Offer, ContractError> {
+        let offer = |x: u128| {
+            self.offer(
+                reserve_status,
+                rates,
+                vault_interest_average,
+                AmountU128::new(x.into()),
+            )
+        };
+
+        // Find an upper bound, starting from a 1:1 redemption rate
+        let mut low = 0;
+        let mut high = max(amount.u128(), 1);
+        let mut bound = offer(high)?;
+        let mut attempts = 0;
+        while bound.offer_amount.lt(&amount) {
+            attempts += 1;
+            low = high + 1;
+            high = high
+                .checked_mul(2)
+                .filter(|_| attempts <= MAX_OUTPUT_DOUBLINGS)
+                .ok_or(ContractError::OutputUnreachable {})?;
+            bound = offer(high)?;
+        }
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let candidate = offer(mid)?;
+            if candidate.offer_amount.lt(&amount) {
+                low = mid + 1;
+            } else {
+                high = mid;
+                bound = candidate;
+            }
+        }
 
-        Ok(())
+        Ok(bound)
     }
 
     /// Receives the original offer, debt tokens, and returned unbonded tokens from the delegate,
     /// reconciles the reserves
     #[allow(clippy::type_complexity)]
-    pub fn close_offer<T: CustomQuery>(
+    pub fn close_offer(
         &self,
-        deps: DepsMut<T>,
-        rates: &Rates,
+        rates: &VaultRates,
         offer: &Offer,
         debt_coin: CheckedCoin<Debt>,
         base_coin: CheckedCoin<Base>,
@@ -161,10 +299,6 @@ impl Broker {
             amount: mut returned_tokens,
         } = base_coin;
 
-        let (total_base, mut total_quote) = TOTALS.may_load(deps.storage)?.unwrap_or_default();
-        total_quote += returned_tokens;
-        TOTALS.save(deps.storage, &(total_base, total_quote))?;
-
         let debt_rate = rates.vault_debt;
         let debt_amount = debt_tokens.mul_ceil(&debt_rate);
 
@@ -200,6 +334,18 @@ impl Broker {
     }
 }
 
+/// Errors if `duration` is outside of `MIN_DURATION..=MAX_DURATION`
+pub fn validate_duration(duration: u64) -> Result<(), ContractError> {
+    ensure!(
+        (MIN_DURATION..=MAX_DURATION).contains(&duration),
+        ContractError::InvalidDuration {
+            min: MIN_DURATION,
+            max: MAX_DURATION
+        }
+    );
+    Ok(())
+}
+
 /// The details of an offer returned by the Broker
 #[cw_serde]
 pub struct Offer {
@@ -226,6 +372,23 @@ impl From<Offer> for String {
     }
 }
 
+/// An offer along with the terms it was priced on
+#[cw_serde]
+pub struct Quote {
+    pub offer: Offer,
+
+    /// The annualised rate charged on the offer, after `min_rate` has been applied
+    pub rate: Decimal,
+
+    /// Whether the offer was clamped by the reserves available, with the shortfall added to the fee
+    pub clamped: bool,
+
+    /// The fee as an annualised rate on the amount received
+    pub effective_rate: Decimal,
+}
+
+/// The totals recorded by the Broker before they were tallied by route, all of which were made
+/// on the primary route
 #[cw_serde]
 pub struct Status {
     /// The total amount of base asset that has been requested for unbonding
@@ -1,13 +1,24 @@
 use crate::{
-    adapter::Adapter,
-    broker::{Broker, Offer, Status},
+    adapter::{Adapter, Unstake},
+    broker::{validate_duration, Broker, Offer, Quote, MAX_DURATION, MAX_MIN_RATE},
     denoms::{Ask, Base, Debt, Rcpt},
+    ownership::PendingOwner,
+    pricing::PricingModel,
     rates::Rates,
+    timelock::PendingChangesResponse,
+    ContractError,
 };
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary, Decimal, Timestamp};
+use cosmwasm_std::{
+    ensure, Addr, Api, Binary, Checksum, Coin, CustomQuery, Decimal, QuerierWrapper, Timestamp,
+};
+use cw20::{Cw20QueryMsg, Cw20ReceiveMsg, TokenInfoResponse};
 use kujira::{CallbackData, CallbackMsg};
 use monetary::{AmountU128, Denom, Rate};
+use std::{
+    cmp::{max, min},
+    fmt,
+};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -18,8 +29,9 @@ pub struct InstantiateMsg {
     pub reserve_address: Addr,
     pub vault_address: Addr,
 
-    /// The ask denom of the Broker - ie the LST/receipt token
-    pub ask_denom: Denom<Ask>,
+    /// The ask denom of the Broker - ie the LST/receipt token. A CW20 token is Unstaked by
+    /// sending it to the controller
+    pub ask_denom: AskAsset,
 
     /// The offer denom of the Broker - ie the underlying bonded token
     pub offer_denom: Denom<Base>,
@@ -30,54 +42,491 @@ pub struct InstantiateMsg {
     /// The minimum offer rate set on the broker
     pub min_rate: Decimal,
 
+    /// The pricing model used by the broker. Defaults to the current GHOST borrow rate
+    pub pricing: Option<PricingModel>,
+
     /// The adapter for the unbonding process
     pub adapter: Adapter,
+
+    /// The max interest rate to size reserves against, if the GHOST vault doesn't publish its
+    /// interest curve. Defaults to 300%
+    pub vault_max_interest: Option<Decimal>,
+
+    /// The reward paid to the address that triggers the completion of a delegate.
+    /// Defaults to no reward
+    pub keeper_reward: Option<KeeperReward>,
+
+    /// The largest change in a provider's redemption rate accepted per day, as a share of the
+    /// last accepted rate, for up to a week. Defaults to 1%
+    pub max_rate_change: Option<Decimal>,
+
+    /// The address that can pause operations in an emergency, alongside the owner
+    pub guardian: Option<Addr>,
+
+    /// The minimum notice in seconds given of a `Change`. Defaults to 1 day
+    pub timelock: Option<u64>,
+
+    /// The recipients the protocol fee is split between, by weight. Defaults to
+    /// `protocol_fee_address` alone, as does an empty list
+    pub fee_recipients: Option<Vec<FeeRecipient>>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
     Unstake {
         max_fee: AmountU128<Base>,
+        /// The minimum amount of the offer denom that must be returned to the Unstaker
+        min_receive: Option<AmountU128<Base>>,
+        /// The block time after which the Unstake is rejected
+        deadline: Option<Timestamp>,
         callback: Option<CallbackData>,
+        /// The front-end that referred the Unstake, paid a share of the protocol fee on completion
+        referrer: Option<Addr>,
     },
 
+    /// Unstakes a CW20 ask token, with a `ReceiveMsg` as the hook message
+    Receive(Cw20ReceiveMsg),
+
     /// Called after the GHOST withdrawal has been made.
     /// At this point, the only funds on the contract will be the received debt tokens from GHOST,
     /// and the received Ask tokens from the user
     Callback(CallbackMsg),
 
     /// Called by a delegate contract when the unbonding process is complete.
-    /// Returns the unbonded tokens, the debt tokens for ghost, and the corresponding offer
-    Complete { offer: Offer },
+    /// Returns the unbonded tokens, the debt tokens for ghost, and the corresponding offer,
+    /// along with the address that triggered the completion
+    Complete { offer: Offer, keeper: Option<Addr> },
 
-    /// Update the Controller config
-    UpdateConfig {
-        owner: Option<Addr>,
-        protocol_fee: Option<Decimal>,
-        protocol_fee_address: Option<Addr>,
-        delegate_code_id: Option<u64>,
+    /// Calls `Complete` on up to `limit` delegates that have reached maturity, in order of
+    /// maturity after `start_after`. Each delegate is completed in its own sub-message, so that a
+    /// failure doesn't prevent the rest completing. The batch event reports the `next` cursor, so
+    /// that delegates which keep failing can be paged past
+    CompleteMatured {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
+    /// Continues a migration that moves up to `limit` legacy delegates over to positions, and
+    /// archives completed positions. Callable by anyone until the migration is done
+    MigratePositions { limit: Option<u32> },
+
+    /// Update the Controller config. Fee, Broker, vault and route settings are updated through a
+    /// `Change`
+    UpdateConfig(ConfigUpdate),
+
+    /// Pays out the fee shares held for the sender, after the callback they were sent with failed
+    ClaimFees {},
+
+    /// Sets the largest share of the protocol fee paid to `referrer`. `None` removes the tier,
+    /// so that nothing is paid to the referrer
+    SetReferralTier {
+        referrer: Addr,
+        share: Option<Decimal>,
+    },
+
+    /// Lists `address` in the fee tier named `tier`. `None` removes it from its tier
+    SetFeeTierMember { address: Addr, tier: Option<String> },
+
+    /// Locks the tokens sent towards the `min_balance` of a fee tier. They count once they have
+    /// been locked for the lock period, which restarts when more are added
+    Lock {},
+
+    /// Returns `amount` of the sender's locked tokens
+    Unlock { amount: Coin },
+
+    /// Schedules a change to take effect at `effective`, which must be at least `timelock`
+    /// seconds away. Defaults to the earliest time allowed
+    ScheduleChange {
+        change: Change,
+        effective: Option<Timestamp>,
+    },
+
+    /// Applies a scheduled change that has reached its effective time, within
+    /// `timelock::EXECUTION_WINDOW` of it. Callable by anyone
+    ExecuteChange { id: u64 },
+
+    /// Cancels a scheduled change
+    CancelChange { id: u64 },
+
+    /// Accepts the provider's current redemption rate on a route, where it has moved further
+    /// than `max_rate_change` allows and the move has been verified
+    AcceptRate { ask_denom: Denom<Ask> },
+
+    /// Pauses operations. Callable by the guardian or owner
+    Pause { operations: Vec<Operation> },
+
+    /// Resumes paused operations. Callable by the owner
+    Unpause { operations: Vec<Operation> },
+
+    /// Removes the guardian, leaving only the owner able to pause operations
+    RemoveGuardian {},
+
+    /// Proposes a new owner, who must accept the transfer before `expiry`. Replaces any
+    /// existing proposal
+    ProposeOwner {
+        owner: Addr,
+        expiry: Option<Timestamp>,
     },
 
+    /// Accepts a proposed ownership transfer. Callable by the proposed owner
+    AcceptOwner {},
+
+    /// Withdraws a proposed ownership transfer. Callable by the owner
+    CancelOwner {},
+}
+
+/// The settings changed by `ExecuteMsg::UpdateConfig`. Those left `None` are unchanged
+#[cw_serde]
+#[derive(Default)]
+pub struct ConfigUpdate {
+    pub keeper_reward: Option<KeeperReward>,
+    pub max_rate_change: Option<Decimal>,
+    pub guardian: Option<Addr>,
+    pub referral_share: Option<Decimal>,
+}
+
+/// The parameters that can only be changed with notice
+#[cw_serde]
+pub enum Change {
     /// Update the Broker config
-    UpdateBroker {
+    Broker {
         min_rate: Option<Decimal>,
         duration: Option<u64>,
+        pricing: Option<PricingModel>,
+    },
+
+    /// Update the protocol fee and the address it's paid to, the code used for new delegates, and
+    /// the fallback ceiling on the GHOST vault's interest rate
+    Config {
+        protocol_fee: Option<Decimal>,
+        protocol_fee_address: Option<Addr>,
+        delegate_code_id: Option<u64>,
+        vault_max_interest: Option<Decimal>,
     },
+
+    /// Update the notice required of later changes. A reduction only applies once the previous
+    /// delay has passed
+    Timelock { delay: u64 },
+
+    /// Replace the fee tiers offered to qualifying Unstakers
+    FeeTiers { tiers: Vec<FeeTier> },
+
+    /// Replace the recipients the protocol fee is split between. An empty list pays it all to
+    /// `protocol_fee_address`
+    FeeRecipients { recipients: Vec<FeeRecipient> },
+
+    /// Adds a route for an additional ask denom, replacing any existing route for that denom that
+    /// has no open positions.
+    /// The primary route is set at instantiation, and can't be replaced
+    Route { route: Route },
+
+    /// Removes the route for an additional ask denom that has no open positions
+    RemoveRoute { ask_denom: Denom<Ask> },
+}
+
+/// The operations that can be paused. Completion of existing positions is never paused, so that
+/// they can always unwind
+#[cw_serde]
+pub enum Operation {
+    /// New Unstakes, of native and CW20 tokens
+    Unstake,
+}
+
+/// An ask denom accepted by the controller, and the provider it is unbonded with. Routes share
+/// the controller's GHOST vault, reserve and pricing
+#[cw_serde]
+pub struct Route {
+    pub ask_denom: AskAsset,
+
+    /// The adapter for the unbonding process
+    pub adapter: Adapter,
+
+    /// The amount of time in seconds that an unbonding takes. The provider's own unbonding
+    /// period is used instead where it reports a longer one
+    pub unbonding_duration: u64,
+}
+
+impl Route {
+    /// Checks the unbonding duration and the adapter, and that a CW20 token can be unbonded by
+    /// the adapter
+    pub fn validate<T: CustomQuery>(
+        &self,
+        api: &dyn Api,
+        querier: QuerierWrapper<T>,
+    ) -> Result<(), ContractError> {
+        validate_duration(self.unbonding_duration)?;
+        if let AskAsset::Cw20 { cw20 } = &self.ask_denom {
+            api.addr_validate(cw20.as_str())?;
+            ensure!(
+                self.adapter.supports_cw20(),
+                ContractError::Cw20Unsupported {}
+            );
+            querier.query_wasm_smart::<TokenInfoResponse>(cw20, &Cw20QueryMsg::TokenInfo {})?;
+        }
+        self.adapter.validate(querier)?;
+        Ok(())
+    }
+
+    /// The Broker pricing offers on this route, over the longer of the configured unbonding
+    /// duration and the one reported by the provider, capped at `MAX_DURATION`
+    pub fn broker<T: CustomQuery>(&self, querier: QuerierWrapper<T>, broker: &Broker) -> Broker {
+        // A provider failing to report its period shouldn't halt Unstakes, so the configured
+        // duration is relied on instead
+        let reported = self
+            .adapter
+            .unbonding_duration(querier)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        // Nor should a misreported one price every offer at the maximum interest
+        Broker {
+            duration: min(max(self.unbonding_duration, reported), MAX_DURATION),
+            ..broker.clone()
+        }
+    }
+}
+
+/// The token Unstaked on a route. A native denom is serialized as a plain string, as the ask
+/// denom was before CW20 tokens were accepted
+#[cw_serde]
+#[serde(untagged)]
+pub enum AskAsset {
+    /// A native or token factory denom, sent with `ExecuteMsg::Unstake`
+    Native(Denom<Ask>),
+
+    /// A CW20 token, sent to the controller with `ReceiveMsg::Unstake`
+    Cw20 { cw20: Addr },
+}
+
+impl AskAsset {
+    /// The denom the asset is accounted under. A CW20 token is identified by its address
+    pub fn denom(&self) -> Denom<Ask> {
+        match self {
+            AskAsset::Native(denom) => denom.clone(),
+            AskAsset::Cw20 { cw20 } => Denom::new(cw20.as_str()),
+        }
+    }
+
+    pub fn is_cw20(&self) -> bool {
+        matches!(self, AskAsset::Cw20 { .. })
+    }
+}
+
+impl fmt::Display for AskAsset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.denom())
+    }
+}
+
+/// The reward paid to the address that triggers the completion of a delegate.
+/// It is paid out of the protocol fee, and capped at the protocol fee earned on the offer
+#[cw_serde]
+pub enum KeeperReward {
+    /// A fixed amount of the offer denom
+    Fixed { amount: AmountU128<Base> },
+
+    /// A share of the fee revenue earned when closing the offer
+    Share { share: Decimal },
+}
+
+impl Default for KeeperReward {
+    fn default() -> Self {
+        Self::Fixed {
+            amount: AmountU128::zero(),
+        }
+    }
+}
+
+impl KeeperReward {
+    /// Errors if a share of more than the whole fee revenue is configured
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if let KeeperReward::Share { share } = self {
+            ensure!(
+                *share <= Decimal::one(),
+                ContractError::InvalidKeeperReward {}
+            );
+        }
+        Ok(())
+    }
+
+    /// The reward for closing an offer that earned `revenue`, of which `protocol_fee` is due to
+    /// the protocol
+    pub fn amount(
+        &self,
+        revenue: AmountU128<Base>,
+        protocol_fee: AmountU128<Base>,
+    ) -> AmountU128<Base> {
+        let reward = match self {
+            KeeperReward::Fixed { amount } => *amount,
+            KeeperReward::Share { share } => revenue.dec_mul_floor(*share),
+        };
+        min(reward, protocol_fee)
+    }
+}
+
+/// A recipient of a share of the protocol fee
+#[cw_serde]
+pub struct FeeRecipient {
+    pub address: Addr,
+
+    /// The share of the protocol fee paid to `address`
+    pub weight: Decimal,
+
+    /// Executed on `address` with the fee attached, in place of a plain transfer
+    pub callback: Option<CallbackData>,
+}
+
+impl FeeRecipient {
+    /// Errors unless the addresses are valid, and the weights are non-zero and sum to 1. No
+    /// recipients at all is valid, in which case the protocol fee address is paid instead
+    pub fn validate(api: &dyn Api, recipients: &[Self]) -> Result<(), ContractError> {
+        if recipients.is_empty() {
+            return Ok(());
+        }
+        let mut total = Decimal::zero();
+        for recipient in recipients {
+            api.addr_validate(recipient.address.as_str())?;
+            ensure!(
+                !recipient.weight.is_zero(),
+                ContractError::InvalidFeeRecipients {}
+            );
+            total = total
+                .checked_add(recipient.weight)
+                .map_err(|_| ContractError::InvalidFeeRecipients {})?;
+        }
+        ensure!(
+            total == Decimal::one(),
+            ContractError::InvalidFeeRecipients {}
+        );
+        Ok(())
+    }
+
+    /// Splits `amount` between `recipients` by weight. Each share is rounded down, and the dust
+    /// is added to the first recipient's share
+    pub fn split(recipients: &[Self], amount: AmountU128<Base>) -> Vec<AmountU128<Base>> {
+        let mut shares: Vec<AmountU128<Base>> = recipients
+            .iter()
+            .map(|x| amount.dec_mul_floor(x.weight))
+            .collect();
+        let total = shares
+            .iter()
+            .fold(AmountU128::zero(), |total, x| total + *x);
+        if let Some(first) = shares.first_mut() {
+            *first = *first + (amount - total);
+        }
+        shares
+    }
+}
+
+/// A discount on Unstakes for institutional partners and token holders. Partners are listed in
+/// the tier with `ExecuteMsg::SetFeeTierMember`
+#[cw_serde]
+pub struct FeeTier {
+    pub name: String,
+
+    /// Holders that have locked at least this balance with the controller for the lock period
+    /// qualify for the tier
+    pub min_balance: Option<Coin>,
+
+    /// Replaces the Broker's `min_rate`, where lower
+    pub min_rate: Option<Decimal>,
+
+    /// The share of the protocol fee rebated to the Unstaker on completion
+    pub rebate: Decimal,
+}
+
+impl FeeTier {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        ensure!(
+            !self.min_rate.is_some_and(|x| x > MAX_MIN_RATE) && self.rebate <= Decimal::one(),
+            ContractError::InvalidFeeTier {
+                name: self.name.clone()
+            }
+        );
+        Ok(())
+    }
+
+    /// The Broker pricing offers for the tier
+    pub fn broker(&self, broker: &Broker) -> Broker {
+        Broker {
+            min_rate: self
+                .min_rate
+                .map_or(broker.min_rate, |x| min(x, broker.min_rate)),
+            ..broker.clone()
+        }
+    }
 }
 
 #[cw_serde]
 pub enum CallbackType {
-    GhostBorrow { offer: Offer },
+    GhostBorrow {
+        offer: Offer,
+        unstaker: Addr,
+        ask_denom: Denom<Ask>,
+        referrer: Option<Addr>,
+        referral_share: Decimal,
+        rebate: Decimal,
+    },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    /// Quotes an Unstake of `amount` of `ask_denom`. Defaults to the primary route
     #[returns(OfferResponse)]
-    Offer { amount: AmountU128<Ask> },
+    Offer {
+        amount: AmountU128<Ask>,
+        ask_denom: Option<Denom<Ask>>,
+        /// Quotes for `sender`, with any fee tier it qualifies for
+        sender: Option<Addr>,
+    },
+
+    /// Quotes the amount of `ask_denom` that must be unstaked to receive at least `amount`
+    /// of the offer denom. Defaults to the primary route
+    #[returns(OfferForOutputResponse)]
+    OfferForOutput {
+        amount: AmountU128<Base>,
+        ask_denom: Option<Denom<Ask>>,
+        /// Quotes for `sender`, with any fee tier it qualifies for
+        sender: Option<Addr>,
+    },
 
+    /// Delegates with an unbonding in progress, ordered by maturity
     #[returns(DelegatesResponse)]
-    Delegates {},
+    Delegates {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        filter: Option<DelegatesFilter>,
+    },
+
+    /// Open positions, ordered by delegate address
+    #[returns(PositionsResponse)]
+    Positions {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
+    /// The open positions of `unstaker`, ordered by delegate address
+    #[returns(PositionsResponse)]
+    PositionsByUser {
+        unstaker: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
+    /// Unbonding positions that have reached maturity, ordered by maturity
+    #[returns(PositionsResponse)]
+    MaturedPositions {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
+    /// Completed positions, which are archived on completion, ordered by delegate address
+    #[returns(PositionsResponse)]
+    CompletedPositions {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
 
     #[returns(RatesResponse)]
     Rates {},
@@ -85,46 +534,151 @@ pub enum QueryMsg {
     #[returns(StatusResponse)]
     Status {},
 
+    /// The routes accepted by the controller, starting with the primary route
+    #[returns(RoutesResponse)]
+    Routes {},
+
+    /// Changes scheduled by the owner, in the order they were scheduled
+    #[returns(PendingChangesResponse<Change>)]
+    PendingChanges {},
+
+    /// The volume referred and fees earned by each referrer, ordered by address
+    #[returns(ReferralsResponse)]
+    Referrals {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
     #[returns(ConfigResponse)]
     Config {},
+
+    /// The fee shares held for `address` to claim
+    #[returns(UnclaimedFeesResponse)]
+    UnclaimedFees { address: Addr },
+
+    /// The addresses listed in a fee tier, ordered by address
+    #[returns(FeeTierMembersResponse)]
+    FeeTierMembers {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
+    /// The tokens `address` has locked towards the fee tiers
+    #[returns(LocksResponse)]
+    Locks { address: Addr },
 }
 
 #[cw_serde]
 pub struct OfferResponse {
     pub amount: AmountU128<Base>,
     pub fee: AmountU128<Base>,
+    /// The pricing model that produced the offer
+    pub model: PricingModel,
+    /// The full offer, as it would be made by the Broker
+    pub offer: Offer,
+    /// The rates that the offer was priced on
+    pub rates: RatesResponse,
+    /// The annualised rate charged on the offer
+    pub rate: Decimal,
+    /// Whether the offer was clamped by the reserves available, with the shortfall added to the fee
+    pub clamped: bool,
+    /// The fee as an annualised rate on the amount received
+    pub effective_rate: Decimal,
+    /// The fee tier applied to the sender
+    pub tier: Option<String>,
+}
+
+#[cw_serde]
+pub struct OfferForOutputResponse {
+    /// The amount of the ask denom required
+    pub amount: AmountU128<Ask>,
+    /// The offer made for `amount`
+    pub offer: Offer,
+    /// The pricing model that produced the offer
+    pub model: PricingModel,
 }
 
 #[cw_serde]
 pub struct RatesResponse {
     pub vault_debt: Rate<Base, Debt>,
     pub vault_interest: Decimal,
+    pub vault_utilization: Decimal,
     pub vault_max_interest: Decimal,
+    /// The interest curve published by the GHOST vault. `None` if the configured
+    /// fallback has been used for `vault_max_interest`
+    pub vault_interest_curve: Option<Vec<(Decimal, Decimal)>>,
     pub provider_redemption: Rate<Base, Ask>,
 }
 
 #[cw_serde]
 pub struct StatusResponse {
-    /// The total amount of base asset that has been requested for unbonding
+    /// The total amount of the primary route's ask denom that has been requested for unbonding.
+    /// Other ask denoms are totalled in `routes`
+    pub total_base: AmountU128<Ask>,
+    /// The total amount of quote asset that has been returned from unbonding, across all routes
+    pub total_quote: AmountU128<Base>,
+    /// The totals of each route
+    pub routes: Vec<RouteStatus>,
+}
+
+#[cw_serde]
+pub struct RouteStatus {
+    pub ask_denom: Denom<Ask>,
+    /// The total amount of `ask_denom` that has been requested for unbonding
     pub total_base: AmountU128<Ask>,
-    /// The total amount of quote asset that has been returned from unbonding
+    /// The total amount of quote asset that has been returned from unbonding `ask_denom`
     pub total_quote: AmountU128<Base>,
 }
 
+#[cw_serde]
+pub struct RoutesResponse {
+    pub routes: Vec<Route>,
+}
+
+/// The hook messages accepted with a CW20 ask token
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Unstakes the tokens sent, as `ExecuteMsg::Unstake`
+    Unstake {
+        max_fee: AmountU128<Base>,
+        min_receive: Option<AmountU128<Base>>,
+        deadline: Option<Timestamp>,
+        callback: Option<CallbackData>,
+        referrer: Option<Addr>,
+    },
+}
+
 #[cw_serde]
 pub struct ConfigResponse {
     pub owner: Addr,
     pub protocol_fee: Decimal,
     pub protocol_fee_address: Addr,
     pub delegate_code_id: u64,
+    pub delegate_code_checksum: Option<Checksum>,
     pub reserve_address: Addr,
     pub vault_address: Addr,
     pub offer_denom: Denom<Base>,
-    pub ask_denom: Denom<Ask>,
+    pub ask_denom: AskAsset,
     pub debt_denom: Denom<Debt>,
     pub ghost_denom: Denom<Rcpt>,
     pub adapter: Adapter,
+    pub vault_max_interest: Decimal,
+    pub keeper_reward: KeeperReward,
+    pub max_rate_change: Decimal,
+    pub guardian: Option<Addr>,
+    pub paused: Vec<Operation>,
+    pub pending_owner: Option<PendingOwner>,
+    pub timelock: u64,
+    /// The recipients the protocol fee is split between
+    pub fee_recipients: Vec<FeeRecipient>,
+    /// The share of the protocol fee paid to referrers, up to each referrer's tier
+    pub referral_share: Decimal,
+    /// The discounts offered to qualifying Unstakers, in order of precedence
+    pub fee_tiers: Vec<FeeTier>,
     pub broker: Broker,
+    /// The unbonding duration offers on the primary route are priced over: the longer of
+    /// `broker.duration` and the unbonding period reported by the provider
+    pub unbonding_duration: u64,
 }
 
 #[cw_serde]
@@ -134,16 +688,150 @@ pub struct ContractResponse {
     pub unbond_end_msg: Binary,
 }
 
+#[cw_serde]
+pub enum DelegatesFilter {
+    /// Delegates that have reached maturity as of the current block, and can be completed
+    Matured,
+    /// Delegates that are still within their unbonding period
+    Unbonding,
+}
+
 #[cw_serde]
 pub struct DelegatesResponse {
+    /// The delegate address, and the time it was created
     pub delegates: Vec<(Addr, Timestamp)>,
+    /// The `start_after` for the next page, if there may be more results
+    pub next: Option<Addr>,
+}
+
+#[cw_serde]
+pub enum PositionStatus {
+    /// The delegate is waiting on the provider's unbonding period
+    Unbonding,
+    /// The unbonded tokens have been returned and the offer closed
+    Completed,
 }
 
-impl From<Offer> for OfferResponse {
-    fn from(value: Offer) -> Self {
+/// An Unstake, from the GHOST borrow through to the return of the unbonded tokens
+#[cw_serde]
+pub struct Position {
+    /// The delegate contract holding the unbonding
+    pub delegate: Addr,
+
+    /// The sender of the Unstake. `None` for positions opened before this was recorded
+    pub unstaker: Option<Addr>,
+
+    /// The offer made to the unstaker
+    pub offer: Offer,
+
+    /// The ask denom unstaked. `None` for positions opened before routes were introduced, which
+    /// are all on the primary route
+    #[serde(default)]
+    pub ask_denom: Option<Denom<Ask>>,
+
+    /// The front-end that referred the Unstake
+    #[serde(default)]
+    pub referrer: Option<Addr>,
+
+    /// The share of the protocol fee paid to the referrer, from its tier
+    #[serde(default)]
+    pub referral_share: Decimal,
+
+    /// The share of the protocol fee rebated to the unstaker, from its fee tier
+    #[serde(default)]
+    pub rebate: Decimal,
+
+    /// The GHOST debt tokens minted for the borrow, and held by the delegate
+    pub debt_tokens: AmountU128<Debt>,
+
+    /// When the delegate was created
+    pub start: Timestamp,
+
+    /// When the unbonding is expected to complete, given the Broker's duration at the start
+    pub maturity: Timestamp,
+
+    pub status: PositionStatus,
+}
+
+#[cw_serde]
+pub struct PositionsResponse {
+    pub positions: Vec<Position>,
+}
+
+#[cw_serde]
+pub struct Referral {
+    pub referrer: Addr,
+
+    /// The largest share of the protocol fee paid to the referrer, set by the owner
+    pub tier: Decimal,
+
+    /// The total offer amount of the Unstakes referred
+    pub volume: AmountU128<Base>,
+
+    /// The total protocol fee paid to the referrer
+    pub earnings: AmountU128<Base>,
+}
+
+#[cw_serde]
+pub struct ReferralsResponse {
+    pub referrals: Vec<Referral>,
+}
+
+#[cw_serde]
+pub struct UnclaimedFeesResponse {
+    pub amount: AmountU128<Base>,
+}
+
+#[cw_serde]
+pub struct FeeTierMember {
+    pub address: Addr,
+
+    /// The name of the fee tier the address is listed in
+    pub tier: String,
+}
+
+#[cw_serde]
+pub struct FeeTierMembersResponse {
+    pub members: Vec<FeeTierMember>,
+}
+
+/// Tokens locked with the controller towards the `min_balance` of a fee tier
+#[cw_serde]
+pub struct Lock {
+    pub amount: Coin,
+
+    /// When the lock was last added to. It counts towards a fee tier once the lock period has
+    /// passed since
+    pub start: Timestamp,
+}
+
+#[cw_serde]
+pub struct LocksResponse {
+    pub locks: Vec<Lock>,
+}
+
+impl OfferResponse {
+    pub fn new(quote: Quote, rates: Rates, model: PricingModel, tier: Option<String>) -> Self {
+        Self {
+            amount: quote.offer.offer_amount,
+            fee: quote.offer.fee,
+            model,
+            offer: quote.offer,
+            rates: rates.into(),
+            rate: quote.rate,
+            clamped: quote.clamped,
+            effective_rate: quote.effective_rate,
+            tier,
+        }
+    }
+}
+
+impl OfferForOutputResponse {
+    pub fn new(offer: Offer, model: PricingModel) -> Self {
         Self {
-            amount: value.offer_amount,
-            fee: value.fee,
+            amount: offer.unbond_amount,
+            offer,
+            model,
         }
     }
 }
@@ -153,17 +841,125 @@ impl From<Rates> for RatesResponse {
         Self {
             vault_debt: value.vault_debt,
             vault_interest: value.vault_interest,
+            vault_utilization: value.vault_utilization,
             vault_max_interest: value.vault_max_interest,
+            vault_interest_curve: value.vault_interest_curve,
             provider_redemption: value.provider_redemption,
         }
     }
 }
 
-impl From<Status> for StatusResponse {
-    fn from(value: Status) -> Self {
+impl StatusResponse {
+    /// Totals `routes`, the first of which is the primary route. Only the quote asset is shared
+    /// by every route, so the base total is that of the primary route
+    pub fn new(routes: Vec<RouteStatus>) -> Self {
         Self {
-            total_base: value.total_base,
-            total_quote: value.total_quote,
+            total_base: routes.first().map(|x| x.total_base).unwrap_or_default(),
+            total_quote: routes
+                .iter()
+                .fold(AmountU128::zero(), |acc, x| acc + x.total_quote),
+            routes,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::eris::ContractConfigResponse;
+    use cosmwasm_std::{
+        testing::{MockApi, MockQuerier},
+        to_json_binary, ContractResult, Empty, SystemResult, Uint128,
+    };
+
+    fn recipient(address: &str, weight: Decimal) -> FeeRecipient {
+        FeeRecipient {
+            address: MockApi::default().addr_make(address),
+            weight,
+            callback: None,
+        }
+    }
+
+    #[test]
+    fn fee_recipients() {
+        let api = MockApi::default();
+        let third = Decimal::from_ratio(1u128, 3u128);
+        let recipients = vec![
+            recipient("treasury", third),
+            recipient("staking", third),
+            recipient("referrals", Decimal::one() - third - third),
+        ];
+        FeeRecipient::validate(&api, &recipients).unwrap();
+
+        // The dust left by rounding down each share goes to the first recipient
+        let shares = FeeRecipient::split(&recipients, AmountU128::new(Uint128::new(100)));
+        assert_eq!(
+            shares,
+            vec![
+                AmountU128::new(Uint128::new(34)),
+                AmountU128::new(Uint128::new(33)),
+                AmountU128::new(Uint128::new(33)),
+            ]
+        );
+
+        FeeRecipient::validate(&api, &[]).unwrap();
+        FeeRecipient::validate(&api, &recipients[..2]).unwrap_err();
+        FeeRecipient::validate(
+            &api,
+            &[
+                recipient("treasury", Decimal::one()),
+                recipient("staking", Decimal::zero()),
+            ],
+        )
+        .unwrap_err();
+        FeeRecipient::validate(
+            &api,
+            &[FeeRecipient {
+                address: Addr::unchecked("treasury"),
+                weight: Decimal::one(),
+                callback: None,
+            }],
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn route_broker_duration() {
+        let provider = |unbond_period: u64| {
+            let mut querier: MockQuerier = MockQuerier::new(&[]);
+            querier.update_wasm(move |_| {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&ContractConfigResponse {
+                        epoch_period: 0,
+                        unbond_period,
+                    })
+                    .unwrap(),
+                ))
+            });
+            querier
+        };
+        let route = Route {
+            ask_denom: AskAsset::Native(Denom::new("ampkuji")),
+            adapter: Adapter::Eris(Addr::unchecked("eris").into()),
+            unbonding_duration: 14 * 24 * 60 * 60,
+        };
+        let broker = Broker {
+            min_rate: Decimal::percent(10),
+            duration: 14 * 24 * 60 * 60,
+            pricing: PricingModel::default(),
+        };
+
+        let querier = provider(21 * 24 * 60 * 60);
+        let duration = route
+            .broker(QuerierWrapper::<Empty>::new(&querier), &broker)
+            .duration;
+        assert_eq!(duration, 21 * 24 * 60 * 60);
+
+        // A reported period beyond the maximum is capped
+        let querier = provider(365 * 24 * 60 * 60);
+        let duration = route
+            .broker(QuerierWrapper::<Empty>::new(&querier), &broker)
+            .duration;
+        assert_eq!(duration, MAX_DURATION);
+    }
+}
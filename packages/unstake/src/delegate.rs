@@ -1,6 +1,10 @@
-use crate::{adapter::Adapter, broker::Offer, denoms::Ask};
+use crate::{
+    adapter::{stride::IbcLifecycleComplete, Adapter, UnbondStatus},
+    broker::Offer,
+    denoms::Ask,
+};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Coin, Timestamp};
 use monetary::CheckedCoin;
 
 /// A delegate is instantiated for each individual Unbonding transaction.
@@ -21,18 +25,62 @@ pub struct InstantiateMsg {
 
     /// The adapter for unbonding,
     pub adapter: Adapter,
+
+    /// Whether the unbond amount is a CW20 token, in which case its denom is the token's
+    /// contract address
+    #[serde(default)]
+    pub cw20: bool,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
     /// Withdraws the completed unbond, and calls back to the controller to repay
-    /// and handle protocol reserves
-    Complete {},
+    /// and handle protocol reserves.
+    /// `keeper` is the address to reward for the completion, and is only honoured when called by
+    /// the controller. Otherwise the sender is rewarded
+    Complete { keeper: Option<Addr> },
 
     /// Callback execugted after unbonded funds have been received
-    Callback {},
+    Callback { keeper: Option<Addr> },
+
+    /// Starts the unbond again, once the automatic retries of a failed IBC transfer have run out.
+    /// Callable by anyone, as the refunded tokens can only be sent on to the provider
+    RetryUnbond {},
+}
+
+#[cw_serde]
+pub enum SudoMsg {
+    /// Sent by the IBC hooks middleware when the transfer to an IBC provider completes.
+    /// A failed or timed out transfer refunds the tokens, and the unbond is started again, up to a
+    /// limited number of retries
+    IbcLifecycleComplete(IbcLifecycleComplete),
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    /// The controller that instantiated the delegate
+    #[returns(Addr)]
+    Controller {},
+
+    /// The offer that was made for the unbonding
+    #[returns(Offer)]
+    Offer {},
+
+    /// The adapter used for the unbonding
+    #[returns(Adapter)]
+    Adapter {},
+
+    /// The time the delegate was instantiated. `None` for delegates created before this was recorded
+    #[returns(Option<Timestamp>)]
+    Created {},
+
+    /// The funds currently held by the delegate
+    #[returns(Vec<Coin>)]
+    Balances {},
+
+    /// The progress of the unbonding, as reported by the provider. `None` where the provider
+    /// doesn't report it
+    #[returns(Option<UnbondStatus>)]
+    Status {},
+}
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Instantiate2AddressError, OverflowError, StdError, Uint128};
+use cosmwasm_std::{Decimal, Instantiate2AddressError, OverflowError, StdError, Uint128};
 use cw_utils::PaymentError;
 use monetary::MonetaryError;
 use thiserror::Error;
@@ -35,9 +35,108 @@ pub enum ContractError {
     #[error("MaxFeeExceeded")]
     MaxFeeExceeded {},
 
+    #[error("MinReceiveNotMet")]
+    MinReceiveNotMet {},
+
+    #[error("DeadlineExceeded")]
+    DeadlineExceeded {},
+
+    #[error("OutputUnreachable")]
+    OutputUnreachable {},
+
     #[error("Insolvent {debt_remaining} remaining")]
     Insolvent { debt_remaining: Uint128 },
 
     #[error("RequestZero")]
     RequestZero {},
+
+    #[error("UnbondingIncomplete {pending} pending")]
+    UnbondingIncomplete { pending: Uint128 },
+
+    #[error("UnknownRoute {ask_denom}")]
+    UnknownRoute { ask_denom: String },
+
+    #[error("InvalidRoute")]
+    InvalidRoute {},
+
+    #[error("OpenPositions {count}")]
+    OpenPositions { count: u32 },
+
+    #[error("Paused")]
+    Paused {},
+
+    #[error("NoPendingOwner")]
+    NoPendingOwner {},
+
+    #[error("NoGuardian")]
+    NoGuardian {},
+
+    #[error("NothingToClaim")]
+    NothingToClaim {},
+
+    #[error("OwnershipExpired")]
+    OwnershipExpired {},
+
+    #[error("UnknownChange {id}")]
+    UnknownChange { id: u64 },
+
+    #[error("TimelockTooShort")]
+    TimelockTooShort {},
+
+    #[error("TimelockNotElapsed")]
+    TimelockNotElapsed {},
+
+    #[error("ChangeExpired {id}")]
+    ChangeExpired { id: u64 },
+
+    #[error("RateDeviation {rate} from {last_rate}")]
+    RateDeviation { rate: Decimal, last_rate: Decimal },
+
+    #[error("Cw20Unsupported")]
+    Cw20Unsupported {},
+
+    #[error("ZeroRate")]
+    ZeroRate {},
+
+    #[error("RetryUnavailable")]
+    RetryUnavailable {},
+
+    #[error("MigrationComplete")]
+    MigrationComplete {},
+
+    #[error("InvalidProtocolFee max {max}")]
+    InvalidProtocolFee { max: Decimal },
+
+    #[error("InvalidVaultMaxInterest max {max}")]
+    InvalidVaultMaxInterest { max: Decimal },
+
+    #[error("InvalidMinRate max {max}")]
+    InvalidMinRate { max: Decimal },
+
+    #[error("InvalidPricingModel max {max}")]
+    InvalidPricingModel { max: Decimal },
+
+    #[error("InvalidMaxRateChange max {max}")]
+    InvalidMaxRateChange { max: Decimal },
+
+    #[error("InvalidKeeperReward")]
+    InvalidKeeperReward {},
+
+    #[error("InvalidTimelock max {max}")]
+    InvalidTimelock { max: u64 },
+
+    #[error("InvalidDuration {min}..={max}")]
+    InvalidDuration { min: u64, max: u64 },
+
+    #[error("InvalidDelegateCode {code_id}")]
+    InvalidDelegateCode { code_id: u64 },
+
+    #[error("InvalidFeeRecipients")]
+    InvalidFeeRecipients {},
+
+    #[error("InvalidReferralShare")]
+    InvalidReferralShare {},
+
+    #[error("InvalidFeeTier {name}")]
+    InvalidFeeTier { name: String },
 }
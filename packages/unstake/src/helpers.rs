@@ -2,8 +2,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::{
-    instantiate2_address, to_json_binary, Addr, Binary, CodeInfoResponse, Coin, CosmosMsg,
-    CustomQuery, Deps, Env, WasmMsg,
+    instantiate2_address, to_json_binary, Addr, Binary, Checksum, Coin, CosmosMsg, CustomQuery,
+    Deps, Env, WasmMsg,
 };
 
 use crate::ContractError;
@@ -54,13 +54,13 @@ impl Controller {
     }
 }
 
+/// The address that code with `checksum` is instantiated at with `label` as its salt
 pub fn predict_address<T: CustomQuery>(
-    code_id: u64,
+    checksum: &Checksum,
     label: &String,
     deps: &Deps<T>,
     env: &Env,
 ) -> Result<(Addr, Binary), ContractError> {
-    let CodeInfoResponse { checksum, .. } = deps.querier.query_wasm_code_info(code_id)?;
     let salt = Binary::from(label.as_bytes().chunks(64).next().unwrap());
     let creator = deps.api.addr_canonicalize(env.contract.address.as_str())?;
     let contract_addr =
@@ -5,7 +5,10 @@ pub mod delegate;
 pub mod denoms;
 pub mod error;
 pub mod helpers;
+pub mod ownership;
+pub mod pricing;
 pub mod rates;
 pub mod reserve;
+pub mod timelock;
 
 pub use error::ContractError;
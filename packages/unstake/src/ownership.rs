@@ -0,0 +1,78 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, ensure_eq, Addr, Timestamp};
+
+use crate::ContractError;
+
+/// An ownership transfer proposed by the current owner, which takes effect once accepted by the
+/// proposed owner
+#[cw_serde]
+pub struct PendingOwner {
+    pub owner: Addr,
+    /// The time after which the proposal can no longer be accepted
+    pub expiry: Option<Timestamp>,
+}
+
+impl PendingOwner {
+    /// Creates a proposal, rejecting an expiry that has already passed
+    pub fn new(
+        owner: Addr,
+        expiry: Option<Timestamp>,
+        now: Timestamp,
+    ) -> Result<Self, ContractError> {
+        ensure!(
+            !expiry.is_some_and(|x| now >= x),
+            ContractError::OwnershipExpired {}
+        );
+        Ok(Self { owner, expiry })
+    }
+
+    /// Returns the new owner, if `sender` is the proposed owner and the proposal hasn't expired
+    pub fn accept(self, sender: &Addr, now: Timestamp) -> Result<Addr, ContractError> {
+        ensure_eq!(sender, &self.owner, ContractError::Unauthorized {});
+        ensure!(
+            !self.expiry.is_some_and(|x| now >= x),
+            ContractError::OwnershipExpired {}
+        );
+        Ok(self.owner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept() {
+        let now = Timestamp::from_seconds(100);
+        let pending = PendingOwner::new(
+            Addr::unchecked("new"),
+            Some(Timestamp::from_seconds(200)),
+            now,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            pending.clone().accept(&Addr::unchecked("other"), now),
+            Err(ContractError::Unauthorized {})
+        ));
+        assert!(matches!(
+            pending
+                .clone()
+                .accept(&Addr::unchecked("new"), Timestamp::from_seconds(200)),
+            Err(ContractError::OwnershipExpired {})
+        ));
+        assert_eq!(
+            pending.accept(&Addr::unchecked("new"), now).unwrap(),
+            Addr::unchecked("new")
+        );
+
+        assert!(matches!(
+            PendingOwner::new(Addr::unchecked("new"), Some(now), now),
+            Err(ContractError::OwnershipExpired {})
+        ));
+        PendingOwner::new(Addr::unchecked("new"), None, now)
+            .unwrap()
+            .accept(&Addr::unchecked("new"), Timestamp::from_seconds(u64::MAX))
+            .unwrap();
+    }
+}
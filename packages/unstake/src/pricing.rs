@@ -0,0 +1,320 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, Decimal, StdResult, Storage, Timestamp};
+use cw_storage_plus::Item;
+use monetary::AmountU128;
+use std::cmp::{max, min};
+
+use crate::{broker::MAX_MIN_RATE, denoms::Base, ContractError};
+
+const RATE_AVERAGE: Item<RateAverage> = Item::new("rate_average");
+
+/// The strategy the Broker uses to choose the annualised rate charged on an Unstake.
+/// The Broker's `min_rate` is always applied as a floor to the rate returned by the model.
+#[cw_serde]
+pub enum PricingModel {
+    /// Charge the current GHOST borrow rate for the whole unbonding period
+    Spot {},
+
+    /// Charge a fixed rate, independent of the GHOST vault
+    Flat { rate: Decimal },
+
+    /// Charge a rate following a kinked curve of the GHOST vault utilization:
+    /// `base + slope_low * min(u, kink) + slope_high * max(u - kink, 0)`
+    Kinked {
+        base: Decimal,
+        kink: Decimal,
+        slope_low: Decimal,
+        slope_high: Decimal,
+    },
+
+    /// Charge the current GHOST borrow rate, plus up to `premium` in proportion to the share
+    /// of the available reserve that the Unstake would consume
+    SizePremium { premium: Decimal },
+
+    /// Charge the time-weighted average of the GHOST borrow rate over the last `window` seconds
+    TimeWeighted { window: u64 },
+}
+
+/// The market conditions that a PricingModel can price against
+pub struct PricingContext {
+    /// The current GHOST borrow rate
+    pub vault_interest: Decimal,
+
+    /// The current GHOST vault utilization
+    pub vault_utilization: Decimal,
+
+    /// The time-weighted average GHOST borrow rate
+    pub vault_interest_average: Decimal,
+
+    /// The value of the Unstake, in terms of the underlying asset
+    pub value: AmountU128<Base>,
+
+    /// The reserves available to the Broker, in terms of the underlying asset
+    pub reserve_available: AmountU128<Base>,
+}
+
+impl Default for PricingModel {
+    fn default() -> Self {
+        Self::Spot {}
+    }
+}
+
+impl PricingModel {
+    /// The annualised rate to charge in the given context. Errors if a model's parameters
+    /// overflow the rate
+    pub fn rate(&self, ctx: &PricingContext) -> Result<Decimal, ContractError> {
+        let rate = match self {
+            PricingModel::Spot {} => ctx.vault_interest,
+            PricingModel::Flat { rate } => *rate,
+            PricingModel::Kinked {
+                base,
+                kink,
+                slope_low,
+                slope_high,
+            } => {
+                let low = min(ctx.vault_utilization, *kink);
+                let high = ctx.vault_utilization.saturating_sub(*kink);
+                base.checked_add(slope_low.checked_mul(low)?)?
+                    .checked_add(slope_high.checked_mul(high)?)?
+            }
+            PricingModel::SizePremium { premium } => {
+                let share = Decimal::checked_from_ratio(
+                    ctx.value.uint128(),
+                    ctx.reserve_available.uint128(),
+                )
+                .map_or(Decimal::one(), |x| min(x, Decimal::one()));
+                ctx.vault_interest
+                    .checked_add(premium.checked_mul(share)?)?
+            }
+            PricingModel::TimeWeighted { .. } => ctx.vault_interest_average,
+        };
+        Ok(rate)
+    }
+
+    /// Errors if the rate the model adds to the GHOST borrow rate, or charges in its place, can
+    /// exceed `MAX_MIN_RATE`
+    pub fn validate(&self) -> Result<(), ContractError> {
+        let invalid = || ContractError::InvalidPricingModel { max: MAX_MIN_RATE };
+        let max_rate = match self {
+            PricingModel::Spot {} | PricingModel::TimeWeighted { .. } => Decimal::zero(),
+            PricingModel::Flat { rate } => *rate,
+            PricingModel::Kinked {
+                base,
+                kink,
+                slope_low,
+                slope_high,
+            } => {
+                ensure!(*kink <= Decimal::one(), invalid());
+                // The curve is highest at full utilization
+                base.checked_add(slope_low.checked_mul(*kink).map_err(|_| invalid())?)
+                    .and_then(|x| x.checked_add(slope_high.checked_mul(Decimal::one() - kink)?))
+                    .map_err(|_| invalid())?
+            }
+            PricingModel::SizePremium { premium } => *premium,
+        };
+        ensure!(max_rate <= MAX_MIN_RATE, invalid());
+        Ok(())
+    }
+}
+
+/// A time-weighted moving average of the GHOST borrow rate, updated whenever the Broker
+/// executes an Unstake or closes an offer
+#[cw_serde]
+pub struct RateAverage {
+    /// The time of the last observation
+    pub time: Timestamp,
+
+    /// The GHOST borrow rate at the last observation
+    pub rate: Decimal,
+
+    /// The average as of the last observation
+    pub average: Decimal,
+}
+
+impl RateAverage {
+    pub fn load(storage: &dyn Storage) -> StdResult<Option<Self>> {
+        RATE_AVERAGE.may_load(storage)
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        RATE_AVERAGE.save(storage, self)
+    }
+
+    /// Rolls the average forward to `now`, assuming the last observed rate has held since
+    /// the last observation
+    pub fn average_at(&self, now: Timestamp, window: u64) -> Decimal {
+        let elapsed = now.seconds().saturating_sub(self.time.seconds());
+        let weight = min(Decimal::from_ratio(elapsed, max(window, 1)), Decimal::one());
+        if self.rate >= self.average {
+            self.average + (self.rate - self.average) * weight
+        } else {
+            self.average - (self.average - self.rate) * weight
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::Uint128;
+
+    fn ctx() -> PricingContext {
+        PricingContext {
+            vault_interest: Decimal::percent(10),
+            vault_utilization: Decimal::percent(90),
+            vault_interest_average: Decimal::percent(6),
+            value: AmountU128::new(Uint128::new(500)),
+            reserve_available: AmountU128::new(Uint128::new(1000)),
+        }
+    }
+
+    #[test]
+    fn spot() {
+        assert_eq!(
+            PricingModel::Spot {}.rate(&ctx()).unwrap(),
+            Decimal::percent(10)
+        );
+    }
+
+    #[test]
+    fn flat() {
+        let model = PricingModel::Flat {
+            rate: Decimal::percent(7),
+        };
+        assert_eq!(model.rate(&ctx()).unwrap(), Decimal::percent(7));
+    }
+
+    #[test]
+    fn kinked() {
+        let model = PricingModel::Kinked {
+            base: Decimal::percent(2),
+            kink: Decimal::percent(80),
+            slope_low: Decimal::percent(10),
+            slope_high: Decimal::percent(100),
+        };
+        // 2% + 10% * 80% + 100% * 10%
+        assert_eq!(model.rate(&ctx()).unwrap(), Decimal::percent(20));
+
+        let below = PricingContext {
+            vault_utilization: Decimal::percent(50),
+            ..ctx()
+        };
+        // 2% + 10% * 50%
+        assert_eq!(model.rate(&below).unwrap(), Decimal::percent(7));
+
+        // Parameters large enough to overflow are an error, rather than a panic
+        let steep = PricingModel::Kinked {
+            base: Decimal::percent(2),
+            kink: Decimal::percent(80),
+            slope_low: Decimal::MAX,
+            slope_high: Decimal::MAX,
+        };
+        let steep_ctx = PricingContext {
+            vault_utilization: Decimal::percent(100),
+            ..ctx()
+        };
+        assert!(matches!(
+            steep.rate(&steep_ctx),
+            Err(ContractError::RateOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn size_premium() {
+        let model = PricingModel::SizePremium {
+            premium: Decimal::percent(4),
+        };
+        // 10% + 4% * 500 / 1000
+        assert_eq!(model.rate(&ctx()).unwrap(), Decimal::percent(12));
+
+        // Consuming more than the whole reserve is capped at the full premium
+        let large = PricingContext {
+            value: AmountU128::new(Uint128::new(5000)),
+            ..ctx()
+        };
+        assert_eq!(model.rate(&large).unwrap(), Decimal::percent(14));
+
+        let empty = PricingContext {
+            reserve_available: AmountU128::zero(),
+            ..ctx()
+        };
+        assert_eq!(model.rate(&empty).unwrap(), Decimal::percent(14));
+    }
+
+    #[test]
+    fn validate() {
+        let invalid = |model: PricingModel| {
+            matches!(
+                model.validate(),
+                Err(ContractError::InvalidPricingModel { max }) if max == MAX_MIN_RATE
+            )
+        };
+        PricingModel::Spot {}.validate().unwrap();
+        PricingModel::Flat { rate: MAX_MIN_RATE }
+            .validate()
+            .unwrap();
+        assert!(invalid(PricingModel::Flat {
+            rate: Decimal::percent(3000)
+        }));
+
+        let kinked = |kink: Decimal, slope_high: Decimal| PricingModel::Kinked {
+            base: Decimal::percent(50),
+            kink,
+            slope_low: Decimal::percent(50),
+            slope_high,
+        };
+        // 50% + 50% * 80% + 1000% * 20%
+        kinked(Decimal::percent(80), Decimal::percent(1000))
+            .validate()
+            .unwrap();
+        assert!(invalid(kinked(
+            Decimal::percent(50),
+            Decimal::percent(1000)
+        )));
+        assert!(invalid(kinked(Decimal::percent(120), Decimal::zero())));
+        assert!(invalid(kinked(Decimal::percent(80), Decimal::MAX)));
+
+        assert!(invalid(PricingModel::SizePremium {
+            premium: Decimal::percent(301)
+        }));
+    }
+
+    #[test]
+    fn time_weighted() {
+        let model = PricingModel::TimeWeighted { window: 3600 };
+        assert_eq!(model.rate(&ctx()).unwrap(), Decimal::percent(6));
+    }
+
+    #[test]
+    fn rate_average() {
+        let average = RateAverage {
+            time: Timestamp::from_seconds(1000),
+            rate: Decimal::percent(20),
+            average: Decimal::percent(10),
+        };
+
+        assert_eq!(
+            average.average_at(Timestamp::from_seconds(1000), 100),
+            Decimal::percent(10)
+        );
+        // Half way through the window
+        assert_eq!(
+            average.average_at(Timestamp::from_seconds(1050), 100),
+            Decimal::percent(15)
+        );
+        // Beyond the window the average converges on the last rate
+        assert_eq!(
+            average.average_at(Timestamp::from_seconds(5000), 100),
+            Decimal::percent(20)
+        );
+
+        let falling = RateAverage {
+            rate: Decimal::percent(4),
+            ..average
+        };
+        assert_eq!(
+            falling.average_at(Timestamp::from_seconds(1050), 100),
+            Decimal::percent(7)
+        );
+    }
+}
@@ -1,54 +1,117 @@
-use cosmwasm_std::{Addr, CustomQuery, Decimal, QuerierWrapper, StdResult};
+use cosmwasm_std::{Addr, CustomQuery, Decimal, QuerierWrapper, StdError};
 use monetary::Rate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     adapter::{Adapter, Unstake},
     denoms::{Ask, Base, Debt, Rcpt},
+    ContractError,
 };
 
 pub struct Rates {
     pub vault_debt: Rate<Base, Debt>,
     pub vault_deposit: Rate<Base, Rcpt>,
     pub vault_interest: Decimal,
+    pub vault_utilization: Decimal,
     pub vault_max_interest: Decimal,
+    /// The utilization to interest rate curve published by the vault, if available.
+    /// `None` when `vault_max_interest` is the configured fallback
+    pub vault_interest_curve: Option<Vec<(Decimal, Decimal)>>,
     pub provider_redemption: Rate<Base, Ask>,
 }
 
-impl Rates {
+/// The rates of the GHOST vault alone, which are shared by every route
+pub struct VaultRates {
+    pub vault_debt: Rate<Base, Debt>,
+    pub vault_deposit: Rate<Base, Rcpt>,
+    pub vault_interest: Decimal,
+    pub vault_utilization: Decimal,
+}
+
+impl VaultRates {
     pub fn load<C: CustomQuery>(
         query: QuerierWrapper<C>,
-        adapter: &Adapter,
         vault: &Addr,
-    ) -> StdResult<Self> {
+    ) -> Result<Self, ContractError> {
         let status: kujira_ghost::receipt_vault::StatusResponse = query.query_wasm_smart(
             vault.to_string(),
             &kujira_ghost::receipt_vault::QueryMsg::Status {},
         )?;
 
+        Ok(Self {
+            vault_debt: rate(status.debt_share_ratio)?,
+            vault_deposit: rate(status.deposit_redemption_ratio)?,
+            vault_interest: status.rate,
+            vault_utilization: Decimal::checked_from_ratio(status.borrowed, status.deposited)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl Rates {
+    pub fn load<C: CustomQuery>(
+        query: QuerierWrapper<C>,
+        adapter: &Adapter,
+        vault: &Addr,
+        max_interest_fallback: Decimal,
+    ) -> Result<Self, ContractError> {
+        let vault_rates = VaultRates::load(query, vault)?;
         let provider_redemption = adapter.redemption_rate(query)?;
 
-        // TODO: Publish & use new interest rate params
-        // let rates: kujira_ghost::receipt_vault::InterestParamsResponse = query.query_wasm_smart(
-        //     self.vault.to_string(),
-        //     &kujira_ghost::receipt_vault::QueryMsg::InterestParams {},
-        // )?;
+        // Older vaults don't publish their interest curve, in which case we size the reserve
+        // requirement against the configured ceiling instead. Any other failure is propagated
+        let vault_interest_curve = match query.query_wasm_smart::<InterestParamsResponse>(
+            vault.to_string(),
+            &kujira_ghost::receipt_vault::QueryMsg::InterestParams {},
+        ) {
+            Ok(x) => Some(x.utilization_to_rate).filter(|x| !x.is_empty()),
+            Err(err) if is_unsupported(&err) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        let vault_max_interest = vault_interest_curve
+            .as_ref()
+            .and_then(|curve| curve.iter().map(|(_, rate)| *rate).max())
+            .unwrap_or(max_interest_fallback);
 
         Ok(Self {
-            vault_debt: Rate::new(status.debt_share_ratio).unwrap(),
-            vault_deposit: Rate::new(status.deposit_redemption_ratio).unwrap(),
-            vault_interest: status.rate,
-            vault_max_interest: Decimal::from_ratio(3u128, 1u128),
-            provider_redemption: Rate::new(provider_redemption).unwrap(),
+            vault_debt: vault_rates.vault_debt,
+            vault_deposit: vault_rates.vault_deposit,
+            vault_interest: vault_rates.vault_interest,
+            vault_utilization: vault_rates.vault_utilization,
+            vault_max_interest,
+            vault_interest_curve,
+            provider_redemption: rate(provider_redemption)?,
         })
     }
 }
 
+/// A zero ratio reported by the vault or provider can't be priced against
+fn rate<A, B>(value: Decimal) -> Result<Rate<A, B>, ContractError> {
+    Rate::new(value).ok_or(ContractError::ZeroRate {})
+}
+
+/// Whether `err` is a contract rejecting a query variant that it doesn't implement
+fn is_unsupported(err: &StdError) -> bool {
+    err.to_string().contains("unknown variant")
+}
+
+/// The GHOST receipt vault's response to `QueryMsg::InterestParams`.
+/// Only the fields required to bound the borrow rate are deserialized.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InterestParamsResponse {
+    /// Piecewise-linear curve of (utilization, interest rate) points
+    pub utilization_to_rate: Vec<(Decimal, Decimal)>,
+}
+
 impl From<Rates> for String {
     fn from(value: Rates) -> Self {
         format!(
-            "vault_debt:{},vault_interest:{},vault_max_interest:{},provider_redemption:{}",
+            "vault_debt:{},vault_interest:{},vault_utilization:{},vault_max_interest:{},provider_redemption:{}",
             value.vault_debt,
             value.vault_interest,
+            value.vault_utilization,
             value.vault_max_interest,
             value.provider_redemption
         )
@@ -1,9 +1,11 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Timestamp};
 use kujira::CallbackData;
 use monetary::{AmountU128, Denom, Rate};
 
 use crate::denoms::{Base, LegacyRsv, Rcpt, Rsv};
+use crate::ownership::PendingOwner;
+use crate::timelock::PendingChangesResponse;
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -12,6 +14,10 @@ pub struct InstantiateMsg {
     pub base_denom: Denom<Base>,
     /// The address of the associated GHOST vault
     pub ghost_vault_addr: Addr,
+    /// The address that can pause operations in an emergency, alongside the owner
+    pub guardian: Option<Addr>,
+    /// The minimum notice in seconds given of a `Change`. Defaults to 1 day
+    pub timelock: Option<u64>,
 }
 
 #[cw_serde]
@@ -31,15 +37,43 @@ pub enum ExecuteMsg {
         original_amount: AmountU128<Base>,
         callback: Option<CallbackData>,
     },
-    /// Add the specified controller to the whitelist.
-    AddController {
-        controller: Addr,
-        limit: Option<AmountU128<Base>>,
+    /// Schedule a change to take effect at `effective`, which must be at least `timelock`
+    /// seconds away. Defaults to the earliest time allowed
+    ScheduleChange {
+        change: Change,
+        effective: Option<Timestamp>,
     },
+    /// Apply a scheduled change that has reached its effective time, within
+    /// `timelock::EXECUTION_WINDOW` of it. Callable by anyone
+    ExecuteChange { id: u64 },
+    /// Cancel a scheduled change
+    CancelChange { id: u64 },
     /// Remove the specified controller from the whitelist.
     RemoveController { controller: Addr },
     /// Update the Reserve config
-    UpdateConfig { owner: Option<Addr> },
+    UpdateConfig { guardian: Option<Addr> },
+
+    /// Pause operations. Callable by the guardian or owner
+    Pause { operations: Vec<Operation> },
+
+    /// Resume paused operations. Callable by the owner
+    Unpause { operations: Vec<Operation> },
+
+    /// Remove the guardian, leaving only the owner able to pause operations
+    RemoveGuardian {},
+
+    /// Propose a new owner, who must accept the transfer before `expiry`. Replaces any
+    /// existing proposal
+    ProposeOwner {
+        owner: Addr,
+        expiry: Option<Timestamp>,
+    },
+
+    /// Accept a proposed ownership transfer. Callable by the proposed owner
+    AcceptOwner {},
+
+    /// Withdraw a proposed ownership transfer. Callable by the owner
+    CancelOwner {},
 
     /// Migration Utility for legacy controller denoms
     MigrateLegacyReserve {
@@ -52,6 +86,28 @@ pub enum ExecuteMsg {
     ExchangeLegacyReserve {},
 }
 
+/// The parameters that can only be changed with notice
+#[cw_serde]
+pub enum Change {
+    /// Add the specified controller to the whitelist, or update its limit
+    Controller {
+        controller: Addr,
+        limit: Option<AmountU128<Base>>,
+    },
+    /// Update the notice required of later changes. A reduction only applies once the previous
+    /// delay has passed
+    Timelock { delay: u64 },
+}
+
+/// The operations that can be paused. Reserves can always be returned, so that outstanding
+/// positions can unwind
+#[cw_serde]
+pub enum Operation {
+    Fund,
+    Withdraw,
+    RequestReserves,
+}
+
 #[cw_serde]
 pub enum CallbackType {}
 
@@ -64,6 +120,9 @@ pub enum QueryMsg {
     Whitelist {},
     #[returns(ConfigResponse)]
     Config {},
+    /// Changes scheduled by the owner, in the order they were scheduled
+    #[returns(PendingChangesResponse<Change>)]
+    PendingChanges {},
 }
 
 #[cw_serde]
@@ -73,6 +132,10 @@ pub struct ConfigResponse {
     pub rsv_denom: Denom<Rsv>,
     pub ghost_denom: Denom<Rcpt>,
     pub ghost_vault_addr: Addr,
+    pub guardian: Option<Addr>,
+    pub paused: Vec<Operation>,
+    pub pending_owner: Option<PendingOwner>,
+    pub timelock: u64,
 }
 
 #[cw_serde]
@@ -0,0 +1,157 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, Order, StdResult, Storage, Timestamp};
+use cw_storage_plus::{Item, Map};
+use serde::{de::DeserializeOwned, Serialize};
+use std::cmp::max;
+
+use crate::ContractError;
+
+/// The delay in seconds that changes are scheduled with, unless configured otherwise
+pub const DEFAULT_DELAY: u64 = 24 * 60 * 60;
+
+/// The time in seconds after its effective time that a change can still be executed, after
+/// which it lapses and can only be cancelled
+pub const EXECUTION_WINDOW: u64 = 14 * 24 * 60 * 60;
+
+/// The longest delay that changes can be scheduled with
+pub const MAX_DELAY: u64 = 30 * 24 * 60 * 60;
+
+#[cw_serde]
+pub struct PendingChange<T> {
+    pub id: u64,
+    pub change: T,
+    /// The time from which the change can be executed
+    pub effective: Timestamp,
+}
+
+#[cw_serde]
+pub struct PendingChangesResponse<T> {
+    pub changes: Vec<PendingChange<T>>,
+}
+
+/// Parameter changes scheduled by the owner, held until their effective time so that users and
+/// integrators have notice of them
+pub struct Timelock<T> {
+    changes: Map<u64, PendingChange<T>>,
+    next_id: Item<u64>,
+    // The delay in force before it was last reduced, and the time until which it still applies
+    reduction: Item<(u64, Timestamp)>,
+}
+
+impl<T: Serialize + DeserializeOwned> Timelock<T> {
+    pub const fn new() -> Self {
+        Self {
+            changes: Map::new("timelock_changes"),
+            next_id: Item::new("timelock_next_id"),
+            reduction: Item::new("timelock_reduction"),
+        }
+    }
+
+    /// The delay that changes scheduled at `now` are held for, given the configured `delay`. A
+    /// reduced delay only applies once the previous one has passed since the reduction, so that
+    /// changes can't be rushed through on the back of it
+    pub fn delay(&self, storage: &dyn Storage, delay: u64, now: Timestamp) -> StdResult<u64> {
+        Ok(match self.reduction.may_load(storage)? {
+            Some((previous, until)) if now < until => max(delay, previous),
+            _ => delay,
+        })
+    }
+
+    /// Records a change of the configured delay from `previous` to `delay` at `now`
+    pub fn update_delay(
+        &self,
+        storage: &mut dyn Storage,
+        previous: u64,
+        delay: u64,
+        now: Timestamp,
+    ) -> StdResult<()> {
+        if delay < previous {
+            let previous = self.delay(storage, previous, now)?;
+            self.reduction
+                .save(storage, &(previous, now.plus_seconds(previous)))?;
+        }
+        Ok(())
+    }
+
+    /// Queues `change` to take effect at `effective`, which must be at least the configured
+    /// `delay` seconds from `now`, or longer while a reduction of it is waited out. Defaults to
+    /// the earliest time allowed
+    pub fn schedule(
+        &self,
+        storage: &mut dyn Storage,
+        change: T,
+        effective: Option<Timestamp>,
+        delay: u64,
+        now: Timestamp,
+    ) -> Result<PendingChange<T>, ContractError> {
+        let earliest = now.plus_seconds(self.delay(storage, delay, now)?);
+        let effective = effective.unwrap_or(earliest);
+        ensure!(effective >= earliest, ContractError::TimelockTooShort {});
+
+        let id = self.next_id.may_load(storage)?.unwrap_or_default();
+        self.next_id.save(storage, &(id + 1))?;
+        let pending = PendingChange {
+            id,
+            change,
+            effective,
+        };
+        self.changes.save(storage, id, &pending)?;
+        Ok(pending)
+    }
+
+    /// Removes and returns a change that has reached its effective time, and hasn't lapsed
+    pub fn take(
+        &self,
+        storage: &mut dyn Storage,
+        id: u64,
+        now: Timestamp,
+    ) -> Result<T, ContractError> {
+        let pending = self.load(storage, id)?;
+        ensure!(
+            now >= pending.effective,
+            ContractError::TimelockNotElapsed {}
+        );
+        ensure!(
+            now.seconds() <= pending.effective.seconds().saturating_add(EXECUTION_WINDOW),
+            ContractError::ChangeExpired { id }
+        );
+        self.changes.remove(storage, id);
+        Ok(pending.change)
+    }
+
+    /// Removes a change before it has been executed
+    pub fn cancel(&self, storage: &mut dyn Storage, id: u64) -> Result<T, ContractError> {
+        let pending = self.load(storage, id)?;
+        self.changes.remove(storage, id);
+        Ok(pending.change)
+    }
+
+    /// All scheduled changes, in the order they were scheduled
+    pub fn all(&self, storage: &dyn Storage) -> StdResult<Vec<PendingChange<T>>> {
+        self.changes
+            .range(storage, None, None, Order::Ascending)
+            .map(|x| x.map(|(_, pending)| pending))
+            .collect()
+    }
+
+    fn load(&self, storage: &dyn Storage, id: u64) -> Result<PendingChange<T>, ContractError> {
+        self.changes
+            .may_load(storage, id)?
+            .ok_or(ContractError::UnknownChange { id })
+    }
+}
+
+/// Errors if `delay` is longer than `MAX_DELAY`
+pub fn validate_delay(delay: u64) -> Result<(), ContractError> {
+    ensure!(
+        delay <= MAX_DELAY,
+        ContractError::InvalidTimelock { max: MAX_DELAY }
+    );
+    Ok(())
+}
+
+impl<T: Serialize + DeserializeOwned> Default for Timelock<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}